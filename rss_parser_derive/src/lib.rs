@@ -0,0 +1,178 @@
+//! `#[derive(GradualRssItem)]` for [rss_parser](https://docs.rs/rss_parser).
+//!
+//! Every named field is filled from the item child element of the same name
+//! (lowercased, like the tags the parser hands to `populate`). Field attributes:
+//!
+//! - `#[rss(tag = "pubDate")]` reads a differently named element.
+//! - `#[rss(cdata)]` only takes the element's CDATA section.
+//! - `#[rss(attr = "url")]` reads an attribute of the element instead of its text.
+//! - `#[rss(skip)]` leaves the field at its `Default` value.
+//!
+//! `Option<T>` fields keep the last value seen, `Vec<T>` fields collect every
+//! occurrence and any other type is overwritten in place. Values are converted
+//! with `FromStr`; values that fail to parse are ignored.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, GenericArgument, LitStr, PathArguments, Type, parse_macro_input};
+
+#[proc_macro_derive(GradualRssItem, attributes(rss))]
+pub fn derive_gradual_rss_item(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input).unwrap_or_else(|err| err.to_compile_error()).into()
+}
+
+enum Source {
+    Text,
+    CData,
+    Attr(String),
+}
+
+enum Shape {
+    Option,
+    Vec,
+    Plain,
+}
+
+struct FieldMapping {
+    ident: syn::Ident,
+    tag: String,
+    source: Source,
+    shape: Shape,
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    name,
+                    "GradualRssItem can only be derived for structs with named fields",
+                ));
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "GradualRssItem can only be derived for structs",
+            ));
+        }
+    };
+
+    let mut idents = Vec::new();
+    let mut mappings = Vec::new();
+    for field in fields {
+        let ident = field.ident.clone().expect("named field");
+        idents.push(ident.clone());
+        if let Some(mapping) = parse_field(ident, field)? {
+            mappings.push(mapping);
+        }
+    }
+
+    // Several fields may read from the same element (its text and its attributes),
+    // so group them into a single match arm per tag.
+    let mut tags: Vec<String> = Vec::new();
+    for mapping in &mappings {
+        if !tags.contains(&mapping.tag) {
+            tags.push(mapping.tag.clone());
+        }
+    }
+    let arms = tags.iter().map(|tag| {
+        let statements = mappings.iter().filter(|m| &m.tag == tag).map(assign);
+        quote! { #tag => { #(#statements)* } }
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::rss_parser::GradualRssItem for #name #ty_generics #where_clause {
+            fn init() -> Self {
+                #name {
+                    #(#idents: ::core::default::Default::default(),)*
+                }
+            }
+
+            fn populate(&mut self, node: ::rss_parser::XmlNode) {
+                match node.tag.as_str() {
+                    #(#arms)*
+                    _ => {}
+                }
+            }
+        }
+    })
+}
+
+fn parse_field(ident: syn::Ident, field: &syn::Field) -> syn::Result<Option<FieldMapping>> {
+    let mut tag = None;
+    let mut source = Source::Text;
+    let mut skip = false;
+
+    for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("rss")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("tag") {
+                let value: LitStr = meta.value()?.parse()?;
+                tag = Some(value.value());
+            } else if meta.path.is_ident("attr") {
+                let value: LitStr = meta.value()?.parse()?;
+                source = Source::Attr(value.value());
+            } else if meta.path.is_ident("cdata") {
+                source = Source::CData;
+            } else if meta.path.is_ident("skip") {
+                skip = true;
+            } else {
+                return Err(meta.error("expected `tag`, `attr`, `cdata` or `skip`"));
+            }
+            Ok(())
+        })?;
+    }
+
+    if skip {
+        return Ok(None);
+    }
+
+    let tag = tag.unwrap_or_else(|| ident.to_string()).to_lowercase();
+    let shape = shape_of(&field.ty);
+    Ok(Some(FieldMapping { ident, tag, source, shape }))
+}
+
+fn shape_of(ty: &Type) -> Shape {
+    let Type::Path(path) = ty else {
+        return Shape::Plain;
+    };
+    let Some(last) = path.path.segments.last() else {
+        return Shape::Plain;
+    };
+    let has_argument = matches!(
+        &last.arguments,
+        PathArguments::AngleBracketed(args) if args.args.iter().any(|arg| matches!(arg, GenericArgument::Type(_)))
+    );
+    match last.ident.to_string().as_str() {
+        "Option" if has_argument => Shape::Option,
+        "Vec" if has_argument => Shape::Vec,
+        _ => Shape::Plain,
+    }
+}
+
+fn assign(mapping: &FieldMapping) -> TokenStream2 {
+    let ident = &mapping.ident;
+    let raw = match &mapping.source {
+        Source::Text => quote! { node.value.as_deref().or(node.cdata.as_deref()) },
+        Source::CData => quote! { node.cdata.as_deref() },
+        Source::Attr(name) => quote! { node.attr(#name) },
+    };
+    let store = match mapping.shape {
+        Shape::Option => quote! { self.#ident = ::core::option::Option::Some(parsed); },
+        Shape::Vec => quote! { self.#ident.push(parsed); },
+        Shape::Plain => quote! { self.#ident = parsed; },
+    };
+    quote! {
+        if let ::core::option::Option::Some(raw) = #raw {
+            if let ::core::result::Result::Ok(parsed) = raw.parse() {
+                #store
+            }
+        }
+    }
+}
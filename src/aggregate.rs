@@ -0,0 +1,377 @@
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use tokio::io::AsyncRead;
+use tokio_stream::{Stream, StreamMap};
+
+#[cfg(feature = "dates")]
+use crate::atom::AtomEntry;
+#[cfg(feature = "dates")]
+use crate::date::{parse_date, DateTime};
+use crate::error::RssError;
+#[cfg(feature = "dates")]
+use crate::feed_item::FeedItem;
+#[cfg(feature = "dates")]
+use crate::item::RssItem;
+use crate::rss_parser::{GradualRssItem, RssParser};
+
+const DEFAULT_CONCURRENCY: usize = 8;
+
+/// Identifies a source of an `Aggregator`, numbered in the order they were added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FeedId(pub usize);
+
+type Items<T> = Pin<Box<dyn Stream<Item = Result<T, RssError>> + Send>>;
+type Opening<T> = Pin<Box<dyn Future<Output = Result<Items<T>, RssError>> + Send>>;
+
+/// Reads many feeds at once and merges their items into one `Stream` of
+/// `(FeedId, Result<T, RssError>)`, in whatever order they arrive.
+///
+/// At most `concurrency` feeds (8 by default) are open at a time; the others
+/// wait their turn in the order they were added. A feed's errors are yielded
+/// with its id and never affect the other feeds. Malformed items are skipped
+/// as with `RssParser::next`; any other error (failing to connect, I/O, a
+/// configured limit) ends that feed.
+pub struct Aggregator<T> {
+    queued: VecDeque<(FeedId, Opening<T>)>,
+    running: StreamMap<FeedId, Source<T>>,
+    concurrency: usize,
+    next_id: usize,
+}
+
+impl<T: GradualRssItem + Send + 'static> Aggregator<T> {
+    pub fn new() -> Self {
+        Aggregator {
+            queued: VecDeque::new(),
+            running: StreamMap::new(),
+            concurrency: DEFAULT_CONCURRENCY,
+            next_id: 0,
+        }
+    }
+
+    /// How many feeds are read at the same time; at least one.
+    pub fn concurrency(mut self, feeds: usize) -> Self {
+        self.concurrency = feeds.max(1);
+        self
+    }
+
+    /// Adds a feed to be parsed with the default options.
+    pub fn add_reader<R: AsyncRead + Unpin + Send + 'static>(&mut self, input: R) -> FeedId {
+        self.add(Box::pin(async move {
+            let parser = RssParser::<T, R>::new(input).await?;
+            Ok(Box::pin(parser.into_stream()) as Items<T>)
+        }))
+    }
+
+    /// Adds a feed with a parser configured through `RssParser::builder`.
+    pub fn add_parser<R: AsyncRead + Unpin + Send + 'static>(&mut self, parser: RssParser<T, R>) -> FeedId {
+        self.add(Box::pin(async move { Ok(Box::pin(parser.into_stream()) as Items<T>) }))
+    }
+
+    /// Adds a feed that is fetched with `RssParser::from_url` when its turn comes.
+    #[cfg(feature = "http")]
+    pub fn add_url(&mut self, url: &str) -> FeedId {
+        self.add_url_with(url, &crate::http::FetchOptions::default())
+    }
+
+    /// Like `add_url`, fetching with `options`, e.g. with a `TlsConnector`
+    /// for `https://` feeds. A `304 Not Modified` is an `HttpError::Status`.
+    #[cfg(feature = "http")]
+    pub fn add_url_with(&mut self, url: &str, options: &crate::http::FetchOptions) -> FeedId {
+        let url = url.to_string();
+        let options = options.clone();
+        self.add(Box::pin(async move {
+            let parser = match RssParser::<T, crate::http::HttpBody>::from_url_with(&url, &options).await? {
+                crate::http::FetchResult::Modified { parser, .. } => parser,
+                crate::http::FetchResult::NotModified => return Err(RssError::Http(crate::http::HttpError::Status(304))),
+            };
+            Ok(Box::pin(parser.into_stream()) as Items<T>)
+        }))
+    }
+
+    fn add(&mut self, opening: Opening<T>) -> FeedId {
+        let id = FeedId(self.next_id);
+        self.next_id += 1;
+        self.queued.push_back((id, opening));
+        id
+    }
+
+    /// Feeds still being read or waiting to be.
+    pub fn remaining(&self) -> usize {
+        self.queued.len() + self.running.len()
+    }
+
+    /// Merges the feeds into one timeline, newest first, like a news reader's
+    /// home page; see `Timeline`.
+    #[cfg(feature = "dates")]
+    pub fn by_date(self, lookahead: usize) -> Timeline<T>
+    where
+        T: Dated,
+    {
+        Timeline {
+            aggregator: self,
+            lookahead: lookahead.max(1),
+            buffered: Vec::new(),
+            done: false,
+        }
+    }
+
+    fn poll_event(&mut self, cx: &mut Context<'_>) -> Poll<Event<T>> {
+        loop {
+            while self.running.len() < self.concurrency
+                && let Some((id, opening)) = self.queued.pop_front()
+            {
+                self.running.insert(id, Source::Opening(opening));
+            }
+            match Pin::new(&mut self.running).poll_next(cx) {
+                Poll::Ready(Some((id, Some(item)))) => return Poll::Ready(Event::Item(id, item)),
+                // A feed ended, which frees a slot for the next one.
+                Poll::Ready(Some((id, None))) => {
+                    self.running.remove(&id);
+                    return Poll::Ready(Event::Ended);
+                }
+                Poll::Ready(None) if self.queued.is_empty() => return Poll::Ready(Event::Done),
+                Poll::Ready(None) => {}
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+enum Event<T> {
+    Item(FeedId, Result<T, RssError>),
+    Ended,
+    Done,
+}
+
+impl<T: GradualRssItem + Send + 'static> Default for Aggregator<T> {
+    fn default() -> Self {
+        Aggregator::new()
+    }
+}
+
+impl<T> Unpin for Aggregator<T> {}
+
+impl<T: GradualRssItem + Send + 'static> Stream for Aggregator<T> {
+    type Item = (FeedId, Result<T, RssError>);
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            match this.poll_event(cx) {
+                Poll::Ready(Event::Item(id, item)) => return Poll::Ready(Some((id, item))),
+                Poll::Ready(Event::Ended) => {}
+                Poll::Ready(Event::Done) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// Items that can be put on a `Timeline`.
+#[cfg(feature = "dates")]
+pub trait Dated {
+    fn date(&self) -> Option<DateTime>;
+}
+
+/// The `pubDate` (or `dc:date`).
+#[cfg(feature = "dates")]
+impl Dated for RssItem {
+    fn date(&self) -> Option<DateTime> {
+        self.parsed_pub_date()
+    }
+}
+
+/// The `updated` date, else `published`.
+#[cfg(feature = "dates")]
+impl Dated for AtomEntry {
+    fn date(&self) -> Option<DateTime> {
+        self.updated.as_deref().or(self.published.as_deref()).and_then(parse_date)
+    }
+}
+
+/// The published date, else updated.
+#[cfg(feature = "dates")]
+impl Dated for FeedItem {
+    fn date(&self) -> Option<DateTime> {
+        self.published.as_deref().or(self.updated.as_deref()).and_then(parse_date)
+    }
+}
+
+/// An `Aggregator`'s items newest first, from `Aggregator::by_date`.
+///
+/// A k-way merge: an item is released once every open feed has `lookahead`
+/// items buffered (or has ended) and it is the newest of them all. Feeds are
+/// expected to list their items roughly newest first; an item up to
+/// `lookahead` places out of order in its own feed still lands in the right
+/// spot. Only open feeds take part, so the concurrency limit should cover all
+/// of them for a single timeline. Undated items and errors are passed on
+/// straight away.
+#[cfg(feature = "dates")]
+pub struct Timeline<T> {
+    aggregator: Aggregator<T>,
+    lookahead: usize,
+    buffered: Vec<(FeedId, DateTime, T)>,
+    done: bool,
+}
+
+#[cfg(feature = "dates")]
+impl<T> Unpin for Timeline<T> {}
+
+#[cfg(feature = "dates")]
+impl<T: GradualRssItem + Dated + Send + 'static> Timeline<T> {
+    // The newest buffered item, if every open feed has enough of them buffered
+    // for it to be final.
+    fn release(&mut self) -> Option<(FeedId, T)> {
+        let complete = self.done
+            || self.aggregator.running.keys().all(|id| {
+                self.buffered.iter().filter(|(feed, ..)| feed == id).count() >= self.lookahead
+            });
+        if !complete {
+            return None;
+        }
+        let newest = (0..self.buffered.len()).max_by_key(|index| self.buffered[*index].1)?;
+        let (id, _, item) = self.buffered.swap_remove(newest);
+        Some((id, item))
+    }
+}
+
+#[cfg(feature = "dates")]
+impl<T: GradualRssItem + Dated + Send + 'static> Stream for Timeline<T> {
+    type Item = (FeedId, Result<T, RssError>);
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some((id, item)) = this.release() {
+                return Poll::Ready(Some((id, Ok(item))));
+            }
+            if this.done {
+                return Poll::Ready(None);
+            }
+            match this.aggregator.poll_event(cx) {
+                Poll::Ready(Event::Item(id, Ok(item))) => match item.date() {
+                    Some(date) => this.buffered.push((id, date, item)),
+                    None => return Poll::Ready(Some((id, Ok(item)))),
+                },
+                Poll::Ready(Event::Item(id, Err(err))) => return Poll::Ready(Some((id, Err(err)))),
+                Poll::Ready(Event::Ended) => {}
+                Poll::Ready(Event::Done) => this.done = true,
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+// One feed of an aggregator. Yields `None` once when it is done, so the
+// aggregator learns about it even while every other feed is pending.
+enum Source<T> {
+    Opening(Opening<T>),
+    Running(Items<T>),
+    Done,
+}
+
+impl<T> Unpin for Source<T> {}
+
+impl<T> Stream for Source<T> {
+    type Item = Option<Result<T, RssError>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            match this {
+                Source::Opening(opening) => match opening.as_mut().poll(cx) {
+                    Poll::Ready(Ok(items)) => *this = Source::Running(items),
+                    Poll::Ready(Err(err)) => {
+                        *this = Source::Done;
+                        return Poll::Ready(Some(Some(Err(err))));
+                    }
+                    Poll::Pending => return Poll::Pending,
+                },
+                Source::Running(items) => {
+                    return match items.as_mut().poll_next(cx) {
+                        Poll::Ready(Some(Ok(item))) => Poll::Ready(Some(Some(Ok(item)))),
+                        Poll::Ready(Some(Err(err))) => {
+                            if err.is_fatal() {
+                                *this = Source::Done;
+                            }
+                            Poll::Ready(Some(Some(Err(err))))
+                        }
+                        Poll::Ready(None) => {
+                            *this = Source::Done;
+                            Poll::Ready(Some(None))
+                        }
+                        Poll::Pending => Poll::Pending,
+                    };
+                }
+                Source::Done => return Poll::Ready(Some(None)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RssItem;
+    use std::io::Cursor;
+    use tokio_stream::StreamExt;
+
+    fn feed(titles: &[&str]) -> Cursor<Vec<u8>> {
+        let items: String = titles.iter().map(|title| format!("<item><title>{}</title></item>", title)).collect();
+        Cursor::new(format!("<rss><channel>{}</channel></rss>", items).into_bytes())
+    }
+
+    #[tokio::test]
+    async fn test_merges_feeds_and_isolates_failures() {
+        let mut aggregator = Aggregator::<RssItem>::new().concurrency(2);
+        let a = aggregator.add_reader(feed(&["a1", "a2"]));
+        let broken = aggregator.add_reader(Cursor::new(b"<rss><channel><item><title>x</b></item><item><title>b1</title></item>".to_vec()));
+        let c = aggregator.add_reader(feed(&["c1"]));
+        assert_eq!(aggregator.remaining(), 3);
+
+        let mut titles = Vec::new();
+        let mut errors = Vec::new();
+        while let Some((id, item)) = aggregator.next().await {
+            match item {
+                Ok(item) => titles.push((id, item.title.unwrap())),
+                Err(err) => errors.push((id, err)),
+            }
+        }
+        titles.sort();
+        assert_eq!(
+            titles,
+            vec![
+                (a, "a1".to_string()),
+                (a, "a2".to_string()),
+                (broken, "b1".to_string()),
+                (c, "c1".to_string())
+            ]
+        );
+        assert!(matches!(errors.as_slice(), [(id, RssError::Xml { .. })] if *id == broken), "{:?}", errors);
+        assert_eq!(aggregator.remaining(), 0);
+    }
+
+    #[cfg(feature = "dates")]
+    #[tokio::test]
+    async fn test_timeline() {
+        fn dated(items: &[(&str, u8)]) -> Cursor<Vec<u8>> {
+            let items: String = items
+                .iter()
+                .map(|(title, day)| format!("<item><title>{}</title><pubDate>2024-01-{:02}T00:00:00Z</pubDate></item>", title, day))
+                .collect();
+            Cursor::new(format!("<rss><channel>{}</channel></rss>", items).into_bytes())
+        }
+
+        let mut aggregator = Aggregator::<RssItem>::new();
+        aggregator.add_reader(dated(&[("a9", 9), ("a5", 5), ("a1", 1)]));
+        // One item out of order, within the look-ahead.
+        aggregator.add_reader(dated(&[("b7", 7), ("b8", 8), ("b2", 2)]));
+        aggregator.add_reader(feed(&["undated"]));
+
+        let titles: Vec<String> = aggregator.by_date(2).map(|(_, item)| item.unwrap().title.unwrap()).collect().await;
+        assert_eq!(titles, vec!["undated", "a9", "b8", "b7", "a5", "b2", "a1"]);
+    }
+}
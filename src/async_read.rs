@@ -0,0 +1,53 @@
+// The `AsyncRead` the parser reads through. Without the `tokio` feature it is
+// a stand-in with just what the parser's own readers use, and the only inputs
+// are the blocking readers of `SyncRssParser`, which are never pending.
+
+#[cfg(feature = "tokio")]
+pub(crate) use tokio::io::{AsyncRead, ReadBuf};
+
+#[cfg(not(feature = "tokio"))]
+pub(crate) use standalone::{AsyncRead, ReadBuf};
+
+#[cfg(not(feature = "tokio"))]
+mod standalone {
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    pub trait AsyncRead {
+        fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<std::io::Result<()>>;
+    }
+
+    /// Like Tokio's, over a buffer that is already initialized.
+    pub struct ReadBuf<'a> {
+        buf: &'a mut [u8],
+        filled: usize,
+    }
+
+    impl<'a> ReadBuf<'a> {
+        pub fn new(buf: &'a mut [u8]) -> Self {
+            ReadBuf { buf, filled: 0 }
+        }
+
+        pub fn filled(&self) -> &[u8] {
+            &self.buf[..self.filled]
+        }
+
+        pub fn remaining(&self) -> usize {
+            self.buf.len() - self.filled
+        }
+
+        pub fn initialize_unfilled(&mut self) -> &mut [u8] {
+            &mut self.buf[self.filled..]
+        }
+
+        pub fn advance(&mut self, n: usize) {
+            assert!(n <= self.remaining(), "filled past the end of the buffer");
+            self.filled += n;
+        }
+
+        pub fn put_slice(&mut self, src: &[u8]) {
+            self.initialize_unfilled()[..src.len()].copy_from_slice(src);
+            self.filled += src.len();
+        }
+    }
+}
@@ -0,0 +1,299 @@
+use serde::Serialize;
+
+use crate::channel::ChannelInfo;
+use crate::enclosure::MimeType;
+use crate::html::first_image;
+use crate::item::Category;
+use crate::media::Media;
+use crate::person::Person;
+use crate::rss_parser::{GradualRssItem, XmlNode};
+use crate::url::absolute;
+
+/// An Atom `<link>`. An absent `rel` is read as `alternate`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Link {
+    pub rel: String,
+    pub href: String,
+    pub mime_type: Option<MimeType>,
+    pub title: Option<String>,
+}
+
+impl Link {
+    pub(crate) fn from_node(node: &XmlNode) -> Option<Self> {
+        Some(Link {
+            rel: node.attr("rel").unwrap_or("alternate").trim().to_string(),
+            href: absolute(node.base.as_deref(), node.attr("href")?.trim().to_string()),
+            mime_type: node.attr("type").and_then(MimeType::parse),
+            title: node.attr("title").map(String::from),
+        })
+    }
+
+    /// Picks the link pointing at the page itself: the first HTML (or untyped)
+    /// alternate link, otherwise the first alternate link of any type.
+    pub fn canonical(links: &[Link]) -> Option<&Link> {
+        let mut alternates = links.iter().filter(|link| link.rel == "alternate");
+        let is_html = |link: &&Link| {
+            link.mime_type
+                .as_ref()
+                .is_none_or(|mime| mime.subtype == "html" || mime.subtype == "xhtml+xml")
+        };
+        alternates.clone().find(is_html).or_else(|| alternates.next())
+    }
+}
+
+/// The common Atom 1.0 `<entry>` elements, for use with `RssParser::new_atom`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct AtomEntry {
+    pub id: Option<String>,
+    pub title: Option<String>,
+    /// The canonical alternate link's `href`, see `Link::canonical`.
+    pub link: Option<String>,
+    pub links: Vec<Link>,
+    pub summary: Option<String>,
+    pub content: Option<String>,
+    pub authors: Vec<Person>,
+    pub categories: Vec<Category>,
+    pub published: Option<String>,
+    pub updated: Option<String>,
+    pub rights: Option<String>,
+    /// The entry's `xml:lang`, which it may inherit from `<feed>`.
+    pub language: Option<String>,
+    pub media: Media,
+    #[serde(skip)]
+    pending_author: Person,
+}
+
+impl GradualRssItem for AtomEntry {
+    fn init() -> Self {
+        AtomEntry::default()
+    }
+
+    fn populate(&mut self, node: XmlNode) {
+        self.media.populate(&node);
+        if node.path.is_empty() {
+            self.language = node.lang.clone();
+        }
+        // Only direct children describe the entry; `<source>` and `<contributor>`
+        // nest elements with the same names.
+        match node.path.as_str() {
+            "id" => self.id = node.into_text(),
+            "title" => self.title = text_construct(node),
+            "link" => {
+                self.links.extend(Link::from_node(&node));
+                self.link = Link::canonical(&self.links).map(|link| link.href.clone());
+            }
+            "summary" => self.summary = text_construct(node),
+            "content" => self.content = text_construct(node),
+            // The author's children arrive before the `<author>` element itself.
+            "author/name" => self.pending_author.name = node.into_text(),
+            "author/email" => self.pending_author.email = node.into_text(),
+            "author/uri" => self.pending_author.uri = node.into_text(),
+            "author" => {
+                let author = std::mem::take(&mut self.pending_author);
+                if !author.is_empty() {
+                    self.authors.push(author);
+                }
+            }
+            "category" => self.categories.extend(node.attr("term").map(|term| Category {
+                name: term.to_string(),
+                domain: node.attr("scheme").map(String::from),
+            })),
+            "published" => self.published = node.into_text(),
+            "updated" => self.updated = node.into_text(),
+            "rights" => self.rights = text_construct(node),
+            _ => {}
+        }
+    }
+
+    // Atom entries without an author inherit the feed's.
+    fn with_channel(&mut self, channel: &ChannelInfo) {
+        if self.authors.is_empty() {
+            self.authors.extend(channel.author.clone());
+        }
+        if self.language.is_none() {
+            self.language = channel.language.clone();
+        }
+        for link in &mut self.links {
+            link.href = absolute(channel.link.as_deref(), std::mem::take(&mut link.href));
+        }
+        self.link = Link::canonical(&self.links).map(|link| link.href.clone());
+    }
+}
+
+impl AtomEntry {
+    /// A representative image for the entry: a Media RSS thumbnail or image,
+    /// else an image enclosure link, or the first `<img>` of the content or
+    /// summary.
+    pub fn thumbnail(&self) -> Option<String> {
+        if let Some(url) = self.media.thumbnail() {
+            return Some(url.to_string());
+        }
+        let enclosure = self.links.iter().find(|link| link.rel == "enclosure" && link.mime_type.as_ref().is_some_and(MimeType::is_image));
+        if let Some(link) = enclosure {
+            return Some(link.href.clone());
+        }
+        let html = [self.content.as_deref(), self.summary.as_deref()];
+        let image = html.into_iter().flatten().find_map(first_image)?;
+        Some(absolute(self.link.as_deref(), image))
+    }
+}
+
+// `type="xhtml"` content is markup wrapped in a `<div>` that is not part of
+// it; it's only available verbatim when inner XML is captured.
+fn text_construct(node: XmlNode) -> Option<String> {
+    if node.attr("type") == Some("xhtml")
+        && let Some(inner) = node.inner_xml.as_deref()
+    {
+        let inner = inner.trim();
+        let unwrapped = inner
+            .strip_prefix("<div")
+            .and_then(|rest| Some(&rest[rest.find('>')? + 1..]))
+            .and_then(|rest| rest.strip_suffix("</div>"));
+        return Some(unwrapped.unwrap_or(inner).to_string());
+    }
+    node.into_text()
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    use super::*;
+    use crate::RssParser;
+    use std::io::Cursor;
+
+    const FEED: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+    <title>Example Feed</title>
+    <link href="https://example.org/"/>
+    <updated>2003-12-13T18:30:02Z</updated>
+    <entry>
+        <title>Atom-Powered Robots Run Amok</title>
+        <link rel="enclosure" href="https://example.org/robots.mp3"/>
+        <link href="https://example.org/2003/12/13/atom03"/>
+        <id>urn:uuid:1225c695-cfb8-4ebb-aaaa-80da344efa6a</id>
+        <updated>2003-12-13T18:30:02Z</updated>
+        <published>2003-12-13T08:29:29-04:00</published>
+        <author><name>John Doe</name><email>john@example.org</email><uri>https://example.org/john</uri></author>
+        <author><name>Second Author</name></author>
+        <category term="robots" scheme="https://example.org/tags"/>
+        <category term="news"/>
+        <contributor><name>Jane Doe</name></contributor>
+        <source><title>Original Feed</title><id>urn:source</id></source>
+        <summary>Some text.</summary>
+        <content type="html"><![CDATA[<p>Full text</p>]]></content>
+    </entry>
+    <entry>
+        <title>Second</title>
+        <link rel="alternate" href="https://example.org/2"/>
+        <id>urn:2</id>
+    </entry>
+</feed>"#;
+
+    #[cfg(feature = "urls")]
+    #[tokio::test]
+    async fn test_xml_base() {
+        let feed = r#"<feed xmlns="http://www.w3.org/2005/Atom" xml:base="https://example.org/blog/">
+            <link href="/"/>
+            <entry xml:base="2024/"><link href="post.html"/><link rel="enclosure" href="https://cdn.example/a.mp3"/></entry>
+            <entry><link href="other.html"/></entry>
+        </feed>"#;
+        let mut parser = RssParser::<AtomEntry, _>::new_atom(Cursor::new(feed.as_bytes())).await.unwrap();
+        let channel: crate::ChannelInfo = parser.channel().await.unwrap();
+        assert_eq!(channel.link.as_deref(), Some("https://example.org/"));
+
+        let entry = parser.next().await.unwrap().unwrap();
+        assert_eq!(entry.link.as_deref(), Some("https://example.org/blog/2024/post.html"));
+        assert_eq!(entry.links[1].href, "https://cdn.example/a.mp3");
+        assert_eq!(parser.next().await.unwrap().unwrap().link.as_deref(), Some("https://example.org/blog/other.html"));
+    }
+
+    #[tokio::test]
+    async fn test_xhtml_content() {
+        let feed = r#"<feed xmlns="http://www.w3.org/2005/Atom"><entry>
+            <title type="xhtml"><div xmlns="http://www.w3.org/1999/xhtml">Plain</div></title>
+            <content type="xhtml">
+                <div xmlns="http://www.w3.org/1999/xhtml"><p>Hello <b>world</b> &amp; <br/>co</p></div>
+            </content>
+        </entry></feed>"#;
+        let mut parser: RssParser<AtomEntry, _> = RssParser::builder()
+            .item_tag("entry")
+            .capture_inner_xml(true)
+            .build(Cursor::new(feed.as_bytes()))
+            .await
+            .unwrap();
+
+        let entry = parser.next().await.unwrap().unwrap();
+        assert_eq!(entry.content.as_deref(), Some("<p>Hello <b>world</b> &amp; <br/>co</p>"));
+        assert_eq!(entry.title.as_deref(), Some("Plain"));
+    }
+
+    #[tokio::test]
+    async fn test_atom_entries() {
+        let mut parser = RssParser::<AtomEntry, _>::new_atom(Cursor::new(FEED.as_bytes())).await.unwrap();
+
+        let entry = parser.next().await.unwrap().unwrap();
+        assert_eq!(entry.title.as_deref(), Some("Atom-Powered Robots Run Amok"));
+        assert_eq!(entry.link.as_deref(), Some("https://example.org/2003/12/13/atom03"));
+        assert_eq!(entry.id.as_deref(), Some("urn:uuid:1225c695-cfb8-4ebb-aaaa-80da344efa6a"));
+        assert_eq!(entry.updated.as_deref(), Some("2003-12-13T18:30:02Z"));
+        assert_eq!(entry.published.as_deref(), Some("2003-12-13T08:29:29-04:00"));
+        assert_eq!(
+            entry.authors,
+            vec![
+                Person {
+                    name: Some("John Doe".to_string()),
+                    email: Some("john@example.org".to_string()),
+                    uri: Some("https://example.org/john".to_string()),
+                },
+                Person::named("Second Author"),
+            ]
+        );
+        assert_eq!(entry.categories.len(), 2);
+        assert_eq!(entry.categories[0].domain.as_deref(), Some("https://example.org/tags"));
+        assert_eq!(entry.categories[1].name, "news");
+        assert_eq!(entry.summary.as_deref(), Some("Some text."));
+        assert_eq!(entry.content.as_deref(), Some("<p>Full text</p>"));
+
+        assert_eq!(entry.links.len(), 2);
+        assert_eq!(entry.links[0].rel, "enclosure");
+        assert_eq!(entry.links[1].rel, "alternate");
+
+        let second = parser.next().await.unwrap().unwrap();
+        assert_eq!(second.link.as_deref(), Some("https://example.org/2"));
+        assert_eq!(second.id.as_deref(), Some("urn:2"));
+
+        assert!(parser.next().await.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_canonical_link() {
+        let link = |rel: &str, href: &str, mime: Option<&str>| Link {
+            rel: rel.to_string(),
+            href: href.to_string(),
+            mime_type: mime.and_then(MimeType::parse),
+            title: None,
+        };
+        let links = vec![
+            link("self", "https://example.org/self", None),
+            link("alternate", "https://example.org/feed.json", Some("application/json")),
+            link("alternate", "https://example.org/page", Some("text/html")),
+        ];
+        assert_eq!(Link::canonical(&links).unwrap().href, "https://example.org/page");
+        assert_eq!(Link::canonical(&links[..2]).unwrap().href, "https://example.org/feed.json");
+        assert!(Link::canonical(&links[..1]).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_rss_parser_ignores_atom_entries() {
+        let mut parser = RssParser::<AtomEntry, _>::new(Cursor::new(FEED.as_bytes())).await.unwrap();
+        assert!(parser.next().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_custom_item_tag() {
+        let feed = "<catalog><Product><title>Widget</title></Product></catalog>";
+        let mut parser = RssParser::<AtomEntry, _>::with_item_tag(Cursor::new(feed.as_bytes()), "product")
+            .await
+            .unwrap();
+        assert_eq!(parser.next().await.unwrap().unwrap().title.as_deref(), Some("Widget"));
+    }
+}
@@ -0,0 +1,104 @@
+use std::future::Future;
+use std::io::{ErrorKind, Read};
+use std::pin::{Pin, pin};
+use std::task::{Context, Poll, Waker};
+
+use crate::async_read::{AsyncRead, ReadBuf};
+use crate::builder::RssParserBuilder;
+use crate::error::RssError;
+use crate::rss_parser::{GradualRssChannel, GradualRssItem, RssParser};
+
+/// `RssParser` over a `std::io::Read`, as an `Iterator`, for programs
+/// without an async runtime. The parser never waits on anything but the
+/// reader, so each call simply runs until the reader has returned enough;
+/// no Tokio runtime is started or needed. With `default-features = false`
+/// Tokio isn't even compiled in.
+pub struct SyncRssParser<T, R> {
+    parser: RssParser<T, SyncReader<R>>,
+}
+
+impl<T: GradualRssItem, R: Read + Unpin> SyncRssParser<T, R> {
+    pub fn new(input: R) -> Self {
+        RssParserBuilder::new().build_sync(input)
+    }
+
+    pub(crate) fn from_builder(builder: RssParserBuilder, input: R) -> Self {
+        let parser = block_on(builder.build(SyncReader(input))).expect("building a parser does not read");
+        SyncRssParser { parser }
+    }
+
+    /// See `RssParser::channel`.
+    pub fn channel<C: GradualRssChannel>(&mut self) -> Result<C, RssError> {
+        block_on(self.parser.channel())
+    }
+
+    /// See `RssParser::warnings`.
+    pub fn warnings(&self) -> &[RssError] {
+        self.parser.warnings()
+    }
+}
+
+impl<T: GradualRssItem> SyncRssParser<T, std::fs::File> {
+    pub fn from_file(path: &str) -> std::io::Result<Self> {
+        Ok(Self::new(std::fs::File::open(path)?))
+    }
+}
+
+impl<T: GradualRssItem, R: Read + Unpin> Iterator for SyncRssParser<T, R> {
+    type Item = Result<T, RssError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        block_on(self.parser.next()).transpose()
+    }
+}
+
+// A blocking reader that is always ready.
+struct SyncReader<R>(R);
+
+impl<R: Read + Unpin> AsyncRead for SyncReader<R> {
+    fn poll_read(self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            match this.0.read(buf.initialize_unfilled()) {
+                Ok(read) => {
+                    buf.advance(read);
+                    return Poll::Ready(Ok(()));
+                }
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => return Poll::Ready(Err(err)),
+            }
+        }
+    }
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChannelInfo, ParseMode, RssItem};
+    use std::io::Cursor;
+
+    // A plain test: there is no runtime here.
+    #[test]
+    fn test_sync_parser() {
+        let feed = "<rss><channel><title>Sync</title><item><title>One</title></item><item><title>Two</title></item></channel></rss>";
+        let mut parser = SyncRssParser::<RssItem, _>::new(Cursor::new(feed));
+        let channel: ChannelInfo = parser.channel().unwrap();
+        assert_eq!(channel.title.as_deref(), Some("Sync"));
+        let titles: Vec<_> = parser.map(|item| item.unwrap().title.unwrap()).collect();
+        assert_eq!(titles, vec!["One", "Two"]);
+
+        let broken = "<rss><channel><item><title>1</wrong></item><item><title>2</title></item></channel></rss>";
+        let parser: SyncRssParser<RssItem, _> = RssParserBuilder::new().mode(ParseMode::Lenient).build_sync(Cursor::new(broken));
+        assert_eq!(parser.map(Result::unwrap).count(), 1);
+    }
+}
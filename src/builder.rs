@@ -0,0 +1,616 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::async_read::AsyncRead;
+use crate::metrics::{Metrics, MetricsHandle};
+use crate::rss_parser::{GradualRssItem, RssParser, XML_KEY_ITEM};
+use crate::stats::{ParserStats, ProgressHandle};
+
+const DEFAULT_BUFFER_CAPACITY: usize = 8 * 1024;
+const DEFAULT_MAX_ITEM_SIZE: u64 = 64 * 1024 * 1024;
+const DEFAULT_MAX_ELEMENT_SIZE: u64 = 16 * 1024 * 1024;
+const DEFAULT_MAX_DEPTH: usize = 128;
+
+/// How the parser reacts to malformed input.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Stop with an error at the first problem (the default).
+    #[default]
+    Strict,
+    /// Skip malformed items, record the error in `RssParser::warnings` and
+    /// keep going. A truncated feed simply ends. I/O errors are still returned.
+    Lenient,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct ParserOptions {
+    pub(crate) item_tag: String,
+    pub(crate) trim_whitespace: bool,
+    pub(crate) collapse_whitespace: bool,
+    pub(crate) lowercase_tags: bool,
+    pub(crate) max_item_size: Option<u64>,
+    pub(crate) max_element_size: Option<u64>,
+    pub(crate) max_depth: Option<usize>,
+    pub(crate) max_feed_size: Option<u64>,
+    pub(crate) stall_timeout: Option<Duration>,
+    pub(crate) decode_entities: bool,
+    pub(crate) buffer_capacity: usize,
+    pub(crate) mode: ParseMode,
+    pub(crate) allow_doctype: bool,
+    pub(crate) capture_inner_xml: bool,
+    pub(crate) capture_raw_items: bool,
+    pub(crate) track_lines: bool,
+    pub(crate) metrics: Option<MetricsHandle>,
+    pub(crate) progress: Option<ProgressHandle>,
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        ParserOptions {
+            item_tag: XML_KEY_ITEM.to_string(),
+            trim_whitespace: false,
+            collapse_whitespace: false,
+            lowercase_tags: true,
+            max_item_size: Some(DEFAULT_MAX_ITEM_SIZE),
+            max_element_size: Some(DEFAULT_MAX_ELEMENT_SIZE),
+            max_depth: Some(DEFAULT_MAX_DEPTH),
+            max_feed_size: None,
+            stall_timeout: None,
+            decode_entities: true,
+            buffer_capacity: DEFAULT_BUFFER_CAPACITY,
+            mode: ParseMode::Strict,
+            allow_doctype: false,
+            capture_inner_xml: false,
+            capture_raw_items: false,
+            track_lines: false,
+            metrics: None,
+            progress: None,
+        }
+    }
+}
+
+impl ParserOptions {
+    // The tag as it will appear in `XmlNode.tag`.
+    pub(crate) fn normalize_tag(&self, tag: &str) -> String {
+        match self.lowercase_tags {
+            true => tag.to_lowercase(),
+            false => tag.to_string(),
+        }
+    }
+
+    // The raw name as it will appear in `XmlNode.tag`, in one allocation.
+    pub(crate) fn normalize_name(&self, name: &[u8]) -> String {
+        let mut tag = String::new();
+        self.normalize_into(name, &mut tag);
+        tag
+    }
+
+    // `normalize_name`, appending to a string whose capacity can be reused.
+    pub(crate) fn normalize_into(&self, name: &[u8], out: &mut String) {
+        let name = String::from_utf8_lossy(name);
+        match self.lowercase_tags {
+            true if name.is_ascii() => {
+                let start = out.len();
+                out.push_str(&name);
+                out[start..].make_ascii_lowercase();
+            }
+            true => out.push_str(&name.to_lowercase()),
+            false => out.push_str(&name),
+        }
+    }
+
+    // Whether a raw name normalizes to `tag`, an already normalized tag,
+    // without building the normalized name for ASCII input.
+    pub(crate) fn name_is(&self, name: &[u8], tag: &str) -> bool {
+        match self.lowercase_tags {
+            true if name.is_ascii() => name.eq_ignore_ascii_case(tag.as_bytes()),
+            true => self.normalize_name(name) == tag,
+            false => name == tag.as_bytes(),
+        }
+    }
+}
+
+/// Configures an `RssParser`; start with `RssParser::builder()`.
+#[derive(Debug, Clone, Default)]
+pub struct RssParserBuilder {
+    options: ParserOptions,
+}
+
+impl RssParserBuilder {
+    pub fn new() -> Self {
+        RssParserBuilder::default()
+    }
+
+    /// The element that delimits items, `item` by default.
+    pub fn item_tag(mut self, item_tag: &str) -> Self {
+        self.options.item_tag = item_tag.to_string();
+        self
+    }
+
+    /// Strips leading and trailing whitespace from text and CDATA, dropping
+    /// values that were only whitespace. Off by default. Elements under
+    /// `xml:space="preserve"` are left alone.
+    pub fn trim_whitespace(mut self, trim: bool) -> Self {
+        self.options.trim_whitespace = trim;
+        self
+    }
+
+    /// Like `trim_whitespace`, and also turns every run of whitespace inside
+    /// text into a single space. CDATA, usually HTML, is only trimmed.
+    pub fn collapse_whitespace(mut self, collapse: bool) -> Self {
+        self.options.collapse_whitespace = collapse;
+        self
+    }
+
+    /// Lowercases tag and attribute names (the default) so `populate` can match
+    /// them regardless of how the feed spells them. When off, names keep their
+    /// original case and the item tag is matched exactly; the built-in item types
+    /// expect lowercase names.
+    pub fn lowercase_tags(mut self, lowercase: bool) -> Self {
+        self.options.lowercase_tags = lowercase;
+        self
+    }
+
+    /// Fails with `RssError::LimitExceeded` when a single item spans more than
+    /// `bytes` of the feed, 64 MiB by default. The rest of the feed can still be read.
+    pub fn max_item_size(mut self, bytes: u64) -> Self {
+        self.options.max_item_size = Some(bytes);
+        self
+    }
+
+    /// Caps the text and CDATA of one element, 16 MiB by default. Text built
+    /// from many small pieces fails the item; a single oversized text, CDATA
+    /// section or tag ends the feed, since it is refused before being buffered.
+    pub fn max_element_size(mut self, bytes: u64) -> Self {
+        self.options.max_element_size = Some(bytes);
+        self
+    }
+
+    /// Caps element nesting, 128 levels by default. Deeper elements fail the
+    /// item they're in and are skipped.
+    pub fn max_depth(mut self, levels: usize) -> Self {
+        self.options.max_depth = Some(levels);
+        self
+    }
+
+    /// Fails with `RssError::FeedTooLarge` once more than `bytes` have been read
+    /// (after transcoding to UTF-8). Unlimited by default.
+    pub fn max_feed_size(mut self, bytes: u64) -> Self {
+        self.options.max_feed_size = Some(bytes);
+        self
+    }
+
+    /// Fails with `RssError::Stalled` when a read gets no data for `timeout`,
+    /// so a dead or trickling server can't hold the parser forever. The timer
+    /// restarts whenever data arrives. Off by default, and without the `tokio`
+    /// feature there is no timer, nor an input that could stall.
+    pub fn stall_timeout(mut self, timeout: Duration) -> Self {
+        self.options.stall_timeout = Some(timeout);
+        self
+    }
+
+    /// Lifts the item size, element size and depth limits, for trusted input.
+    pub fn no_limits(mut self) -> Self {
+        self.options.max_item_size = None;
+        self.options.max_element_size = None;
+        self.options.max_depth = None;
+        self
+    }
+
+    /// Decodes `&amp;`, `&nbsp;`, `&#8217;` and other entity and character
+    /// references in text (the default), or keeps them as written.
+    pub fn decode_entities(mut self, decode: bool) -> Self {
+        self.options.decode_entities = decode;
+        self
+    }
+
+    /// Capacity of the read buffer in front of the input, 8 KiB by default.
+    pub fn buffer_capacity(mut self, bytes: usize) -> Self {
+        self.options.buffer_capacity = bytes;
+        self
+    }
+
+    /// Accepts `<!DOCTYPE>` declarations, which are rejected with
+    /// `RssError::DoctypeNotAllowed` by default. Either way the DTD is ignored:
+    /// its entities are never expanded and external ones never fetched, so
+    /// XXE and entity expansion bombs have no effect.
+    pub fn allow_doctype(mut self, allow: bool) -> Self {
+        self.options.allow_doctype = allow;
+        self
+    }
+
+    pub fn mode(mut self, mode: ParseMode) -> Self {
+        self.options.mode = mode;
+        self
+    }
+
+    /// Fills `XmlNode.inner_xml` with each element's markup as written. Off by
+    /// default, since the parser then holds on to the raw text of every open element.
+    pub fn capture_inner_xml(mut self, capture: bool) -> Self {
+        self.options.capture_inner_xml = capture;
+        self
+    }
+
+    /// Keeps each item's markup for `RssParser::next_with_raw`. Off by default.
+    pub fn capture_raw_items(mut self, capture: bool) -> Self {
+        self.options.capture_raw_items = capture;
+        self
+    }
+
+    /// Counts lines so `RssParser::locate` and `item_location` can report
+    /// line and column numbers, not just byte offsets. Off by default.
+    pub fn track_lines(mut self, track: bool) -> Self {
+        self.options.track_lines = track;
+        self
+    }
+
+    /// Reports parsed items, their latency and parse errors to `metrics`.
+    pub fn metrics(mut self, metrics: Arc<dyn Metrics>) -> Self {
+        self.options.metrics = Some(MetricsHandle(metrics));
+        self
+    }
+
+    /// Calls `progress` with the parser's `stats` after every item and when
+    /// the feed ends, for progress bars over large downloads.
+    pub fn on_progress(mut self, progress: impl Fn(&ParserStats) + Send + Sync + 'static) -> Self {
+        self.options.progress = Some(ProgressHandle(Arc::new(progress)));
+        self
+    }
+
+    /// Builds a `SyncRssParser`, for reading without an async runtime.
+    #[cfg(feature = "blocking")]
+    pub fn build_sync<T: GradualRssItem, R: std::io::Read + Unpin>(self, input: R) -> crate::blocking::SyncRssParser<T, R> {
+        crate::blocking::SyncRssParser::from_builder(self, input)
+    }
+
+    pub async fn build<T: GradualRssItem, R: AsyncRead + Unpin>(self, input: R) -> std::io::Result<RssParser<T, R>> {
+        Ok(RssParser::from_options(input, self.options))
+    }
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    use super::*;
+    use crate::{RssError, RssItem, XmlNode};
+    use std::io::Cursor;
+
+    #[derive(Debug, Default)]
+    struct Tags(Vec<(String, Option<String>)>);
+
+    impl GradualRssItem for Tags {
+        fn init() -> Self {
+            Tags::default()
+        }
+
+        fn populate(&mut self, node: XmlNode) {
+            if !node.path.is_empty() {
+                self.0.push((node.tag, node.value));
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_builder_defaults_match_new() {
+        let feed = "<rss><channel><item><title> A &amp; B </title></item></channel></rss>";
+        let mut parser: RssParser<RssItem, _> = RssParser::builder().build(Cursor::new(feed.as_bytes())).await.unwrap();
+        assert_eq!(parser.next().await.unwrap().unwrap().title.as_deref(), Some(" A & B "));
+    }
+
+    #[tokio::test]
+    async fn test_trim_and_case() {
+        let feed = "<Catalog><Product>\n  <Name>\n    Widget\n  </Name>\n  <Note>   </Note>\n</Product></Catalog>";
+        let mut parser: RssParser<Tags, _> = RssParser::builder()
+            .item_tag("Product")
+            .lowercase_tags(false)
+            .trim_whitespace(true)
+            .buffer_capacity(16)
+            .build(Cursor::new(feed.as_bytes()))
+            .await
+            .unwrap();
+
+        let item = parser.next().await.unwrap().unwrap();
+        assert_eq!(item.0, vec![("Name".to_string(), Some("Widget".to_string())), ("Note".to_string(), None)]);
+        assert!(parser.next().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_end_tags_match_item_tag_ignoring_case() {
+        let feed = "<Liste><ÜBUNG><Name>Eins</Name></ÜBUNG><übung><Name>Zwei</Name></übung></Liste>";
+        let mut parser: RssParser<Tags, _> =
+            RssParser::builder().item_tag("Übung").build(Cursor::new(feed.as_bytes())).await.unwrap();
+        assert_eq!(parser.next().await.unwrap().unwrap().0, vec![("name".to_string(), Some("Eins".to_string()))]);
+        assert_eq!(parser.next().await.unwrap().unwrap().0, vec![("name".to_string(), Some("Zwei".to_string()))]);
+
+        let options = ParserOptions::default();
+        assert!(options.name_is(b"ITEM", "item"));
+        assert!(!options.name_is(b"items", "item"));
+    }
+
+    #[tokio::test]
+    async fn test_preserved_case_channel() {
+        #[derive(Default)]
+        struct Title(Option<String>);
+
+        impl crate::GradualRssChannel for Title {
+            fn init() -> Self {
+                Title::default()
+            }
+
+            fn populate(&mut self, node: XmlNode) {
+                if node.path_is("title") {
+                    self.0 = node.into_text();
+                }
+            }
+        }
+
+        let feed = "<RSS><Channel><Title>Mixed</Title><Item><Title>I</Title></Item></Channel></RSS>";
+        let mut parser: RssParser<Tags, _> = RssParser::builder()
+            .item_tag("Item")
+            .lowercase_tags(false)
+            .build(Cursor::new(feed.as_bytes()))
+            .await
+            .unwrap();
+
+        let channel: Title = parser.channel().await.unwrap();
+        assert_eq!(channel.0.as_deref(), Some("Mixed"));
+        assert_eq!(parser.next().await.unwrap().unwrap().0, vec![("Title".to_string(), Some("I".to_string()))]);
+    }
+
+    #[tokio::test]
+    async fn test_collapse_whitespace() {
+        let feed = "<rss><channel><item>\
+            <title>\n    Breaking\n    news  \t today\n</title>\
+            <x:code xml:space=\"preserve\"><x:line>  keep   this  </x:line></x:code>\
+            </item></channel></rss>";
+        let mut parser: RssParser<Tags, _> = RssParser::builder()
+            .collapse_whitespace(true)
+            .build(Cursor::new(feed.as_bytes()))
+            .await
+            .unwrap();
+
+        let item = parser.next().await.unwrap().unwrap();
+        assert_eq!(item.0[0], ("title".to_string(), Some("Breaking news today".to_string())));
+        assert_eq!(item.0[1], ("x:line".to_string(), Some("  keep   this  ".to_string())));
+    }
+
+    #[tokio::test]
+    async fn test_max_item_size() {
+        let big = "x".repeat(500);
+        let feed = format!(
+            "<rss><channel><item><title>{big}</title><link>skipped</link></item><item><title>small</title></item></channel></rss>"
+        );
+        let mut parser: RssParser<RssItem, _> = RssParser::builder()
+            .max_item_size(100)
+            .build(Cursor::new(feed.into_bytes()))
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            parser.next().await,
+            Err(RssError::LimitExceeded { limit: "item size", max: 100, .. })
+        ));
+        assert_eq!(parser.next().await.unwrap().unwrap().title.as_deref(), Some("small"));
+        assert!(parser.next().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_raw_items() {
+        let feed = "<rss><channel><title>Feed</title>\
+            <item>\n  <title>One</title>\n  <x:unknown a='1'>kept</x:unknown>\n</item>\
+            <item/></channel></rss>";
+        let mut parser: RssParser<RssItem, _> = RssParser::builder()
+            .capture_raw_items(true)
+            .buffer_capacity(16)
+            .build(Cursor::new(feed.as_bytes()))
+            .await
+            .unwrap();
+
+        let channel: crate::ChannelInfo = parser.channel().await.unwrap();
+        assert_eq!(channel.title.as_deref(), Some("Feed"));
+
+        let (item, raw) = parser.next_with_raw().await.unwrap().unwrap();
+        assert_eq!(item.title.as_deref(), Some("One"));
+        assert_eq!(
+            raw.as_deref(),
+            Some("<item>\n  <title>One</title>\n  <x:unknown a='1'>kept</x:unknown>\n</item>")
+        );
+        let (_, raw) = parser.next_with_raw().await.unwrap().unwrap();
+        assert_eq!(raw.as_deref(), Some("<item/>"));
+        assert!(parser.next_with_raw().await.unwrap().is_none());
+
+        let mut plain: RssParser<RssItem, _> = RssParser::builder().build(Cursor::new(feed.as_bytes())).await.unwrap();
+        assert!(plain.next_with_raw().await.unwrap().unwrap().1.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_max_element_size() {
+        let huge = "x".repeat(10_000);
+        let feed = format!(
+            "<rss><channel><item><title>{}</title></item><item><title>ok</title></item></channel></rss>",
+            "a&amp;".repeat(100)
+        );
+        let mut parser: RssParser<RssItem, _> = RssParser::builder()
+            .max_element_size(100)
+            .build(Cursor::new(feed.into_bytes()))
+            .await
+            .unwrap();
+        assert!(matches!(
+            parser.next().await,
+            Err(RssError::LimitExceeded { limit: "element size", max: 100, .. })
+        ));
+        assert_eq!(parser.next().await.unwrap().unwrap().title.as_deref(), Some("ok"));
+
+        // One huge text node is refused while it is still being read.
+        let feed = format!("<rss><channel><item><title>{huge}</title></item></channel></rss>");
+        let mut parser: RssParser<RssItem, _> = RssParser::builder()
+            .max_element_size(100)
+            .buffer_capacity(64)
+            .build(Cursor::new(feed.into_bytes()))
+            .await
+            .unwrap();
+        let err = parser.next().await.unwrap_err();
+        assert!(matches!(err, RssError::LimitExceeded { limit: "element size", .. }), "{err}");
+        assert!(parser.next().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_max_depth() {
+        let deep = "<x>".repeat(10) + &"</x>".repeat(10);
+        let feed = format!(
+            "<rss><channel><item><title>deep</title>{deep}</item><item><title>shallow</title><x><x/></x></item></channel></rss>"
+        );
+        let mut parser: RssParser<RssItem, _> = RssParser::builder()
+            .max_depth(6)
+            .build(Cursor::new(feed.into_bytes()))
+            .await
+            .unwrap();
+
+        let err = parser.next().await.unwrap_err();
+        assert_eq!(err.to_string(), "depth limit of 6 levels exceeded at byte 48");
+        assert_eq!(parser.next().await.unwrap().unwrap().title.as_deref(), Some("shallow"));
+        assert!(parser.next().await.unwrap().is_none());
+
+        // Text in skipped elements doesn't end up in their kept ancestor.
+        let feed = "<rss><channel><description>Kept<x>lost &amp; <![CDATA[gone]]></x></description></channel></rss>";
+        let mut parser: RssParser<RssItem, _> = RssParser::builder().max_depth(3).build(Cursor::new(feed.as_bytes())).await.unwrap();
+        assert!(parser.channel::<crate::ChannelInfo>().await.is_err());
+        let channel: crate::ChannelInfo = parser.channel().await.unwrap();
+        assert_eq!(channel.description.as_deref(), Some("Kept"));
+    }
+
+    #[tokio::test]
+    async fn test_max_feed_size() {
+        let feed = format!("<rss><channel>{}</channel></rss>", "<item><title>t</title></item>".repeat(100));
+        let mut parser: RssParser<RssItem, _> = RssParser::builder()
+            .max_feed_size(500)
+            .buffer_capacity(64)
+            .build(Cursor::new(feed.into_bytes()))
+            .await
+            .unwrap();
+
+        let mut items = 0;
+        let err = loop {
+            match parser.next().await {
+                Ok(Some(_)) => items += 1,
+                Ok(None) => panic!("the limit was not enforced"),
+                Err(err) => break err,
+            }
+        };
+        assert!(matches!(err, RssError::FeedTooLarge { max: 500 }));
+        assert!((10..20).contains(&items), "{items}");
+        assert!(parser.next().await.unwrap().is_none());
+
+        // A stream skipping errors still ends.
+        let feed = format!("<rss><channel>{}</channel></rss>", "<item><title>t</title></item>".repeat(100));
+        let parser: RssParser<RssItem, _> = RssParser::builder()
+            .max_feed_size(200)
+            .buffer_capacity(64)
+            .build(Cursor::new(feed.into_bytes()))
+            .await
+            .unwrap();
+        let results: Vec<_> = tokio_stream::StreamExt::collect(parser.into_stream()).await;
+        assert_eq!(results.iter().filter(|result| result.is_err()).count(), 1);
+        assert!(matches!(results.last(), Some(Err(RssError::FeedTooLarge { max: 200 }))));
+    }
+
+    #[tokio::test]
+    async fn test_stall_timeout() {
+        let (mut server, client) = tokio::io::duplex(64);
+        tokio::io::AsyncWriteExt::write_all(&mut server, b"<rss><channel><item><title>t</title></item><item>")
+            .await
+            .unwrap();
+        let mut parser: RssParser<RssItem, _> = RssParser::builder()
+            .stall_timeout(Duration::from_millis(50))
+            .build(client)
+            .await
+            .unwrap();
+
+        assert_eq!(parser.next().await.unwrap().unwrap().title.as_deref(), Some("t"));
+        let err = parser.next().await.unwrap_err();
+        assert!(matches!(err, RssError::Stalled { position: 49, .. }), "{err}");
+        drop(server);
+    }
+
+    const DOCTYPE: &str = r#"<?xml version="1.0"?>
+<!DOCTYPE rss [<!ENTITY lol "lol"><!ENTITY lol2 "&lol;&lol;&lol;"><!ENTITY xxe SYSTEM "file:///etc/passwd">]>
+<rss><channel><item><title>&lol2; &xxe;</title></item></channel></rss>"#;
+
+    #[tokio::test]
+    async fn test_doctype_rejected() {
+        let mut parser: RssParser<RssItem, _> = RssParser::builder().build(Cursor::new(DOCTYPE.as_bytes())).await.unwrap();
+        assert!(matches!(parser.next().await, Err(RssError::DoctypeNotAllowed { position: 22 })));
+
+        let mut parser: RssParser<RssItem, _> = RssParser::builder()
+            .allow_doctype(true)
+            .build(Cursor::new(DOCTYPE.as_bytes()))
+            .await
+            .unwrap();
+        let item = parser.next().await.unwrap().unwrap();
+        assert_eq!(item.title.as_deref(), Some("&lol2; &xxe;"));
+    }
+
+    const BROKEN: &str = "<rss><channel>\
+        <item><title>one</title></item>\
+        <item><title>two</titel><link>x</link></item>\
+        <item><title>three</title></item>\
+        <item><title>cut";
+
+    #[tokio::test]
+    async fn test_strict_mode_stops() {
+        let mut parser: RssParser<RssItem, _> = RssParser::builder().build(Cursor::new(BROKEN.as_bytes())).await.unwrap();
+        assert_eq!(parser.next().await.unwrap().unwrap().title.as_deref(), Some("one"));
+        match parser.next().await {
+            Err(RssError::Xml { position, .. }) => assert_eq!(&BROKEN[position as usize..][..8], "</titel>"),
+            other => panic!("expected an XML error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lenient_mode_skips() {
+        let mut parser: RssParser<RssItem, _> = RssParser::builder()
+            .mode(ParseMode::Lenient)
+            .build(Cursor::new(BROKEN.as_bytes()))
+            .await
+            .unwrap();
+
+        let mut titles = Vec::new();
+        while let Some(item) = parser.next().await.unwrap() {
+            titles.extend(item.title);
+        }
+        assert_eq!(titles, vec!["one".to_string(), "three".to_string()]);
+        assert!(matches!(parser.warnings(), [RssError::Xml { .. }, RssError::UnexpectedEof]));
+    }
+
+    #[tokio::test]
+    async fn test_lenient_warnings_report() {
+        let feed = format!(
+            "<rss><channel><item><title>{}</title><link>kept</link></item></channel></rss>",
+            "abc&amp;".repeat(20)
+        );
+        let mut parser: RssParser<RssItem, _> = RssParser::builder()
+            .mode(ParseMode::Lenient)
+            .max_element_size(10)
+            .build(Cursor::new(feed.into_bytes()))
+            .await
+            .unwrap();
+
+        let item = parser.next().await.unwrap().unwrap();
+        assert_eq!(item.title.as_deref(), Some("abc&abc&ab"));
+        assert_eq!(item.link.as_deref(), Some("kept"));
+        assert!(matches!(parser.warnings(), [RssError::LimitExceeded { limit: "element size", .. }]));
+
+        // An unsupported encoding is read as ASCII and noted in either mode.
+        let feed = b"<?xml version=\"1.0\" encoding=\"Shift_JIS\"?><rss><channel><item><title>t</title></item></channel></rss>";
+        let mut parser = RssParser::<RssItem, _>::new(Cursor::new(feed)).await.unwrap();
+        assert_eq!(parser.next().await.unwrap().unwrap().title.as_deref(), Some("t"));
+        assert!(matches!(parser.warnings(), [RssError::UnknownEncoding(label)] if label == "Shift_JIS"));
+
+        // Text outside ASCII is replaced in lenient mode and stops a strict parser.
+        let feed = b"<?xml version=\"1.0\" encoding=\"Shift_JIS\"?><rss><channel><item><title>\x93\xfa</title></item></channel></rss>";
+        let mut parser: RssParser<RssItem, _> = RssParser::builder().mode(ParseMode::Lenient).build(Cursor::new(feed)).await.unwrap();
+        assert_eq!(parser.next().await.unwrap().unwrap().title.as_deref(), Some("\u{FFFD}\u{FFFD}"));
+        let mut parser = RssParser::<RssItem, _>::new(Cursor::new(feed)).await.unwrap();
+        assert!(matches!(parser.next().await, Err(RssError::UnknownEncoding(label)) if label == "Shift_JIS"));
+        assert!(parser.next().await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,56 @@
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use tokio::io::{AsyncRead, ReadBuf};
+use tokio_stream::Stream;
+
+/// A stream of byte chunks read as input, from `RssParser::from_stream`.
+pub struct ByteStream<S, B> {
+    stream: S,
+    chunk: Option<B>,
+    position: usize,
+}
+
+impl<S, B> ByteStream<S, B> {
+    pub(crate) fn new(stream: S) -> Self {
+        ByteStream {
+            stream,
+            chunk: None,
+            position: 0,
+        }
+    }
+}
+
+impl<S: Unpin, B> Unpin for ByteStream<S, B> {}
+
+impl<S, B, E> AsyncRead for ByteStream<S, B>
+where
+    S: Stream<Item = Result<B, E>> + Unpin,
+    B: AsRef<[u8]>,
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(chunk) = &this.chunk {
+                let rest = &chunk.as_ref()[this.position..];
+                if !rest.is_empty() {
+                    let count = rest.len().min(buf.remaining());
+                    buf.put_slice(&rest[..count]);
+                    this.position += count;
+                    return Poll::Ready(Ok(()));
+                }
+            }
+            match Pin::new(&mut this.stream).poll_next(cx) {
+                Poll::Ready(Some(Ok(chunk))) => {
+                    this.chunk = Some(chunk);
+                    this.position = 0;
+                }
+                Poll::Ready(Some(Err(err))) => return Poll::Ready(Err(io::Error::other(err))),
+                Poll::Ready(None) => return Poll::Ready(Ok(())),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use std::pin::Pin;
+use std::task::{Context, Poll, ready};
+
+use crate::async_read::{AsyncRead, ReadBuf};
+
+/// Keeps a copy of the bytes read so slices of the document can be handed out
+/// verbatim by their reader positions. Disabled recorders are a pass-through.
+pub(crate) struct Recorder<R> {
+    inner: R,
+    enabled: bool,
+    recorded: Vec<u8>,
+    // Stream position of `recorded[0]`.
+    base: u64,
+}
+
+impl<R> Recorder<R> {
+    pub(crate) fn new(inner: R, enabled: bool) -> Self {
+        Recorder {
+            inner,
+            enabled,
+            recorded: Vec::new(),
+            base: 0,
+        }
+    }
+
+    pub(crate) fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    pub(crate) fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Forgets everything before `position`; those bytes can no longer be sliced.
+    pub(crate) fn discard_before(&mut self, position: u64) {
+        let count = position.saturating_sub(self.base).min(self.recorded.len() as u64);
+        self.recorded.drain(..count as usize);
+        self.base += count;
+    }
+
+    /// The bytes between two stream positions, if they are still recorded.
+    pub(crate) fn slice(&self, start: u64, end: u64) -> Option<&[u8]> {
+        let start = usize::try_from(start.checked_sub(self.base)?).ok()?;
+        let end = usize::try_from(end.checked_sub(self.base)?).ok()?;
+        self.recorded.get(start..end)
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for Recorder<R> {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        let filled = buf.filled().len();
+        ready!(Pin::new(&mut this.inner).poll_read(cx, buf))?;
+        if this.enabled {
+            this.recorded.extend_from_slice(&buf.filled()[filled..]);
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+// Without Tokio, quick-xml reads the document through `std::io::Read`. The
+// inputs are blocking readers then, so a read that is pending went nowhere.
+#[cfg(not(feature = "tokio"))]
+impl<R: AsyncRead + Unpin> std::io::Read for Recorder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let mut buf = ReadBuf::new(buf);
+        let mut cx = Context::from_waker(std::task::Waker::noop());
+        match Pin::new(self).poll_read(&mut cx, &mut buf) {
+            Poll::Ready(result) => result.map(|()| buf.filled().len()),
+            Poll::Pending => Err(std::io::ErrorKind::WouldBlock.into()),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use tokio::io::AsyncReadExt;
+
+    #[tokio::test]
+    async fn test_slices_by_position() {
+        let mut recorder = Recorder::new(Cursor::new(b"0123456789".to_vec()), true);
+        let mut out = [0u8; 6];
+        recorder.read_exact(&mut out).await.unwrap();
+
+        assert_eq!(recorder.slice(2, 5), Some(&b"234"[..]));
+        recorder.discard_before(3);
+        assert_eq!(recorder.slice(2, 5), None);
+        assert_eq!(recorder.slice(3, 6), Some(&b"345"[..]));
+        assert_eq!(recorder.slice(3, 7), None);
+    }
+}
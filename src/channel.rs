@@ -0,0 +1,261 @@
+use serde::Serialize;
+
+use crate::ns;
+use crate::person::Person;
+use crate::rss_parser::{GradualRssChannel, XmlNode};
+use crate::url::{absolute, url_text};
+
+/// The feed's logo: RSS `<image>`, or Atom `<logo>` (falling back to `<icon>`),
+/// which only carry the URL.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ChannelImage {
+    pub url: Option<String>,
+    pub title: Option<String>,
+    pub link: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub description: Option<String>,
+}
+
+/// Feed-level metadata from RSS `<channel>` or Atom `<feed>` elements.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ChannelInfo {
+    pub title: Option<String>,
+    pub link: Option<String>,
+    pub description: Option<String>,
+    pub language: Option<String>,
+    pub last_build_date: Option<String>,
+    pub image: Option<ChannelImage>,
+    /// RSS `<managingEditor>` or the first Atom feed `<author>`.
+    pub author: Option<Person>,
+    /// RSS `<ttl>`: how many minutes the feed may be cached before fetching it again.
+    pub ttl: Option<u32>,
+    /// RSS `<skipHours>`: the hours (0-23, GMT) in which it shouldn't be fetched.
+    pub skip_hours: Vec<u8>,
+    /// RSS `<skipDays>`: the days (`Monday` to `Sunday`) on which it shouldn't be fetched.
+    pub skip_days: Vec<String>,
+    /// The WebSub hubs the feed is published to, from Atom (or `atom:link`)
+    /// links with `rel="hub"`.
+    pub hubs: Vec<String>,
+    /// The feed's own URL, from the `rel="self"` link, which WebSub uses as the topic.
+    pub self_link: Option<String>,
+    /// The next page of a paged feed (RFC 5005), from the `rel="next"` link.
+    pub next_page: Option<String>,
+    /// The archive document before this one (RFC 5005), from the `rel="prev-archive"` link.
+    pub prev_archive: Option<String>,
+    #[serde(skip)]
+    pending_author: Person,
+}
+
+impl ChannelImage {
+    fn from_url(url: Option<String>) -> Self {
+        ChannelImage {
+            url,
+            ..ChannelImage::default()
+        }
+    }
+}
+
+impl GradualRssChannel for ChannelInfo {
+    fn init() -> Self {
+        ChannelInfo::default()
+    }
+
+    fn populate(&mut self, node: XmlNode) {
+        if let Some(field) = node.path.strip_prefix("image/") {
+            let image = self.image.get_or_insert_with(ChannelImage::default);
+            match field {
+                "url" => image.url = url_text(node).map(|url| absolute(self.link.as_deref(), url)),
+                "title" => image.title = node.into_text(),
+                "link" => image.link = node.into_text(),
+                "width" => image.width = node.into_text().and_then(|width| width.trim().parse().ok()),
+                "height" => image.height = node.into_text().and_then(|height| height.trim().parse().ok()),
+                "description" => image.description = node.into_text(),
+                _ => {}
+            }
+            return;
+        }
+
+        // Atom links, also found in RSS as `atom:link`.
+        let atom_link = !node.path.contains('/') && node.extension_name(ns::ATOM, "atom").is_some_and(|name| name.eq_ignore_ascii_case("link"));
+        if (node.path == "link" || atom_link)
+            && let Some(href) = node.attr("href")
+        {
+            let href = absolute(node.base.as_deref(), href.to_string());
+            match node.attr("rel").unwrap_or("alternate") {
+                "alternate" if self.link.is_none() => self.link = Some(href),
+                "hub" => self.hubs.push(href),
+                "self" => self.self_link = Some(href),
+                "next" => self.next_page = Some(href),
+                "prev-archive" => self.prev_archive = Some(href),
+                _ => {}
+            }
+            return;
+        }
+
+        match node.path.as_str() {
+            "title" => self.title = node.into_text(),
+            // RSS links are text.
+            "link" => self.link = url_text(node),
+            "description" | "subtitle" => self.description = node.into_text(),
+            "language" => self.language = node.into_text(),
+            "lastbuilddate" | "updated" => self.last_build_date = node.into_text(),
+            "logo" => self.image = Some(ChannelImage::from_url(url_text(node))),
+            "icon" if self.image.is_none() => self.image = Some(ChannelImage::from_url(url_text(node))),
+            "managingeditor" => self.author = node.into_text().as_deref().and_then(Person::parse),
+            "ttl" => self.ttl = node.into_text().and_then(|ttl| ttl.trim().parse().ok()),
+            "skiphours/hour" => {
+                if let Some(hour) = node.into_text().and_then(|hour| hour.trim().parse().ok()).filter(|hour| *hour < 24) {
+                    self.skip_hours.push(hour);
+                }
+            }
+            "skipdays/day" => self.skip_days.extend(node.into_text().map(|day| day.trim().to_string())),
+            "author/name" => self.pending_author.name = node.into_text(),
+            "author/email" => self.pending_author.email = node.into_text(),
+            "author/uri" => self.pending_author.uri = node.into_text(),
+            "author" => {
+                let author = std::mem::take(&mut self.pending_author);
+                if self.author.is_none() && !author.is_empty() {
+                    self.author = Some(author);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    use super::*;
+    use crate::{RssItem, RssParser};
+    use std::io::Cursor;
+
+    const FEED: &str = r#"<?xml version="1.0"?>
+<rss version="2.0">
+    <channel>
+        <title>Example Channel</title>
+        <link>https://example.com/</link>
+        <description>News and more</description>
+        <language>en-us</language>
+        <lastBuildDate>Tue, 02 Jan 2024 00:00:00 GMT</lastBuildDate>
+        <managingEditor>editor@example.com (The Editor)</managingEditor>
+        <image>
+            <url>https://example.com/logo.png</url>
+            <title>Example Logo</title>
+            <link>https://example.com/home</link>
+            <width>88</width>
+            <height>31</height>
+        </image>
+        <item>
+            <title>First</title>
+        </item>
+        <item>
+            <title>Second</title>
+        </item>
+    </channel>
+</rss>"#;
+
+    #[tokio::test]
+    async fn test_channel_before_items() {
+        let mut parser = RssParser::<RssItem, _>::new(Cursor::new(FEED.as_bytes())).await.unwrap();
+        let channel: ChannelInfo = parser.channel().await.unwrap();
+
+        assert_eq!(channel.title.as_deref(), Some("Example Channel"));
+        assert_eq!(channel.link.as_deref(), Some("https://example.com/"));
+        assert_eq!(channel.description.as_deref(), Some("News and more"));
+        assert_eq!(channel.language.as_deref(), Some("en-us"));
+        assert_eq!(channel.last_build_date.as_deref(), Some("Tue, 02 Jan 2024 00:00:00 GMT"));
+        assert_eq!(channel.author.as_ref().and_then(|author| author.name.as_deref()), Some("The Editor"));
+
+        // The image's title and link must not override the channel's.
+        assert_eq!(
+            channel.image,
+            Some(ChannelImage {
+                url: Some("https://example.com/logo.png".to_string()),
+                title: Some("Example Logo".to_string()),
+                link: Some("https://example.com/home".to_string()),
+                width: Some(88),
+                height: Some(31),
+                description: None,
+            })
+        );
+
+        // Item titles must not leak into the channel, and no item is lost.
+        let first = parser.next().await.unwrap().unwrap();
+        assert_eq!(first.title.as_deref(), Some("First"));
+        assert_eq!(first.author, channel.author);
+        assert_eq!(parser.next().await.unwrap().unwrap().title.as_deref(), Some("Second"));
+        assert!(parser.next().await.unwrap().is_none());
+
+        let again: ChannelInfo = parser.channel().await.unwrap();
+        assert_eq!(again, channel);
+    }
+
+    #[tokio::test]
+    async fn test_channel_with_leading_empty_item() {
+        let feed = "<rss><channel><title>T</title><item/><item><title>Next</title></item></channel></rss>";
+        let mut parser = RssParser::<RssItem, _>::new(Cursor::new(feed.as_bytes())).await.unwrap();
+
+        let channel: ChannelInfo = parser.channel().await.unwrap();
+        assert_eq!(channel.title.as_deref(), Some("T"));
+        assert!(parser.next().await.unwrap().unwrap().title.is_none());
+        assert_eq!(parser.next().await.unwrap().unwrap().title.as_deref(), Some("Next"));
+    }
+
+    #[tokio::test]
+    async fn test_atom_feed_metadata() {
+        let feed = r#"<feed xmlns="http://www.w3.org/2005/Atom">
+    <title>Atom Site</title>
+    <subtitle>All the things</subtitle>
+    <link rel="self" href="https://example.org/feed.atom"/>
+    <link href="https://example.org/"/>
+    <updated>2024-01-01T00:00:00Z</updated>
+    <icon>https://example.org/favicon.ico</icon>
+    <author><name>Feed Author</name></author>
+    <entry><title>Entry</title></entry>
+    <entry><title>Own</title><author><name>Entry Author</name></author></entry>
+</feed>"#;
+        let mut parser = RssParser::<crate::AtomEntry, _>::new_atom(Cursor::new(feed.as_bytes())).await.unwrap();
+        let channel: ChannelInfo = parser.channel().await.unwrap();
+
+        assert_eq!(channel.title.as_deref(), Some("Atom Site"));
+        assert_eq!(channel.description.as_deref(), Some("All the things"));
+        assert_eq!(channel.link.as_deref(), Some("https://example.org/"));
+        assert_eq!(channel.last_build_date.as_deref(), Some("2024-01-01T00:00:00Z"));
+        assert_eq!(channel.image.and_then(|image| image.url).as_deref(), Some("https://example.org/favicon.ico"));
+        assert_eq!(channel.author, Some(Person::named("Feed Author")));
+        assert_eq!(channel.self_link.as_deref(), Some("https://example.org/feed.atom"));
+
+        // Entries without an author inherit the feed's.
+        let entry = parser.next().await.unwrap().unwrap();
+        assert_eq!(entry.title.as_deref(), Some("Entry"));
+        assert_eq!(entry.authors, vec![Person::named("Feed Author")]);
+        assert_eq!(parser.next().await.unwrap().unwrap().authors, vec![Person::named("Entry Author")]);
+    }
+
+    #[tokio::test]
+    async fn test_websub_links() {
+        let feed = r#"<rss xmlns:atom="http://www.w3.org/2005/Atom"><channel>
+    <link>https://example.com/</link>
+    <atom:link rel="hub" href="https://hub.example.com/"/>
+    <atom:link rel="hub" href="https://other-hub.example.com/"/>
+    <atom:link rel="self" href="https://example.com/feed.xml"/>
+    <item/>
+</channel></rss>"#;
+        let mut parser = RssParser::<RssItem, _>::new(Cursor::new(feed.as_bytes())).await.unwrap();
+        let channel: ChannelInfo = parser.channel().await.unwrap();
+        assert_eq!(channel.link.as_deref(), Some("https://example.com/"));
+        assert_eq!(channel.hubs, vec!["https://hub.example.com/", "https://other-hub.example.com/"]);
+        assert_eq!(channel.self_link.as_deref(), Some("https://example.com/feed.xml"));
+    }
+
+    #[tokio::test]
+    async fn test_channel_after_detect() {
+        let feed = r#"<feed><title>Detected</title><entry><title>E</title></entry></feed>"#;
+        let crate::FeedParser::Atom(mut parser) = crate::FeedParser::detect(Cursor::new(feed.as_bytes())).await.unwrap() else {
+            panic!("expected an Atom parser");
+        };
+        let channel: ChannelInfo = parser.channel().await.unwrap();
+        assert_eq!(channel.title.as_deref(), Some("Detected"));
+    }
+}
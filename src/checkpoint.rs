@@ -0,0 +1,46 @@
+use crate::builder::ParserOptions;
+use crate::rss_parser::XmlNode;
+
+/// Where a parser stood between two items, from `RssParser::checkpoint`, so
+/// `RssParser::resume` can carry on from there with a fresh input.
+///
+/// It holds the open elements around the items (with the namespaces they
+/// declare), the channel metadata read so far and the parser's options.
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+    pub(crate) offset: u64,
+    pub(crate) ancestors: Vec<XmlNode>,
+    pub(crate) channel_nodes: Vec<XmlNode>,
+    pub(crate) options: ParserOptions,
+    pub(crate) items: u64,
+    pub(crate) item_seen: bool,
+}
+
+impl Checkpoint {
+    /// The byte offset into the input where the resumed input has to start,
+    /// e.g. for a `Range: bytes=<offset>-` request.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Items returned before the checkpoint.
+    pub fn items(&self) -> u64 {
+        self.items
+    }
+
+    // The namespaces the open elements declare, innermost first, with `""`
+    // for the default namespace.
+    pub(crate) fn namespaces(&self) -> Vec<(String, String)> {
+        let mut namespaces = Vec::new();
+        for node in self.ancestors.iter().rev() {
+            for (key, value) in &node.attributes {
+                if key == "xmlns" {
+                    namespaces.push((String::new(), value.clone()));
+                } else if let Some(prefix) = key.strip_prefix("xmlns:") {
+                    namespaces.push((prefix.to_string(), value.clone()));
+                }
+            }
+        }
+        namespaces
+    }
+}
@@ -0,0 +1,251 @@
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::atom::AtomEntry;
+use crate::channel::ChannelInfo;
+#[cfg(feature = "dates")]
+use crate::date::parse_date;
+use crate::error::RssError;
+use crate::feed::{FeedFormat, FeedParser};
+use crate::feed_item::FeedItem;
+use crate::item::RssItem;
+use crate::writer::{AtomWriter, JsonFeedWriter, RssWriter};
+
+/// The output format of `convert`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetFormat {
+    Rss,
+    Atom,
+    JsonFeed,
+}
+
+/// Reads an RSS 2.0, RSS 1.0 or Atom feed and writes it to `output` as
+/// `target`, one item at a time. Returns the number of items converted.
+///
+/// Only the channel elements that come before the first item are carried over.
+/// With the `dates` feature, dates are rewritten in the target's format
+/// (RFC 822 for RSS, RFC 3339 otherwise) and entries without an `updated` date
+/// get the published one, which Atom requires; without it dates are copied as
+/// written.
+pub async fn convert<R, W>(input: R, output: W, target: TargetFormat) -> Result<usize, RssError>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    Transform::new().target(target).run(input, output).await
+}
+
+type Step<'a> = Box<dyn FnMut(FeedItem) -> Option<FeedItem> + Send + 'a>;
+
+/// Reads a feed, runs every item through the `filter` and `map` steps in the
+/// order they were added and writes the survivors out as a feed again, for
+/// proxies that strip ads or keep only the items matching a keyword.
+///
+/// Items pass through `FeedItem`, so whatever it has no field for (RSS
+/// comments and source, Media RSS) is dropped. The output is RSS 2.0 for RSS
+/// input (including RSS 1.0) and Atom for Atom input unless `target` says
+/// otherwise; see `convert` for how dates are handled.
+#[derive(Default)]
+pub struct Transform<'a> {
+    steps: Vec<Step<'a>>,
+    target: Option<TargetFormat>,
+}
+
+impl<'a> Transform<'a> {
+    pub fn new() -> Self {
+        Transform::default()
+    }
+
+    /// Drops the items `keep` returns false for.
+    pub fn filter(mut self, mut keep: impl FnMut(&FeedItem) -> bool + Send + 'a) -> Self {
+        self.steps.push(Box::new(move |item| keep(&item).then_some(item)));
+        self
+    }
+
+    /// Rewrites every item.
+    pub fn map(mut self, mut map: impl FnMut(FeedItem) -> FeedItem + Send + 'a) -> Self {
+        self.steps.push(Box::new(move |item| Some(map(item))));
+        self
+    }
+
+    /// Rewrites or drops every item.
+    pub fn filter_map(mut self, step: impl FnMut(FeedItem) -> Option<FeedItem> + Send + 'a) -> Self {
+        self.steps.push(Box::new(step));
+        self
+    }
+
+    pub fn target(mut self, target: TargetFormat) -> Self {
+        self.target = Some(target);
+        self
+    }
+
+    /// Returns the number of items written.
+    pub async fn run<R, W>(mut self, input: R, output: W) -> Result<usize, RssError>
+    where
+        R: AsyncRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        let mut parser = FeedParser::detect(input).await?;
+        let target = self.target.unwrap_or(match parser.format() {
+            FeedFormat::Rss | FeedFormat::Rdf => TargetFormat::Rss,
+            FeedFormat::Atom => TargetFormat::Atom,
+        });
+        let mut channel: ChannelInfo = parser.channel().await?;
+        channel.last_build_date = redate(channel.last_build_date, target);
+
+        let mut count = 0;
+        match target {
+            TargetFormat::Rss => {
+                let mut writer = RssWriter::new(output, &channel).await?;
+                while let Some(item) = self.next(&mut parser, target).await? {
+                    writer.write_item(&RssItem::from(item)).await?;
+                    count += 1;
+                }
+                writer.finish().await?;
+            }
+            TargetFormat::Atom => {
+                let mut writer = AtomWriter::new(output, &channel).await?;
+                while let Some(item) = self.next(&mut parser, target).await? {
+                    writer.write_item(&AtomEntry::from(item)).await?;
+                    count += 1;
+                }
+                writer.finish().await?;
+            }
+            TargetFormat::JsonFeed => {
+                let mut writer = JsonFeedWriter::new(output, &channel).await?;
+                while let Some(item) = self.next(&mut parser, target).await? {
+                    writer.write_item(&item).await?;
+                    count += 1;
+                }
+                writer.finish().await?;
+            }
+        }
+        Ok(count)
+    }
+
+    // The next item that made it through every step.
+    async fn next<R: AsyncRead + Unpin>(&mut self, parser: &mut FeedParser<R>, target: TargetFormat) -> Result<Option<FeedItem>, RssError> {
+        'items: while let Some(mut item) = parser.next().await? {
+            for step in &mut self.steps {
+                match step(item) {
+                    Some(kept) => item = kept,
+                    None => continue 'items,
+                }
+            }
+            return Ok(Some(convert_item(item, target)));
+        }
+        Ok(None)
+    }
+}
+
+fn convert_item(mut item: FeedItem, target: TargetFormat) -> FeedItem {
+    item.published = redate(item.published, target);
+    item.updated = redate(item.updated, target);
+    if target == TargetFormat::Atom && item.updated.is_none() {
+        item.updated = item.published.clone();
+    }
+    item
+}
+
+#[cfg(feature = "dates")]
+fn redate(date: Option<String>, target: TargetFormat) -> Option<String> {
+    let date = date?;
+    Some(match parse_date(&date) {
+        Some(parsed) if target == TargetFormat::Rss => parsed.to_rfc2822(),
+        Some(parsed) => parsed.to_string(),
+        None => date,
+    })
+}
+
+#[cfg(not(feature = "dates"))]
+fn redate(date: Option<String>, _target: TargetFormat) -> Option<String> {
+    date
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RssParser;
+    use std::io::Cursor;
+
+    const RSS: &str = r#"<rss version="2.0"><channel>
+<title>Site</title><link>https://example.com/</link><description>News</description>
+<item><title>One</title><link>https://example.com/1</link><guid>https://example.com/1</guid>
+<enclosure url="https://example.com/1.mp3" type="audio/mpeg" length="5"/><category>Tech</category>
+<pubDate>Tue, 02 Jan 2024 15:04:05 GMT</pubDate></item>
+<item><title>Two &amp; more</title><guid isPermaLink="false">two</guid></item>
+</channel></rss>"#;
+
+    #[tokio::test]
+    async fn test_rss_to_atom() {
+        let mut out = Vec::new();
+        assert_eq!(convert(Cursor::new(RSS.as_bytes()), &mut out, TargetFormat::Atom).await.unwrap(), 2);
+
+        let mut parser = RssParser::<AtomEntry, _>::new_atom(Cursor::new(out)).await.unwrap();
+        let feed: ChannelInfo = parser.channel().await.unwrap();
+        assert_eq!(feed.title.as_deref(), Some("Site"));
+
+        let first = parser.next().await.unwrap().unwrap();
+        assert_eq!(first.id.as_deref(), Some("https://example.com/1"));
+        assert_eq!(first.link.as_deref(), Some("https://example.com/1"));
+        assert_eq!(first.links[1].rel, "enclosure");
+        assert_eq!(first.categories[0].name, "Tech");
+        assert!(first.updated.is_some());
+        assert_eq!(parser.next().await.unwrap().unwrap().title.as_deref(), Some("Two & more"));
+    }
+
+    #[tokio::test]
+    async fn test_atom_to_rss() {
+        let atom = r#"<feed xmlns="http://www.w3.org/2005/Atom"><title>Site</title><link href="https://example.org/"/>
+<entry><id>urn:1</id><title>One</title><link href="https://example.org/1"/><published>2024-01-02T15:04:05Z</published></entry>
+</feed>"#;
+        let mut out = Vec::new();
+        convert(Cursor::new(atom.as_bytes()), &mut out, TargetFormat::Rss).await.unwrap();
+
+        let mut parser = RssParser::<RssItem, _>::new(Cursor::new(out)).await.unwrap();
+        let item = parser.next().await.unwrap().unwrap();
+        assert_eq!(item.link.as_deref(), Some("https://example.org/1"));
+        assert_eq!(item.guid.as_ref().map(|guid| (guid.value.as_str(), guid.is_permalink)), Some(("urn:1", false)));
+        #[cfg(feature = "dates")]
+        assert_eq!(item.pub_date.as_deref(), Some("Tue, 02 Jan 2024 15:04:05 +0000"));
+    }
+
+    #[tokio::test]
+    async fn test_transform() {
+        let mut seen = 0;
+        let mut out = Vec::new();
+        let written = Transform::new()
+            .map(|mut item| {
+                seen += 1;
+                item.title = item.title.map(|title| title.to_uppercase());
+                item
+            })
+            .filter(|item| item.title.as_deref() != Some("ONE"))
+            .run(Cursor::new(RSS.as_bytes()), &mut out)
+            .await
+            .unwrap();
+        assert_eq!((written, seen), (1, 2));
+
+        // RSS stays RSS.
+        let mut parser = RssParser::<RssItem, _>::new(Cursor::new(out)).await.unwrap();
+        assert_eq!(parser.channel::<ChannelInfo>().await.unwrap().description.as_deref(), Some("News"));
+        let item = parser.next().await.unwrap().unwrap();
+        assert_eq!(item.title.as_deref(), Some("TWO & MORE"));
+        assert!(parser.next().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_rss_to_json_feed() {
+        let mut out = Vec::new();
+        convert(Cursor::new(RSS.as_bytes()), &mut out, TargetFormat::JsonFeed).await.unwrap();
+
+        let feed: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(feed["title"], "Site");
+        assert_eq!(feed["home_page_url"], "https://example.com/");
+        let items = feed["items"].as_array().unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0]["url"], "https://example.com/1");
+        assert_eq!(items[0]["tags"][0], "Tech");
+        assert_eq!(items[0]["attachments"][0]["mime_type"], "audio/mpeg");
+        assert_eq!(items[1]["id"], "two");
+    }
+}
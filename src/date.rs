@@ -0,0 +1,378 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+/// A date and time with the fixed UTC offset it was written with.
+///
+/// Comparisons and equality go by the instant, so `12:00+01:00` equals `11:00Z`.
+#[derive(Debug, Clone, Copy)]
+pub struct DateTime {
+    year: i32,
+    month: u8,
+    day: u8,
+    hour: u8,
+    minute: u8,
+    second: u8,
+    offset_minutes: i32,
+}
+
+impl DateTime {
+    /// `None` unless the fields make a real date and time, with an offset
+    /// of less than a day. A leap second is folded into the one before it.
+    pub fn new(year: i32, month: u8, day: u8, hour: u8, minute: u8, second: u8, offset_minutes: i32) -> Option<Self> {
+        if offset_minutes.abs() >= 24 * 60 {
+            return None;
+        }
+        build(year, month, day, (hour, minute, second), offset_minutes)
+    }
+
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+
+    /// From 1 for January.
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+
+    pub fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    pub fn minute(&self) -> u8 {
+        self.minute
+    }
+
+    pub fn second(&self) -> u8 {
+        self.second
+    }
+
+    /// Offset from UTC in minutes, e.g. `-240` for EDT.
+    pub fn offset_minutes(&self) -> i32 {
+        self.offset_minutes
+    }
+
+    /// Seconds since 1970-01-01T00:00:00Z.
+    pub fn timestamp(&self) -> i64 {
+        let days = days_from_civil(self.year, self.month, self.day);
+        days * 86_400 + self.hour as i64 * 3600 + self.minute as i64 * 60 + self.second as i64
+            - self.offset_minutes as i64 * 60
+    }
+
+    /// Formats as RFC 822 / RFC 2822 for RSS, e.g. `Tue, 02 Jan 2024 03:04:05 +0100`.
+    pub fn to_rfc2822(&self) -> String {
+        const DAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+        const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+        let weekday = DAYS[days_from_civil(self.year, self.month, self.day).rem_euclid(7) as usize];
+        let sign = if self.offset_minutes < 0 { '-' } else { '+' };
+        format!(
+            "{}, {:02} {} {:04} {:02}:{:02}:{:02} {}{:02}{:02}",
+            weekday,
+            self.day,
+            MONTHS[self.month as usize - 1],
+            self.year,
+            self.hour,
+            self.minute,
+            self.second,
+            sign,
+            self.offset_minutes.abs() / 60,
+            self.offset_minutes.abs() % 60
+        )
+    }
+}
+
+impl PartialEq for DateTime {
+    fn eq(&self, other: &Self) -> bool {
+        self.timestamp() == other.timestamp()
+    }
+}
+
+impl Eq for DateTime {}
+
+impl PartialOrd for DateTime {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for DateTime {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.timestamp().cmp(&other.timestamp())
+    }
+}
+
+/// Formats as RFC 3339, e.g. `2024-01-02T03:04:05+01:00`.
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )?;
+        match self.offset_minutes {
+            0 => write!(f, "Z"),
+            offset => {
+                let sign = if offset < 0 { '-' } else { '+' };
+                write!(f, "{}{:02}:{:02}", sign, offset.abs() / 60, offset.abs() % 60)
+            }
+        }
+    }
+}
+
+/// Parses the date formats found in feeds: RFC 822 / RFC 2822 (`pubDate`),
+/// RFC 3339 (Atom) and their common sloppy variants such as two-digit years,
+/// missing seconds, full month names, named time zones or no zone at all
+/// (taken as UTC, as are zone names it doesn't know).
+pub fn parse_date(input: &str) -> Option<DateTime> {
+    let input = input.trim();
+    if input.as_bytes().first().is_some_and(u8::is_ascii_digit) && input.as_bytes().get(4) == Some(&b'-') {
+        parse_rfc3339(input)
+    } else {
+        parse_rfc822(input)
+    }
+}
+
+fn parse_rfc3339(input: &str) -> Option<DateTime> {
+    let (date, rest) = input.split_at(input.find(['T', 't', ' ']).unwrap_or(input.len()));
+    let mut parts = date.split('-');
+    let year = parts.next()?.parse().ok()?;
+    let month = parts.next()?.parse().ok()?;
+    let day = parts.next()?.parse().ok()?;
+    if parts.next().is_some() {
+        return None;
+    }
+
+    let rest = rest.get(1..).unwrap_or("").trim();
+    if rest.is_empty() {
+        return build(year, month, day, (0, 0, 0), 0);
+    }
+    let zone_at = rest.find(['Z', 'z', '+', '-']).unwrap_or(rest.len());
+    let (time, zone) = rest.split_at(zone_at);
+    let offset = match zone.trim() {
+        "" => 0,
+        zone => parse_zone(zone)?,
+    };
+    build(year, month, day, parse_time(time.trim())?, offset)
+}
+
+fn parse_rfc822(input: &str) -> Option<DateTime> {
+    let mut tokens = input
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|token| !token.is_empty())
+        .peekable();
+
+    // The weekday is optional and not checked against the date.
+    if tokens.peek()?.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        tokens.next();
+    }
+
+    let day = tokens.next()?.parse().ok()?;
+    let month = month_from_name(tokens.next()?)?;
+    let year = match tokens.next()? {
+        year if year.len() <= 2 => {
+            let year: i32 = year.parse().ok()?;
+            if year < 50 { 2000 + year } else { 1900 + year }
+        }
+        year => year.parse().ok()?,
+    };
+    let time = match tokens.next() {
+        Some(time) => parse_time(time)?,
+        None => (0, 0, 0),
+    };
+    let offset = match tokens.next() {
+        Some(zone) => parse_zone(zone)?,
+        None => 0,
+    };
+    build(year, month, day, time, offset)
+}
+
+// `HH:MM`, `HH:MM:SS` or `HH:MM:SS.fraction`; the fraction is dropped.
+fn parse_time(time: &str) -> Option<(u8, u8, u8)> {
+    let mut parts = time.split(':');
+    let hour = parts.next()?.parse().ok()?;
+    let minute = parts.next()?.parse().ok()?;
+    let second = match parts.next() {
+        Some(second) => second.split(['.', ',']).next()?.parse().ok()?,
+        None => 0,
+    };
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((hour, minute, second))
+}
+
+fn parse_zone(zone: &str) -> Option<i32> {
+    let named = match zone.to_ascii_uppercase().as_str() {
+        "Z" | "GMT" | "UT" | "UTC" => Some(0),
+        "EST" => Some(-5 * 60),
+        "EDT" => Some(-4 * 60),
+        "CST" => Some(-6 * 60),
+        "CDT" => Some(-5 * 60),
+        "MST" => Some(-7 * 60),
+        "MDT" => Some(-6 * 60),
+        "PST" => Some(-8 * 60),
+        "PDT" => Some(-7 * 60),
+        "AKST" => Some(-9 * 60),
+        "AKDT" => Some(-8 * 60),
+        "HST" => Some(-10 * 60),
+        "AST" => Some(-4 * 60),
+        "ADT" => Some(-3 * 60),
+        "WET" => Some(0),
+        "WEST" | "BST" | "CET" | "MET" => Some(60),
+        "CEST" | "MEST" | "EET" => Some(2 * 60),
+        "EEST" | "MSK" => Some(3 * 60),
+        "IST" => Some(5 * 60 + 30),
+        "SGT" | "HKT" | "AWST" => Some(8 * 60),
+        "JST" | "KST" => Some(9 * 60),
+        "ACST" => Some(9 * 60 + 30),
+        "AEST" => Some(10 * 60),
+        "ACDT" => Some(10 * 60 + 30),
+        "AEDT" => Some(11 * 60),
+        "NZST" => Some(12 * 60),
+        "NZDT" => Some(13 * 60),
+        // Other names, including military letters, are taken as UTC rather
+        // than losing the whole date.
+        name if (1..=5).contains(&name.len()) && name.bytes().all(|b| b.is_ascii_alphabetic()) => Some(0),
+        _ => None,
+    };
+    if named.is_some() {
+        return named;
+    }
+
+    let sign = match zone.as_bytes().first()? {
+        b'+' => 1,
+        b'-' => -1,
+        _ => return None,
+    };
+    let digits: String = zone[1..].chars().filter(|c| *c != ':').collect();
+    if digits.len() != 4 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let hours: i32 = digits[..2].parse().ok()?;
+    let minutes: i32 = digits[2..].parse().ok()?;
+    if hours >= 24 || minutes >= 60 {
+        return None;
+    }
+    Some(sign * (hours * 60 + minutes))
+}
+
+fn month_from_name(name: &str) -> Option<u8> {
+    const MONTHS: [&str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+    let name = name.to_ascii_lowercase();
+    let position = MONTHS.iter().position(|month| name.starts_with(month))?;
+    Some(position as u8 + 1)
+}
+
+fn build(year: i32, month: u8, day: u8, (hour, minute, second): (u8, u8, u8), offset_minutes: i32) -> Option<DateTime> {
+    let valid = (1..=12).contains(&month)
+        && day >= 1
+        && day <= days_in_month(year, month)
+        && hour < 24
+        && minute < 60
+        && second <= 60;
+    valid.then_some(DateTime {
+        year,
+        month,
+        day,
+        hour,
+        minute,
+        // Leap seconds are folded into the preceding second.
+        second: second.min(59),
+        offset_minutes,
+    })
+}
+
+fn days_in_month(year: i32, month: u8) -> u8 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// Days since the Unix epoch for a proleptic Gregorian date (Howard Hinnant's algorithm).
+fn days_from_civil(year: i32, month: u8, day: u8) -> i64 {
+    let year = if month <= 2 { year as i64 - 1 } else { year as i64 };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = month as i64;
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rfc822() {
+        let date = parse_date("Tue, 02 Jan 2024 15:04:05 GMT").unwrap();
+        assert_eq!(date.timestamp(), 1_704_207_845);
+        assert_eq!(date.to_string(), "2024-01-02T15:04:05Z");
+
+        let date = parse_date("Sat, 07 Sep 2002 00:00:01 -0400").unwrap();
+        assert_eq!(date.offset_minutes(), -240);
+        assert_eq!(date.to_string(), "2002-09-07T00:00:01-04:00");
+        assert_eq!(date, parse_date("Sat, 07 Sep 2002 04:00:01 +0000").unwrap());
+        assert_eq!(date.to_rfc2822(), "Sat, 07 Sep 2002 00:00:01 -0400");
+        assert_eq!(parse_date("2024-01-02T15:04:05+05:30").unwrap().to_rfc2822(), "Tue, 02 Jan 2024 15:04:05 +0530");
+    }
+
+    #[test]
+    fn test_sloppy_rfc822() {
+        let expected = parse_date("Mon, 01 Jan 2024 09:30:00 GMT").unwrap();
+        for input in [
+            "Monday, 1 January 2024 09:30:00 GMT",
+            "01 Jan 24 09:30 UT",
+            "Mon,  1 Jan 2024 09:30:00",
+            "Mon, 01 Jan 2024 04:30:00 EST",
+            "1 Jan 2024 10:30:00 +01:00",
+            "Mon, 01 Jan 2024 10:30:00 CET",
+            "Mon, 01 Jan 2024 20:30:00 AEDT",
+            "Mon, 01 Jan 2024 09:30:00 XYZ",
+        ] {
+            assert_eq!(parse_date(input), Some(expected), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_rfc3339() {
+        let date = parse_date("2003-12-13T08:29:29-04:00").unwrap();
+        assert_eq!(date.to_string(), "2003-12-13T08:29:29-04:00");
+        assert_eq!(date, parse_date("2003-12-13T12:29:29Z").unwrap());
+
+        assert_eq!(parse_date("2024-02-29T10:00:00.123456+0000"), parse_date("2024-02-29 10:00:00"));
+        assert_eq!(parse_date("2024-02-29").unwrap().timestamp(), 1_709_164_800);
+    }
+
+    #[test]
+    fn test_invalid_dates() {
+        for input in ["", "yesterday", "2023-02-29T00:00:00Z", "Mon, 32 Jan 2024", "Mon, 01 Foo 2024", "2024-01-01T25:00:00Z"] {
+            assert!(parse_date(input).is_none(), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_new() {
+        let date = DateTime::new(2024, 2, 29, 23, 59, 60, 90).unwrap();
+        assert_eq!((date.month(), date.second()), (2, 59));
+        assert_eq!(date.to_rfc2822(), "Thu, 29 Feb 2024 23:59:59 +0130");
+        assert!(DateTime::new(2024, 0, 1, 0, 0, 0, 0).is_none());
+        assert!(DateTime::new(2024, 13, 1, 0, 0, 0, 0).is_none());
+        assert!(DateTime::new(2023, 2, 29, 0, 0, 0, 0).is_none());
+        assert!(DateTime::new(2024, 1, 1, 0, 0, 0, 24 * 60).is_none());
+    }
+
+    #[test]
+    fn test_ordering() {
+        let earlier = parse_date("2024-01-01T12:00:00+02:00").unwrap();
+        let later = parse_date("2024-01-01T11:00:00Z").unwrap();
+        assert!(earlier < later);
+        assert_eq!(parse_date("1969-12-31T23:59:59Z").unwrap().timestamp(), -1);
+    }
+}
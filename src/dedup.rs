@@ -0,0 +1,362 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tokio_stream::Stream;
+
+#[cfg(feature = "dates")]
+use crate::aggregate::Dated;
+use crate::atom::AtomEntry;
+#[cfg(feature = "dates")]
+use crate::date::DateTime;
+use crate::diff::{Diff, Snapshot};
+use crate::error::RssError;
+use crate::feed_item::FeedItem;
+use crate::filters::FilterItems;
+use crate::item::{fnv1a, RssItem};
+#[cfg(feature = "dates")]
+use crate::range::{Since, Until};
+use crate::range::TakeItems;
+use crate::store::SeenStore;
+
+const DEFAULT_CAPACITY: usize = 10_000;
+
+/// Items that `dedup` can tell apart.
+pub trait Identify {
+    /// A stable key for the item, or `None` if it has nothing to go by.
+    fn identity(&self) -> Option<String>;
+}
+
+/// See `RssItem::identity`.
+impl Identify for RssItem {
+    fn identity(&self) -> Option<String> {
+        RssItem::identity(self)
+    }
+}
+
+/// The id, then the link, then a hash of the title (or summary).
+impl Identify for AtomEntry {
+    fn identity(&self) -> Option<String> {
+        identity(self.id.as_deref(), self.link.as_deref(), self.title.as_deref().or(self.summary.as_deref()))
+    }
+}
+
+/// The id, then the link, then a hash of the title (or summary).
+impl Identify for FeedItem {
+    fn identity(&self) -> Option<String> {
+        identity(self.id.as_deref(), self.link(), self.title.as_deref().or(self.summary.as_deref()))
+    }
+}
+
+fn identity(id: Option<&str>, link: Option<&str>, text: Option<&str>) -> Option<String> {
+    id.or(link)
+        .map(String::from)
+        .or_else(|| text.map(|text| format!("hash:{:016x}", fnv1a(text.as_bytes()))))
+}
+
+/// Remembers the identities `dedup` has let through.
+pub trait SeenSet {
+    /// Records `key`, returning false if it was already there.
+    fn insert(&mut self, key: String) -> bool;
+}
+
+/// Remembers everything, for streams known to be finite.
+impl SeenSet for HashSet<String> {
+    fn insert(&mut self, key: String) -> bool {
+        HashSet::insert(self, key)
+    }
+}
+
+/// Remembers the `capacity` most recently seen identities, forgetting the
+/// least recently seen first.
+#[derive(Debug, Clone)]
+pub struct LruSet {
+    capacity: usize,
+    // Each key's latest sighting; `order` keeps stale sightings until they reach the front.
+    seen: HashMap<String, u64>,
+    order: VecDeque<(u64, String)>,
+    clock: u64,
+}
+
+impl LruSet {
+    pub fn new(capacity: usize) -> Self {
+        LruSet {
+            capacity: capacity.max(1),
+            seen: HashMap::new(),
+            order: VecDeque::new(),
+            clock: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.seen.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.seen.is_empty()
+    }
+}
+
+impl Default for LruSet {
+    fn default() -> Self {
+        LruSet::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl SeenSet for LruSet {
+    fn insert(&mut self, key: String) -> bool {
+        self.clock += 1;
+        let new = self.seen.insert(key.clone(), self.clock).is_none();
+        self.order.push_back((self.clock, key));
+        while self.seen.len() > self.capacity
+            && let Some((tick, key)) = self.order.pop_front()
+        {
+            if self.seen.get(&key) == Some(&tick) {
+                self.seen.remove(&key);
+            }
+        }
+        // Don't let repeated sightings of the same keys grow `order` unbounded.
+        if self.order.len() > 2 * self.capacity {
+            let seen = &self.seen;
+            self.order.retain(|(tick, key)| seen.get(key) == Some(tick));
+        }
+        new
+    }
+}
+
+/// Skips items whose identity was seen before, from `RssStreamExt::dedup`.
+///
+/// Errors and items without an identity are passed on.
+pub struct Dedup<S, K = LruSet> {
+    stream: S,
+    seen: K,
+}
+
+impl<S, K> Dedup<S, K> {
+    /// The seen-set, e.g. to carry it over to the next fetch of the feed.
+    pub fn into_seen(self) -> K {
+        self.seen
+    }
+}
+
+impl<S: Unpin, K> Unpin for Dedup<S, K> {}
+
+impl<S, T, K> Stream for Dedup<S, K>
+where
+    S: Stream<Item = Result<T, RssError>> + Unpin,
+    T: Identify,
+    K: SeenSet,
+{
+    type Item = Result<T, RssError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            match Pin::new(&mut this.stream).poll_next(cx) {
+                Poll::Ready(Some(Ok(item))) => {
+                    if item.identity().is_none_or(|key| this.seen.insert(key)) {
+                        return Poll::Ready(Some(Ok(item)));
+                    }
+                }
+                other => return other,
+            }
+        }
+    }
+}
+
+type Lookup<T, K> = Pin<Box<dyn Future<Output = (K, io::Result<bool>, T)> + Send>>;
+
+/// Like `Dedup`, but checking a `SeenStore`, from `RssStreamExt::dedup_store`.
+///
+/// New identities are stored with the Unix time they were first seen. A store
+/// error is returned in place of the item it was checking.
+pub struct DedupStore<S, T, K> {
+    stream: S,
+    store: Option<K>,
+    pending: Option<Lookup<T, K>>,
+}
+
+impl<S, T, K> DedupStore<S, T, K> {
+    /// The store, unless a lookup was abandoned half way.
+    pub fn into_store(self) -> Option<K> {
+        self.store
+    }
+}
+
+impl<S: Unpin, T, K> Unpin for DedupStore<S, T, K> {}
+
+impl<S, T, K> Stream for DedupStore<S, T, K>
+where
+    S: Stream<Item = Result<T, RssError>> + Unpin,
+    T: Identify + Send + 'static,
+    K: SeenStore + Send + Sync + 'static,
+{
+    type Item = Result<T, RssError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(pending) = this.pending.as_mut() {
+                let Poll::Ready((store, new, item)) = pending.as_mut().poll(cx) else {
+                    return Poll::Pending;
+                };
+                this.pending = None;
+                this.store = Some(store);
+                match new {
+                    Ok(true) => return Poll::Ready(Some(Ok(item))),
+                    Ok(false) => continue,
+                    Err(err) => return Poll::Ready(Some(Err(err.into()))),
+                }
+            }
+            let item = match Pin::new(&mut this.stream).poll_next(cx) {
+                Poll::Ready(Some(Ok(item))) => item,
+                other => return other,
+            };
+            let (Some(key), Some(mut store)) = (item.identity(), this.store.take()) else {
+                return Poll::Ready(Some(Ok(item)));
+            };
+            this.pending = Some(Box::pin(async move {
+                let new = match store.get(&key).await {
+                    Ok(Some(_)) => Ok(false),
+                    Ok(None) => {
+                        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+                        store.put(key, now.as_secs().to_string()).await.map(|()| true)
+                    }
+                    Err(err) => Err(err),
+                };
+                (store, new, item)
+            }));
+        }
+    }
+}
+
+/// Adapters for streams of parsed items, such as `RssStream`.
+pub trait RssStreamExt<T>: Stream<Item = Result<T, RssError>> + Sized {
+    /// Drops items already seen by guid, link or content hash, remembering the
+    /// last 10,000 identities.
+    fn dedup(self) -> Dedup<Self> {
+        self.dedup_with(LruSet::default())
+    }
+
+    /// Like `dedup`, with `seen` deciding what was already seen.
+    fn dedup_with<K: SeenSet>(self, seen: K) -> Dedup<Self, K> {
+        Dedup { stream: self, seen }
+    }
+
+    /// Turns the items into `ItemChange`s against the `previous` fetch,
+    /// skipping the ones that haven't changed. Contents are compared through
+    /// their `Serialize` output.
+    fn diff(self, previous: Snapshot) -> Diff<Self>
+    where
+        T: serde::Serialize,
+    {
+        Diff::new(self, previous)
+    }
+
+    /// Keeps the items `predicate` accepts, such as the ready-made ones in `filters`.
+    fn filter_items<F: FnMut(&T) -> bool>(self, predicate: F) -> FilterItems<Self, F> {
+        FilterItems { stream: self, predicate }
+    }
+
+    /// Ends after `count` items, dropping the rest of the stream unread.
+    fn take_items(self, count: usize) -> TakeItems<Self> {
+        TakeItems::new(self, count)
+    }
+
+    /// Items dated `since` or later, ending at the first older one; see `Since`.
+    #[cfg(feature = "dates")]
+    fn since(self, since: DateTime) -> Since<Self>
+    where
+        T: Dated,
+    {
+        Since::new(self, since)
+    }
+
+    /// Skips items dated after `until`.
+    #[cfg(feature = "dates")]
+    fn until(self, until: DateTime) -> Until<Self>
+    where
+        T: Dated,
+    {
+        Until::new(self, until)
+    }
+
+    /// Like `dedup`, remembering identities in `store` instead of memory.
+    fn dedup_store<K: SeenStore>(self, store: K) -> DedupStore<Self, T, K> {
+        DedupStore {
+            stream: self,
+            store: Some(store),
+            pending: None,
+        }
+    }
+}
+
+impl<S, T> RssStreamExt<T> for S where S: Stream<Item = Result<T, RssError>> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::MemoryStore;
+    use crate::RssParser;
+    use std::io::Cursor;
+    use tokio_stream::StreamExt;
+
+    #[tokio::test]
+    async fn test_dedup() {
+        let feed = r#"<rss><channel>
+<item><title>One</title><guid>1</guid></item>
+<item><title>One again</title><guid>1</guid></item>
+<item><title>Two</title><link>https://example.com/2</link></item>
+<item><title>Two again</title><link>https://example.com/2</link></item>
+<item><title>Three</title></item>
+<item><title>Three</title></item>
+<item><category>untitled</category></item>
+<item><category>untitled</category></item>
+</channel></rss>"#;
+        let parser = RssParser::<RssItem, _>::new(Cursor::new(feed.as_bytes())).await.unwrap();
+        let titles: Vec<Option<String>> = parser.into_stream().dedup().map(|item| item.unwrap().title).collect().await;
+        assert_eq!(
+            titles,
+            vec![Some("One".to_string()), Some("Two".to_string()), Some("Three".to_string()), None, None]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dedup_store() {
+        let feed = |guids: &[&str]| {
+            let items: String = guids.iter().map(|guid| format!("<item><guid>{}</guid></item>", guid)).collect();
+            Cursor::new(format!("<rss><channel>{}</channel></rss>", items).into_bytes())
+        };
+        let guids = |items: Vec<Result<RssItem, RssError>>| -> Vec<String> {
+            items.into_iter().map(|item| item.unwrap().guid.unwrap().value).collect()
+        };
+
+        let parser = RssParser::<RssItem, _>::new(feed(&["1", "2", "1"])).await.unwrap();
+        let mut items = parser.into_stream().dedup_store(MemoryStore::new());
+        let first: Vec<_> = (&mut items).collect().await;
+        assert_eq!(guids(first), vec!["1", "2"]);
+
+        // The next fetch only lets the new item through.
+        let store = items.into_store().unwrap();
+        assert_eq!(store.len(), 2);
+        let parser = RssParser::<RssItem, _>::new(feed(&["3", "2"])).await.unwrap();
+        let second: Vec<_> = parser.into_stream().dedup_store(store).collect().await;
+        assert_eq!(guids(second), vec!["3"]);
+    }
+
+    #[test]
+    fn test_lru_set() {
+        let mut seen = LruSet::new(2);
+        assert!(seen.insert("a".to_string()));
+        assert!(seen.insert("b".to_string()));
+        assert!(!seen.insert("a".to_string()));
+        // "b" is now the least recently seen.
+        assert!(seen.insert("c".to_string()));
+        assert_eq!(seen.len(), 2);
+        assert!(!seen.insert("a".to_string()));
+        assert!(seen.insert("b".to_string()));
+    }
+}
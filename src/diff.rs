@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use serde::{Deserialize, Serialize};
+use tokio_stream::Stream;
+
+use crate::dedup::Identify;
+use crate::error::RssError;
+use crate::item::fnv1a;
+
+/// An item that wasn't in the previous fetch, or was but has changed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ItemChange<T> {
+    Added(T),
+    Updated(T),
+}
+
+impl<T> ItemChange<T> {
+    pub fn item(&self) -> &T {
+        match self {
+            ItemChange::Added(item) | ItemChange::Updated(item) => item,
+        }
+    }
+
+    pub fn into_item(self) -> T {
+        match self {
+            ItemChange::Added(item) | ItemChange::Updated(item) => item,
+        }
+    }
+}
+
+/// The identities of a fetch's items with a hash of their contents, for
+/// `RssStreamExt::diff` to compare the next fetch against.
+///
+/// Serializable, so it can be kept between runs.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    // `None` for identities given without contents, which are never reported as updated.
+    items: HashMap<String, Option<u64>>,
+}
+
+impl Snapshot {
+    pub fn new() -> Self {
+        Snapshot::default()
+    }
+
+    /// A snapshot of just the identities, e.g. from `RssItem::identity`.
+    /// Items with these identities are not reported again, whatever changed.
+    pub fn from_identities(identities: impl IntoIterator<Item = String>) -> Self {
+        Snapshot {
+            items: identities.into_iter().map(|identity| (identity, None)).collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn contains(&self, identity: &str) -> bool {
+        self.items.contains_key(identity)
+    }
+
+    // How the item changed since this snapshot, with the entry it gets in the next one.
+    fn compare<T: Identify + Serialize>(&self, item: &T) -> (Change, String, Option<u64>) {
+        let hash = serde_json::to_vec(item).ok().map(|contents| fnv1a(&contents));
+        let identity = item
+            .identity()
+            .unwrap_or_else(|| format!("hash:{:016x}", hash.unwrap_or_default()));
+        let change = match self.items.get(&identity) {
+            None => Change::Added,
+            Some(Some(previous)) if hash.is_some_and(|hash| hash != *previous) => Change::Updated,
+            Some(_) => Change::Unchanged,
+        };
+        (change, identity, hash)
+    }
+}
+
+enum Change {
+    Added,
+    Updated,
+    Unchanged,
+}
+
+/// The new and changed items of a stream, from `RssStreamExt::diff`.
+///
+/// Errors are passed on.
+pub struct Diff<S> {
+    stream: S,
+    previous: Snapshot,
+    current: Snapshot,
+}
+
+impl<S> Diff<S> {
+    pub(crate) fn new(stream: S, previous: Snapshot) -> Self {
+        Diff {
+            stream,
+            previous,
+            current: Snapshot::new(),
+        }
+    }
+
+    /// The items read so far, as the snapshot for the next fetch.
+    pub fn into_snapshot(self) -> Snapshot {
+        self.current
+    }
+}
+
+impl<S: Unpin> Unpin for Diff<S> {}
+
+impl<S, T> Stream for Diff<S>
+where
+    S: Stream<Item = Result<T, RssError>> + Unpin,
+    T: Identify + Serialize,
+{
+    type Item = Result<ItemChange<T>, RssError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            let item = match Pin::new(&mut this.stream).poll_next(cx) {
+                Poll::Ready(Some(Ok(item))) => item,
+                Poll::Ready(Some(Err(err))) => return Poll::Ready(Some(Err(err))),
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            };
+            let (change, identity, hash) = this.previous.compare(&item);
+            // A duplicate within the fetch is only reported once.
+            if this.current.items.insert(identity, hash).is_some() {
+                continue;
+            }
+            match change {
+                Change::Added => return Poll::Ready(Some(Ok(ItemChange::Added(item)))),
+                Change::Updated => return Poll::Ready(Some(Ok(ItemChange::Updated(item)))),
+                Change::Unchanged => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RssItem, RssParser, RssStreamExt};
+    use std::io::Cursor;
+    use tokio_stream::StreamExt;
+
+    fn feed(items: &[(&str, &str)]) -> Cursor<Vec<u8>> {
+        let items: String = items
+            .iter()
+            .map(|(guid, title)| format!("<item><guid>{}</guid><title>{}</title></item>", guid, title))
+            .collect();
+        Cursor::new(format!("<rss><channel>{}</channel></rss>", items).into_bytes())
+    }
+
+    async fn diff(items: &[(&str, &str)], previous: Snapshot) -> (Vec<ItemChange<String>>, Snapshot) {
+        let parser = RssParser::<RssItem, _>::new(feed(items)).await.unwrap();
+        let mut changes = parser.into_stream().diff(previous);
+        let mut out = Vec::new();
+        while let Some(change) = changes.next().await {
+            out.push(match change.unwrap() {
+                ItemChange::Added(item) => ItemChange::Added(item.title.unwrap()),
+                ItemChange::Updated(item) => ItemChange::Updated(item.title.unwrap()),
+            });
+        }
+        (out, changes.into_snapshot())
+    }
+
+    #[tokio::test]
+    async fn test_diff_between_fetches() {
+        let (first, snapshot) = diff(&[("1", "One"), ("2", "Two")], Snapshot::new()).await;
+        assert_eq!(first, vec![ItemChange::Added("One".to_string()), ItemChange::Added("Two".to_string())]);
+
+        let (second, snapshot) = diff(&[("3", "Three"), ("1", "One"), ("2", "Two, fixed")], snapshot).await;
+        assert_eq!(second, vec![ItemChange::Added("Three".to_string()), ItemChange::Updated("Two, fixed".to_string())]);
+        assert_eq!(snapshot.len(), 3);
+
+        // A snapshot survives a round trip through JSON.
+        let snapshot: Snapshot = serde_json::from_str(&serde_json::to_string(&snapshot).unwrap()).unwrap();
+        let (third, _) = diff(&[("3", "Three")], snapshot).await;
+        assert!(third.is_empty());
+
+        let (known, _) = diff(&[("1", "Changed"), ("4", "Four")], Snapshot::from_identities(["1".to_string()])).await;
+        assert_eq!(known, vec![ItemChange::Added("Four".to_string())]);
+    }
+}
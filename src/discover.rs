@@ -0,0 +1,192 @@
+#[cfg(feature = "http")]
+use crate::error::RssError;
+use crate::html::{Token, Tokens};
+use crate::url::resolve_url;
+
+const FEED_TYPES: [&str; 5] = [
+    "application/rss+xml",
+    "application/atom+xml",
+    "application/rdf+xml",
+    "application/feed+json",
+    "application/json",
+];
+
+/// Paths tried by `discover` when a page links no feed.
+#[cfg(feature = "http")]
+const FALLBACKS: [&str; 7] = ["/feed", "/rss.xml", "/feed.xml", "/atom.xml", "/rss", "/index.xml", "/feed.json"];
+
+#[cfg(feature = "http")]
+const MAX_PAGE_BYTES: u64 = 1024 * 1024;
+
+/// A feed found by `discover` or `discover_links`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredFeed {
+    pub url: String,
+    pub title: Option<String>,
+    /// The advertised `type`, e.g. `application/atom+xml`.
+    pub mime_type: Option<String>,
+}
+
+/// The feeds an HTML page advertises with `<link rel="alternate">`, in page
+/// order, with their URLs resolved against `page_url` (or the page's `<base>`).
+pub fn discover_links(html: &str, page_url: &str) -> Vec<DiscoveredFeed> {
+    let mut base = page_url.to_string();
+    let mut feeds = Vec::new();
+    for token in Tokens::new(html) {
+        let Token::Start { name, attributes, .. } = token else { continue };
+        let attr = |wanted: &str| attributes.iter().find(|(name, _)| name == wanted).map(|(_, value)| value.as_str());
+        match name.as_str() {
+            "base" => {
+                if let Some(href) = attr("href").and_then(|href| resolve_url(&base, href)) {
+                    base = href;
+                }
+            }
+            "link" => {
+                let rel = attr("rel").unwrap_or_default().to_ascii_lowercase();
+                let rels: Vec<&str> = rel.split_ascii_whitespace().collect();
+                let mime_type = attr("type").map(|mime_type| mime_type.trim().to_ascii_lowercase());
+                let is_feed_type = mime_type.as_deref().is_some_and(|mime_type| FEED_TYPES.contains(&mime_type));
+                if rels.contains(&"alternate")
+                    && !rels.contains(&"stylesheet")
+                    && is_feed_type
+                    && let Some(url) = attr("href").and_then(|href| resolve_url(&base, href))
+                    && !feeds.iter().any(|feed: &DiscoveredFeed| feed.url == url)
+                {
+                    feeds.push(DiscoveredFeed {
+                        url,
+                        title: attr("title").map(String::from),
+                        mime_type,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    feeds
+}
+
+/// Finds the feeds of a site: `url` itself if it is a feed, else the feeds
+/// its page links to, else the first of the usual locations (`/feed`,
+/// `/rss.xml`, `/atom.xml` and so on) that holds one.
+#[cfg(feature = "http")]
+pub async fn discover(url: &str) -> Result<Vec<DiscoveredFeed>, RssError> {
+    discover_with(url, &crate::http::FetchOptions::default()).await
+}
+
+/// Like `discover`, fetching with `options`, e.g. with a `TlsConnector` for
+/// `https://` sites.
+#[cfg(feature = "http")]
+pub async fn discover_with(url: &str, options: &crate::http::FetchOptions) -> Result<Vec<DiscoveredFeed>, RssError> {
+    use tokio::io::AsyncReadExt;
+
+    let (head, body) = crate::http::fetch(url, options).await?;
+    let mut page = Vec::new();
+    body.take(MAX_PAGE_BYTES).read_to_end(&mut page).await?;
+    let mime_type = content_type(&head);
+    if !mime_type.as_deref().is_some_and(|mime_type| mime_type.contains("html"))
+        && let Some(feed) = as_feed(url, &page, mime_type.clone()).await
+    {
+        return Ok(vec![feed]);
+    }
+
+    let feeds = discover_links(&String::from_utf8_lossy(&page), url);
+    if !feeds.is_empty() {
+        return Ok(feeds);
+    }
+    for path in FALLBACKS {
+        let Some(candidate) = resolve_url(url, path) else { continue };
+        let Ok((head, body)) = crate::http::fetch(&candidate, options).await else {
+            continue;
+        };
+        let mut contents = Vec::new();
+        if body.take(MAX_PAGE_BYTES).read_to_end(&mut contents).await.is_err() {
+            continue;
+        }
+        if let Some(feed) = as_feed(&candidate, &contents, content_type(&head)).await {
+            return Ok(vec![feed]);
+        }
+    }
+    Ok(Vec::new())
+}
+
+// The media type of a response, without parameters like `charset`.
+#[cfg(feature = "http")]
+fn content_type(head: &crate::http::ResponseHead) -> Option<String> {
+    let content_type = head.header("content-type")?;
+    Some(content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase())
+}
+
+// `contents` as a `DiscoveredFeed`, if it parses as one.
+#[cfg(feature = "http")]
+async fn as_feed(url: &str, contents: &[u8], mime_type: Option<String>) -> Option<DiscoveredFeed> {
+    use crate::channel::ChannelInfo;
+    use crate::feed::FeedParser;
+
+    let title = match FeedParser::detect(std::io::Cursor::new(contents)).await {
+        Ok(parser) => parser.parse_channel_only::<ChannelInfo>().await.ok()?.title,
+        Err(_) => {
+            let json: serde_json::Value = serde_json::from_slice(contents).ok()?;
+            if !json["version"].as_str()?.starts_with("https://jsonfeed.org/") {
+                return None;
+            }
+            json["title"].as_str().map(String::from)
+        }
+    };
+    Some(DiscoveredFeed {
+        url: url.to_string(),
+        title,
+        mime_type,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_discover_links() {
+        let html = r#"<!DOCTYPE html><html><head>
+<!-- <link rel="alternate" type="application/rss+xml" href="/commented"> -->
+<link rel=stylesheet href=/style.css>
+<LINK REL="alternate" TYPE="application/rss+xml" TITLE="Posts &amp; news" HREF="/feed.xml">
+<link rel='alternate' type='application/atom+xml' href='atom?lang=en&amp;full=1' />
+<link rel="alternate" hreflang="de" href="/de/">
+<base href="https://cdn.example.com/blog/">
+<link rel="alternate feed" type="application/feed+json" href="feed.json">
+<link rel="alternate" type="application/rss+xml" href="https://cdn.example.com/blog/feed.json">
+</head><body><a href="/feed.xml">RSS</a></body></html>"#;
+        let feeds = discover_links(html, "https://example.com/blog/post");
+        let urls: Vec<&str> = feeds.iter().map(|feed| feed.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            vec!["https://example.com/feed.xml", "https://example.com/blog/atom?lang=en&full=1", "https://cdn.example.com/blog/feed.json"]
+        );
+        assert_eq!(feeds[0].title.as_deref(), Some("Posts & news"));
+        assert_eq!(feeds[1].mime_type.as_deref(), Some("application/atom+xml"));
+    }
+
+    #[cfg(feature = "http")]
+    #[tokio::test]
+    async fn test_discover_falls_back_to_common_paths() {
+        use crate::http::tests::serve;
+
+        let page = "<html><head><title>No feed here</title></head></html>";
+        let feed = "<rss><channel><title>Found</title></channel></rss>";
+        let base = serve(vec![
+            format!("HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: {}\r\n\r\n{}", page.len(), page),
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n".to_string(),
+            format!("HTTP/1.1 200 OK\r\nContent-Type: application/rss+xml\r\nContent-Length: {}\r\n\r\n{}", feed.len(), feed),
+        ])
+        .await;
+
+        let feeds = discover(&format!("{}/blog", base)).await.unwrap();
+        assert_eq!(
+            feeds,
+            vec![DiscoveredFeed {
+                url: format!("{}/rss.xml", base),
+                title: Some("Found".to_string()),
+                mime_type: Some("application/rss+xml".to_string()),
+            }]
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde_json::{Map, Value};
+
+use crate::rss_parser::{GradualRssItem, XmlNode};
+
+pub(crate) const TEXT_KEY: &str = "$text";
+
+/// Every element's text keyed by its path (`title`, `author/name`), and every
+/// attribute as `path@name` (`enclosure@url`; the item's own as `@name`).
+/// Later elements with the same path replace earlier ones.
+impl GradualRssItem for HashMap<String, String> {
+    fn init() -> Self {
+        HashMap::new()
+    }
+
+    fn populate(&mut self, node: XmlNode) {
+        for (name, value) in &node.attributes {
+            self.insert(format!("{}@{}", node.path, name), value.clone());
+        }
+        if !node.path.is_empty() {
+            let path = node.path.clone();
+            self.insert(path, node.into_text().unwrap_or_default());
+        }
+    }
+}
+
+/// Like the `HashMap` implementation, but keeping every occurrence of a path
+/// in document order, e.g. all `category` values.
+impl GradualRssItem for BTreeMap<String, Vec<String>> {
+    fn init() -> Self {
+        BTreeMap::new()
+    }
+
+    fn populate(&mut self, node: XmlNode) {
+        for (name, value) in &node.attributes {
+            self.entry(format!("{}@{}", node.path, name)).or_default().push(value.clone());
+        }
+        if !node.path.is_empty() {
+            let path = node.path.clone();
+            self.entry(path).or_default().push(node.into_text().unwrap_or_default());
+        }
+    }
+}
+
+/// The item as a JSON object: child elements are keys named by their tag as
+/// the parser reports it (lowercased unless `lowercase_tags(false)`, prefix
+/// included, e.g. `dc:creator`), attributes are `@name` keys and an element
+/// with attributes or children keeps its text under `$text`. Elements with
+/// only text are strings, and repeated tags collect into an array.
+impl GradualRssItem for Value {
+    fn init() -> Self {
+        // Holds the elements still being built until `finish`.
+        Value::Object(Map::new())
+    }
+
+    fn populate(&mut self, node: XmlNode) {
+        if let Value::Object(pending) = self {
+            add_node(pending, node);
+        }
+    }
+
+    fn finish(mut self) -> Result<Self, String> {
+        Ok(match &mut self {
+            Value::Object(pending) => pending.remove("").unwrap_or_else(|| Value::Object(Map::new())),
+            _ => self,
+        })
+    }
+}
+
+// Adds an element to the tree in `pending`, which maps the path of every
+// element still open to the object of its finished children. Children close
+// before their parent, which then takes its object over; the item element
+// itself, closing last, leaves the whole item under "".
+pub(crate) fn add_node(pending: &mut Map<String, Value>, node: XmlNode) {
+    let mut object = match pending.remove(&node.path) {
+        Some(Value::Object(object)) => object,
+        _ => Map::new(),
+    };
+    for (name, value) in &node.attributes {
+        object.insert(format!("@{}", name), Value::String(value.clone()));
+    }
+    if node.path.is_empty() {
+        pending.insert(String::new(), Value::Object(object));
+        return;
+    }
+
+    let parent = node.path.rsplit_once('/').map_or("", |(parent, _)| parent).to_string();
+    let tag = node.tag.clone();
+    let value = match node.into_text() {
+        Some(text) if object.is_empty() => Value::String(text),
+        None if object.is_empty() => Value::String(String::new()),
+        text => {
+            if let Some(text) = text.filter(|text| !text.trim().is_empty()) {
+                object.insert(TEXT_KEY.to_string(), Value::String(text));
+            }
+            Value::Object(object)
+        }
+    };
+
+    let Value::Object(siblings) = pending.entry(parent).or_insert_with(|| Value::Object(Map::new())) else {
+        return;
+    };
+    match siblings.get_mut(&tag) {
+        Some(Value::Array(values)) => values.push(value),
+        Some(existing) => *existing = Value::Array(vec![existing.take(), value]),
+        None => {
+            siblings.insert(tag, value);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    use super::*;
+    use crate::RssParser;
+    use serde_json::json;
+    use std::io::Cursor;
+
+    const FEED: &str = r#"<rss><channel><item id="1">
+<title>One</title>
+<category>a</category><category>b</category>
+<enclosure url="https://example.com/1.mp3" length="5"/>
+<author><name>Jane</name></author>
+</item></channel></rss>"#;
+
+    #[tokio::test]
+    async fn test_maps() {
+        let mut parser = RssParser::<HashMap<String, String>, _>::new(Cursor::new(FEED.as_bytes())).await.unwrap();
+        let item = parser.next().await.unwrap().unwrap();
+        assert_eq!(item["title"], "One");
+        assert_eq!(item["category"], "b");
+        assert_eq!(item["enclosure@url"], "https://example.com/1.mp3");
+        assert_eq!(item["author/name"], "Jane");
+        assert_eq!(item["@id"], "1");
+
+        let mut parser = RssParser::<BTreeMap<String, Vec<String>>, _>::new(Cursor::new(FEED.as_bytes())).await.unwrap();
+        let item = parser.next().await.unwrap().unwrap();
+        assert_eq!(item["category"], vec!["a".to_string(), "b".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_json_value() {
+        let mut parser = RssParser::<Value, _>::new(Cursor::new(FEED.as_bytes())).await.unwrap();
+        assert_eq!(
+            parser.next().await.unwrap().unwrap(),
+            json!({
+                "@id": "1",
+                "title": "One",
+                "category": ["a", "b"],
+                "enclosure": {"@url": "https://example.com/1.mp3", "@length": "5"},
+                "author": {"name": "Jane"},
+            })
+        );
+        assert!(parser.next().await.unwrap().is_none());
+    }
+}
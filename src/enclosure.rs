@@ -0,0 +1,135 @@
+use std::fmt;
+
+use serde::{Serialize, Serializer};
+
+use crate::rss_parser::XmlNode;
+use crate::url::absolute;
+
+/// A parsed MIME type such as `audio/mpeg` or `text/html; charset=utf-8`.
+///
+/// Type, subtype and parameter names are lowercased.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MimeType {
+    pub kind: String,
+    pub subtype: String,
+    pub parameters: Vec<(String, String)>,
+}
+
+impl MimeType {
+    pub fn parse(input: &str) -> Option<Self> {
+        let mut parts = input.split(';');
+        let (kind, subtype) = parts.next()?.trim().split_once('/')?;
+        let (kind, subtype) = (kind.trim(), subtype.trim());
+        if kind.is_empty() || subtype.is_empty() || kind.contains(char::is_whitespace) || subtype.contains(char::is_whitespace) {
+            return None;
+        }
+
+        let parameters = parts
+            .filter_map(|parameter| {
+                let (name, value) = parameter.split_once('=')?;
+                Some((name.trim().to_ascii_lowercase(), value.trim().trim_matches('"').to_string()))
+            })
+            .collect();
+        Some(MimeType {
+            kind: kind.to_ascii_lowercase(),
+            subtype: subtype.to_ascii_lowercase(),
+            parameters,
+        })
+    }
+
+    /// The type without parameters, e.g. `audio/mpeg`.
+    pub fn essence(&self) -> String {
+        format!("{}/{}", self.kind, self.subtype)
+    }
+
+    pub fn parameter(&self, name: &str) -> Option<&str> {
+        self.parameters
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn is_audio(&self) -> bool {
+        self.kind == "audio"
+    }
+
+    pub fn is_video(&self) -> bool {
+        self.kind == "video"
+    }
+
+    pub fn is_image(&self) -> bool {
+        self.kind == "image"
+    }
+}
+
+/// Serialized as the string it displays as, e.g. `"audio/mpeg"`.
+impl Serialize for MimeType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl fmt::Display for MimeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.kind, self.subtype)?;
+        for (name, value) in &self.parameters {
+            write!(f, "; {}={}", name, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// A media file attached to an item with `<enclosure url=".." length=".." type=".."/>`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Enclosure {
+    pub url: String,
+    /// Size in bytes; missing when the attribute is absent or not a number.
+    pub length: Option<u64>,
+    pub mime_type: Option<MimeType>,
+}
+
+impl Enclosure {
+    /// Reads the enclosure attributes off a node, or `None` if it has no `url`.
+    pub fn from_node(node: &XmlNode) -> Option<Self> {
+        Some(Enclosure {
+            url: absolute(node.base.as_deref(), node.attr("url")?.trim().to_string()),
+            length: node.attr("length").and_then(|length| length.trim().parse().ok()),
+            mime_type: node.attr("type").and_then(MimeType::parse),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mime_type() {
+        let mime = MimeType::parse("Text/HTML; Charset=\"UTF-8\"").unwrap();
+        assert_eq!(mime.essence(), "text/html");
+        assert_eq!(mime.parameter("charset"), Some("UTF-8"));
+        assert_eq!(mime.to_string(), "text/html; charset=UTF-8");
+
+        assert!(MimeType::parse("audio/mpeg").unwrap().is_audio());
+        assert!(MimeType::parse("audio").is_none());
+        assert!(MimeType::parse("/mpeg").is_none());
+        assert!(MimeType::parse("audio mpeg/x").is_none());
+    }
+
+    #[test]
+    fn test_enclosure_from_node() {
+        let mut node = XmlNode::new("enclosure".to_string());
+        node.attributes = vec![
+            ("url".to_string(), "https://example.com/a.mp4".to_string()),
+            ("length".to_string(), "unknown".to_string()),
+            ("type".to_string(), "video/mp4".to_string()),
+        ];
+        let enclosure = Enclosure::from_node(&node).unwrap();
+        assert_eq!(enclosure.url, "https://example.com/a.mp4");
+        assert_eq!(enclosure.length, None);
+        assert!(enclosure.mime_type.unwrap().is_video());
+
+        node.attributes.remove(0);
+        assert!(Enclosure::from_node(&node).is_none());
+    }
+}
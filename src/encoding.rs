@@ -0,0 +1,475 @@
+use std::pin::Pin;
+use std::task::{Context, Poll, ready};
+
+use crate::async_read::{AsyncRead, ReadBuf};
+
+// Enough for any reasonable XML declaration.
+const SNIFF_LIMIT: usize = 1024;
+const READ_CHUNK: usize = 8 * 1024;
+
+/// A character encoding the parser can transcode to UTF-8.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    /// Also used for `ISO-8859-1` and `US-ASCII`, the way browsers do.
+    Windows1252,
+    Iso8859_15,
+    Windows1250,
+    Windows1251,
+    Iso8859_2,
+    Koi8R,
+}
+
+impl Encoding {
+    /// Looks up an encoding by the label used in `<?xml encoding="..."?>`.
+    pub fn for_label(label: &str) -> Option<Self> {
+        match label.trim().to_ascii_lowercase().as_str() {
+            "utf-8" | "utf8" | "unicode-1-1-utf-8" => Some(Encoding::Utf8),
+            "utf-16" | "utf-16le" => Some(Encoding::Utf16Le),
+            "utf-16be" => Some(Encoding::Utf16Be),
+            "windows-1252" | "cp1252" | "x-cp1252" | "iso-8859-1" | "iso8859-1" | "iso_8859-1" | "latin1" | "l1"
+            | "us-ascii" | "ascii" | "ansi_x3.4-1968" => Some(Encoding::Windows1252),
+            "iso-8859-15" | "iso8859-15" | "iso_8859-15" | "latin9" | "latin-9" | "l9" => Some(Encoding::Iso8859_15),
+            "windows-1250" | "cp1250" | "x-cp1250" => Some(Encoding::Windows1250),
+            "windows-1251" | "cp1251" | "x-cp1251" => Some(Encoding::Windows1251),
+            "iso-8859-2" | "iso8859-2" | "iso_8859-2" | "latin2" | "l2" => Some(Encoding::Iso8859_2),
+            "koi8-r" | "koi8r" | "koi8" | "cskoi8r" => Some(Encoding::Koi8R),
+            _ => None,
+        }
+    }
+}
+
+const WINDOWS_1252_HIGH: [char; 32] = [
+    '\u{20AC}', '\u{0081}', '\u{201A}', '\u{0192}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}', '\u{02C6}', '\u{2030}',
+    '\u{0160}', '\u{2039}', '\u{0152}', '\u{008D}', '\u{017D}', '\u{008F}', '\u{0090}', '\u{2018}', '\u{2019}', '\u{201C}',
+    '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}', '\u{02DC}', '\u{2122}', '\u{0161}', '\u{203A}', '\u{0153}', '\u{009D}',
+    '\u{017E}', '\u{0178}',
+];
+
+fn windows_1252(byte: u8) -> char {
+    match byte {
+        0x80..=0x9F => WINDOWS_1252_HIGH[(byte - 0x80) as usize],
+        _ => byte as char,
+    }
+}
+
+fn iso_8859_15(byte: u8) -> char {
+    match byte {
+        0xA4 => '\u{20AC}',
+        0xA6 => '\u{0160}',
+        0xA8 => '\u{0161}',
+        0xB4 => '\u{017D}',
+        0xB8 => '\u{017E}',
+        0xBC => '\u{0152}',
+        0xBD => '\u{0153}',
+        0xBE => '\u{0178}',
+        _ => byte as char,
+    }
+}
+
+// Bytes 0x80 to 0xFF of the other single-byte encodings; undefined ones stand
+// for the C1 control of the same number, as in windows-1252.
+const WINDOWS_1250_HIGH: [char; 128] = [
+    '\u{20AC}', '\u{0081}', '\u{201A}', '\u{0083}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}', '\u{0088}', '\u{2030}',
+    '\u{0160}', '\u{2039}', '\u{015A}', '\u{0164}', '\u{017D}', '\u{0179}', '\u{0090}', '\u{2018}', '\u{2019}', '\u{201C}',
+    '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}', '\u{0098}', '\u{2122}', '\u{0161}', '\u{203A}', '\u{015B}', '\u{0165}',
+    '\u{017E}', '\u{017A}', '\u{00A0}', '\u{02C7}', '\u{02D8}', '\u{0141}', '\u{00A4}', '\u{0104}', '\u{00A6}', '\u{00A7}',
+    '\u{00A8}', '\u{00A9}', '\u{015E}', '\u{00AB}', '\u{00AC}', '\u{00AD}', '\u{00AE}', '\u{017B}', '\u{00B0}', '\u{00B1}',
+    '\u{02DB}', '\u{0142}', '\u{00B4}', '\u{00B5}', '\u{00B6}', '\u{00B7}', '\u{00B8}', '\u{0105}', '\u{015F}', '\u{00BB}',
+    '\u{013D}', '\u{02DD}', '\u{013E}', '\u{017C}', '\u{0154}', '\u{00C1}', '\u{00C2}', '\u{0102}', '\u{00C4}', '\u{0139}',
+    '\u{0106}', '\u{00C7}', '\u{010C}', '\u{00C9}', '\u{0118}', '\u{00CB}', '\u{011A}', '\u{00CD}', '\u{00CE}', '\u{010E}',
+    '\u{0110}', '\u{0143}', '\u{0147}', '\u{00D3}', '\u{00D4}', '\u{0150}', '\u{00D6}', '\u{00D7}', '\u{0158}', '\u{016E}',
+    '\u{00DA}', '\u{0170}', '\u{00DC}', '\u{00DD}', '\u{0162}', '\u{00DF}', '\u{0155}', '\u{00E1}', '\u{00E2}', '\u{0103}',
+    '\u{00E4}', '\u{013A}', '\u{0107}', '\u{00E7}', '\u{010D}', '\u{00E9}', '\u{0119}', '\u{00EB}', '\u{011B}', '\u{00ED}',
+    '\u{00EE}', '\u{010F}', '\u{0111}', '\u{0144}', '\u{0148}', '\u{00F3}', '\u{00F4}', '\u{0151}', '\u{00F6}', '\u{00F7}',
+    '\u{0159}', '\u{016F}', '\u{00FA}', '\u{0171}', '\u{00FC}', '\u{00FD}', '\u{0163}', '\u{02D9}',
+];
+const WINDOWS_1251_HIGH: [char; 128] = [
+    '\u{0402}', '\u{0403}', '\u{201A}', '\u{0453}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}', '\u{20AC}', '\u{2030}',
+    '\u{0409}', '\u{2039}', '\u{040A}', '\u{040C}', '\u{040B}', '\u{040F}', '\u{0452}', '\u{2018}', '\u{2019}', '\u{201C}',
+    '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}', '\u{0098}', '\u{2122}', '\u{0459}', '\u{203A}', '\u{045A}', '\u{045C}',
+    '\u{045B}', '\u{045F}', '\u{00A0}', '\u{040E}', '\u{045E}', '\u{0408}', '\u{00A4}', '\u{0490}', '\u{00A6}', '\u{00A7}',
+    '\u{0401}', '\u{00A9}', '\u{0404}', '\u{00AB}', '\u{00AC}', '\u{00AD}', '\u{00AE}', '\u{0407}', '\u{00B0}', '\u{00B1}',
+    '\u{0406}', '\u{0456}', '\u{0491}', '\u{00B5}', '\u{00B6}', '\u{00B7}', '\u{0451}', '\u{2116}', '\u{0454}', '\u{00BB}',
+    '\u{0458}', '\u{0405}', '\u{0455}', '\u{0457}', '\u{0410}', '\u{0411}', '\u{0412}', '\u{0413}', '\u{0414}', '\u{0415}',
+    '\u{0416}', '\u{0417}', '\u{0418}', '\u{0419}', '\u{041A}', '\u{041B}', '\u{041C}', '\u{041D}', '\u{041E}', '\u{041F}',
+    '\u{0420}', '\u{0421}', '\u{0422}', '\u{0423}', '\u{0424}', '\u{0425}', '\u{0426}', '\u{0427}', '\u{0428}', '\u{0429}',
+    '\u{042A}', '\u{042B}', '\u{042C}', '\u{042D}', '\u{042E}', '\u{042F}', '\u{0430}', '\u{0431}', '\u{0432}', '\u{0433}',
+    '\u{0434}', '\u{0435}', '\u{0436}', '\u{0437}', '\u{0438}', '\u{0439}', '\u{043A}', '\u{043B}', '\u{043C}', '\u{043D}',
+    '\u{043E}', '\u{043F}', '\u{0440}', '\u{0441}', '\u{0442}', '\u{0443}', '\u{0444}', '\u{0445}', '\u{0446}', '\u{0447}',
+    '\u{0448}', '\u{0449}', '\u{044A}', '\u{044B}', '\u{044C}', '\u{044D}', '\u{044E}', '\u{044F}',
+];
+const ISO_8859_2_HIGH: [char; 128] = [
+    '\u{0080}', '\u{0081}', '\u{0082}', '\u{0083}', '\u{0084}', '\u{0085}', '\u{0086}', '\u{0087}', '\u{0088}', '\u{0089}',
+    '\u{008A}', '\u{008B}', '\u{008C}', '\u{008D}', '\u{008E}', '\u{008F}', '\u{0090}', '\u{0091}', '\u{0092}', '\u{0093}',
+    '\u{0094}', '\u{0095}', '\u{0096}', '\u{0097}', '\u{0098}', '\u{0099}', '\u{009A}', '\u{009B}', '\u{009C}', '\u{009D}',
+    '\u{009E}', '\u{009F}', '\u{00A0}', '\u{0104}', '\u{02D8}', '\u{0141}', '\u{00A4}', '\u{013D}', '\u{015A}', '\u{00A7}',
+    '\u{00A8}', '\u{0160}', '\u{015E}', '\u{0164}', '\u{0179}', '\u{00AD}', '\u{017D}', '\u{017B}', '\u{00B0}', '\u{0105}',
+    '\u{02DB}', '\u{0142}', '\u{00B4}', '\u{013E}', '\u{015B}', '\u{02C7}', '\u{00B8}', '\u{0161}', '\u{015F}', '\u{0165}',
+    '\u{017A}', '\u{02DD}', '\u{017E}', '\u{017C}', '\u{0154}', '\u{00C1}', '\u{00C2}', '\u{0102}', '\u{00C4}', '\u{0139}',
+    '\u{0106}', '\u{00C7}', '\u{010C}', '\u{00C9}', '\u{0118}', '\u{00CB}', '\u{011A}', '\u{00CD}', '\u{00CE}', '\u{010E}',
+    '\u{0110}', '\u{0143}', '\u{0147}', '\u{00D3}', '\u{00D4}', '\u{0150}', '\u{00D6}', '\u{00D7}', '\u{0158}', '\u{016E}',
+    '\u{00DA}', '\u{0170}', '\u{00DC}', '\u{00DD}', '\u{0162}', '\u{00DF}', '\u{0155}', '\u{00E1}', '\u{00E2}', '\u{0103}',
+    '\u{00E4}', '\u{013A}', '\u{0107}', '\u{00E7}', '\u{010D}', '\u{00E9}', '\u{0119}', '\u{00EB}', '\u{011B}', '\u{00ED}',
+    '\u{00EE}', '\u{010F}', '\u{0111}', '\u{0144}', '\u{0148}', '\u{00F3}', '\u{00F4}', '\u{0151}', '\u{00F6}', '\u{00F7}',
+    '\u{0159}', '\u{016F}', '\u{00FA}', '\u{0171}', '\u{00FC}', '\u{00FD}', '\u{0163}', '\u{02D9}',
+];
+const KOI8_R_HIGH: [char; 128] = [
+    '\u{2500}', '\u{2502}', '\u{250C}', '\u{2510}', '\u{2514}', '\u{2518}', '\u{251C}', '\u{2524}', '\u{252C}', '\u{2534}',
+    '\u{253C}', '\u{2580}', '\u{2584}', '\u{2588}', '\u{258C}', '\u{2590}', '\u{2591}', '\u{2592}', '\u{2593}', '\u{2320}',
+    '\u{25A0}', '\u{2219}', '\u{221A}', '\u{2248}', '\u{2264}', '\u{2265}', '\u{00A0}', '\u{2321}', '\u{00B0}', '\u{00B2}',
+    '\u{00B7}', '\u{00F7}', '\u{2550}', '\u{2551}', '\u{2552}', '\u{0451}', '\u{2553}', '\u{2554}', '\u{2555}', '\u{2556}',
+    '\u{2557}', '\u{2558}', '\u{2559}', '\u{255A}', '\u{255B}', '\u{255C}', '\u{255D}', '\u{255E}', '\u{255F}', '\u{2560}',
+    '\u{2561}', '\u{0401}', '\u{2562}', '\u{2563}', '\u{2564}', '\u{2565}', '\u{2566}', '\u{2567}', '\u{2568}', '\u{2569}',
+    '\u{256A}', '\u{256B}', '\u{256C}', '\u{00A9}', '\u{044E}', '\u{0430}', '\u{0431}', '\u{0446}', '\u{0434}', '\u{0435}',
+    '\u{0444}', '\u{0433}', '\u{0445}', '\u{0438}', '\u{0439}', '\u{043A}', '\u{043B}', '\u{043C}', '\u{043D}', '\u{043E}',
+    '\u{043F}', '\u{044F}', '\u{0440}', '\u{0441}', '\u{0442}', '\u{0443}', '\u{0436}', '\u{0432}', '\u{044C}', '\u{044B}',
+    '\u{0437}', '\u{0448}', '\u{044D}', '\u{0449}', '\u{0447}', '\u{044A}', '\u{042E}', '\u{0410}', '\u{0411}', '\u{0426}',
+    '\u{0414}', '\u{0415}', '\u{0424}', '\u{0413}', '\u{0425}', '\u{0418}', '\u{0419}', '\u{041A}', '\u{041B}', '\u{041C}',
+    '\u{041D}', '\u{041E}', '\u{041F}', '\u{042F}', '\u{0420}', '\u{0421}', '\u{0422}', '\u{0423}', '\u{0416}', '\u{0412}',
+    '\u{042C}', '\u{042B}', '\u{0417}', '\u{0428}', '\u{042D}', '\u{0429}', '\u{0427}', '\u{042A}',
+];
+
+fn single_byte(table: &[char; 128], byte: u8) -> char {
+    match byte {
+        0x80.. => table[(byte - 0x80) as usize],
+        _ => byte as char,
+    }
+}
+
+// Works out the encoding from a byte order mark or the XML declaration and
+// returns it with the number of BOM bytes to skip.
+fn sniff(prefix: &[u8]) -> (Option<Encoding>, usize) {
+    match prefix {
+        [0xEF, 0xBB, 0xBF, ..] => return (Some(Encoding::Utf8), 3),
+        [0xFF, 0xFE, ..] => return (Some(Encoding::Utf16Le), 2),
+        [0xFE, 0xFF, ..] => return (Some(Encoding::Utf16Be), 2),
+        [b'<', 0, b'?', 0, ..] => return (Some(Encoding::Utf16Le), 0),
+        [0, b'<', 0, b'?', ..] => return (Some(Encoding::Utf16Be), 0),
+        _ => {}
+    }
+
+    match declared_label(prefix) {
+        Some(label) => (Encoding::for_label(&label), 0),
+        None => (Some(Encoding::Utf8), 0),
+    }
+}
+
+// The `encoding` of the XML declaration at the start of `prefix`.
+fn declared_label(prefix: &[u8]) -> Option<String> {
+    let text = String::from_utf8_lossy(prefix);
+    text.strip_prefix("<?xml")
+        .and_then(|decl| decl.split("?>").next())
+        .and_then(|decl| decl.split_once("encoding"))
+        .and_then(|(_, rest)| {
+            let rest = rest.trim_start().strip_prefix('=')?.trim_start();
+            let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+            rest[1..].split(quote).next().map(String::from)
+        })
+}
+
+/// Transcodes a feed to UTF-8 on the fly, so quick-xml only ever sees UTF-8.
+pub(crate) struct DecodingReader<R> {
+    inner: R,
+    encoding: Option<Encoding>,
+    sniffed: bool,
+    unknown_label: Option<String>,
+    // A byte outside ASCII was replaced, in a feed of unknown encoding.
+    replaced: bool,
+    raw: Vec<u8>,
+    decoded: Vec<u8>,
+    decoded_pos: usize,
+    eof: bool,
+    // Length of the byte order mark skipped at the start.
+    bom: usize,
+}
+
+impl<R> DecodingReader<R> {
+    pub(crate) fn new(inner: R) -> Self {
+        DecodingReader {
+            inner,
+            encoding: None,
+            sniffed: false,
+            unknown_label: None,
+            replaced: false,
+            raw: Vec::new(),
+            decoded: Vec::new(),
+            decoded_pos: 0,
+            eof: false,
+            bom: 0,
+        }
+    }
+
+    /// Reads `inner` as UTF-8 from the first byte, for input that starts in
+    /// the middle of a document.
+    pub(crate) fn resumed(inner: R) -> Self {
+        DecodingReader {
+            encoding: Some(Encoding::Utf8),
+            sniffed: true,
+            ..DecodingReader::new(inner)
+        }
+    }
+
+    pub(crate) fn bom_len(&self) -> usize {
+        self.bom
+    }
+
+    /// The detected encoding, once the start of the document has been read.
+    pub(crate) fn encoding(&self) -> Option<Encoding> {
+        self.encoding
+    }
+
+    /// The declared encoding label when it wasn't recognised.
+    pub(crate) fn unknown_label(&self) -> Option<&str> {
+        self.unknown_label.as_deref()
+    }
+
+    /// Whether a byte of a feed in an unrecognised encoding couldn't be read
+    /// as ASCII and was replaced with U+FFFD.
+    pub(crate) fn replaced(&self) -> bool {
+        self.replaced
+    }
+
+    /// The inner reader and the bytes read from it but not yet handed on;
+    /// those already transcoded come back as UTF-8.
+    pub(crate) fn into_inner(self) -> (R, Vec<u8>) {
+        let mut rest = self.decoded;
+        rest.drain(..self.decoded_pos);
+        rest.extend_from_slice(&self.raw);
+        (self.inner, rest)
+    }
+
+    fn sniff_ready(&self) -> bool {
+        self.eof
+            || self.raw.len() >= SNIFF_LIMIT
+            || (self.raw.len() >= 4 && !self.raw.starts_with(b"<?xml"))
+            || self.raw.windows(2).any(|window| window == b"?>")
+    }
+
+    // Converts as much of `raw` as possible, keeping incomplete UTF-16 code
+    // units around until the next read.
+    fn transcode(&mut self) {
+        let encoding = self.encoding.unwrap_or(Encoding::Utf8);
+        self.decoded.drain(..self.decoded_pos);
+        self.decoded_pos = 0;
+
+        let mut out = String::new();
+        // Whatever the unknown encoding is, ASCII most likely reads the same in it.
+        if self.unknown_label.is_some() {
+            let replaced = &mut self.replaced;
+            out.extend(self.raw.drain(..).map(|byte| {
+                *replaced |= !byte.is_ascii();
+                if byte.is_ascii() { byte as char } else { '\u{FFFD}' }
+            }));
+            self.decoded.extend_from_slice(out.as_bytes());
+            return;
+        }
+        match encoding {
+            Encoding::Utf8 => {
+                self.decoded.append(&mut self.raw);
+                return;
+            }
+            Encoding::Windows1252 => out.extend(self.raw.drain(..).map(windows_1252)),
+            Encoding::Iso8859_15 => out.extend(self.raw.drain(..).map(iso_8859_15)),
+            Encoding::Windows1250 => out.extend(self.raw.drain(..).map(|byte| single_byte(&WINDOWS_1250_HIGH, byte))),
+            Encoding::Windows1251 => out.extend(self.raw.drain(..).map(|byte| single_byte(&WINDOWS_1251_HIGH, byte))),
+            Encoding::Iso8859_2 => out.extend(self.raw.drain(..).map(|byte| single_byte(&ISO_8859_2_HIGH, byte))),
+            Encoding::Koi8R => out.extend(self.raw.drain(..).map(|byte| single_byte(&KOI8_R_HIGH, byte))),
+            Encoding::Utf16Le | Encoding::Utf16Be => {
+                let units: Vec<u16> = self
+                    .raw
+                    .chunks_exact(2)
+                    .map(|pair| match encoding {
+                        Encoding::Utf16Le => u16::from_le_bytes([pair[0], pair[1]]),
+                        _ => u16::from_be_bytes([pair[0], pair[1]]),
+                    })
+                    .collect();
+                // A trailing high surrogate needs its partner from the next read.
+                let mut usable = units.len();
+                if !self.eof && units.last().is_some_and(|unit| (0xD800..0xDC00).contains(unit)) {
+                    usable -= 1;
+                }
+                out.extend(char::decode_utf16(units[..usable].iter().copied()).map(|c| c.unwrap_or('\u{FFFD}')));
+                self.raw.drain(..usable * 2);
+                if self.eof && !self.raw.is_empty() {
+                    self.raw.clear();
+                    out.push('\u{FFFD}');
+                }
+            }
+        }
+        self.decoded.extend_from_slice(out.as_bytes());
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for DecodingReader<R> {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            if this.decoded_pos < this.decoded.len() {
+                let available = &this.decoded[this.decoded_pos..];
+                let take = available.len().min(buf.remaining());
+                buf.put_slice(&available[..take]);
+                this.decoded_pos += take;
+                return Poll::Ready(Ok(()));
+            }
+
+            // UTF-8 needs no conversion once the prefix has been dealt with.
+            if this.sniffed && this.encoding == Some(Encoding::Utf8) && this.unknown_label.is_none() && this.raw.is_empty() {
+                return Pin::new(&mut this.inner).poll_read(cx, buf);
+            }
+            if this.eof && this.raw.is_empty() {
+                return Poll::Ready(Ok(()));
+            }
+
+            let mut chunk = [0u8; READ_CHUNK];
+            let mut chunk_buf = ReadBuf::new(&mut chunk);
+            if !this.eof {
+                ready!(Pin::new(&mut this.inner).poll_read(cx, &mut chunk_buf))?;
+                if chunk_buf.filled().is_empty() {
+                    this.eof = true;
+                }
+                this.raw.extend_from_slice(chunk_buf.filled());
+            }
+
+            if !this.sniffed {
+                if !this.sniff_ready() {
+                    continue;
+                }
+                // Unknown labels are read as ASCII, for the parser to report.
+                let (encoding, bom) = sniff(&this.raw);
+                if encoding.is_none() {
+                    this.unknown_label = declared_label(&this.raw);
+                }
+                this.encoding = Some(encoding.unwrap_or(Encoding::Utf8));
+                this.raw.drain(..bom);
+                this.bom = bom;
+                this.sniffed = true;
+            }
+            this.transcode();
+        }
+    }
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    use super::*;
+    use crate::{RssItem, RssParser};
+    use std::io::Cursor;
+    use tokio::io::AsyncReadExt;
+
+    async fn decode(bytes: Vec<u8>) -> (String, Option<Encoding>) {
+        let mut reader = DecodingReader::new(Cursor::new(bytes));
+        let mut out = String::new();
+        reader.read_to_string(&mut out).await.unwrap();
+        (out, reader.encoding())
+    }
+
+    #[tokio::test]
+    async fn test_windows_1252_declaration() {
+        let mut feed = b"<?xml version='1.0' encoding='windows-1252'?><rss><channel><item><title>".to_vec();
+        feed.extend_from_slice(&[0x93, b'Q', 0x94, b' ', 0x80, b'5', b' ', 0xE9]);
+        feed.extend_from_slice(b"</title></item></channel></rss>");
+
+        let mut parser = RssParser::<RssItem, _>::new(Cursor::new(feed)).await.unwrap();
+        let item = parser.next().await.unwrap().unwrap();
+        assert_eq!(item.title.as_deref(), Some("\u{201C}Q\u{201D} \u{20AC}5 \u{E9}"));
+    }
+
+    #[tokio::test]
+    async fn test_latin1_labels() {
+        let mut feed = b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?><a>".to_vec();
+        feed.extend_from_slice(&[0xFC, 0xDF]);
+        feed.extend_from_slice(b"</a>");
+        let (text, encoding) = decode(feed).await;
+        assert!(text.ends_with("<a>\u{FC}\u{DF}</a>"));
+        assert_eq!(encoding, Some(Encoding::Windows1252));
+
+        let mut feed = b"<?xml version=\"1.0\" encoding=\"iso-8859-15\"?><a>".to_vec();
+        feed.push(0xA4);
+        feed.extend_from_slice(b"</a>");
+        assert!(decode(feed).await.0.ends_with("<a>\u{20AC}</a>"));
+    }
+
+    #[tokio::test]
+    async fn test_utf16_with_bom() {
+        let xml = "<?xml version=\"1.0\" encoding=\"UTF-16\"?><rss><channel><item><title>Caf\u{E9} \u{1F600}</title></item></channel></rss>";
+        let mut le = vec![0xFF, 0xFE];
+        le.extend(xml.encode_utf16().flat_map(|unit| unit.to_le_bytes()));
+        let mut parser = RssParser::<RssItem, _>::new(Cursor::new(le)).await.unwrap();
+        assert_eq!(parser.next().await.unwrap().unwrap().title.as_deref(), Some("Caf\u{E9} \u{1F600}"));
+
+        let be: Vec<u8> = xml.encode_utf16().flat_map(|unit| unit.to_be_bytes()).collect();
+        let (text, encoding) = decode(be).await;
+        assert_eq!(text, xml);
+        assert_eq!(encoding, Some(Encoding::Utf16Be));
+    }
+
+    #[tokio::test]
+    async fn test_utf16_split_across_reads() {
+        let xml = "<a>\u{1F600}\u{1F600}</a>";
+        let bytes: Vec<u8> = [0xFF, 0xFE]
+            .into_iter()
+            .chain(xml.encode_utf16().flat_map(|unit| unit.to_le_bytes()))
+            .collect();
+
+        let (client, mut server) = tokio::io::duplex(3);
+        tokio::spawn(async move {
+            use tokio::io::AsyncWriteExt;
+            server.write_all(&bytes).await.unwrap();
+        });
+        let mut reader = DecodingReader::new(client);
+        let mut out = String::new();
+        reader.read_to_string(&mut out).await.unwrap();
+        assert_eq!(out, xml);
+    }
+
+    #[tokio::test]
+    async fn test_utf8_bom_and_passthrough() {
+        let mut feed = vec![0xEF, 0xBB, 0xBF];
+        feed.extend_from_slice("<rss>\u{2603}</rss>".as_bytes());
+        let (text, encoding) = decode(feed).await;
+        assert_eq!(text, "<rss>\u{2603}</rss>");
+        assert_eq!(encoding, Some(Encoding::Utf8));
+
+        let (text, _) = decode(b"<rss/>".to_vec()).await;
+        assert_eq!(text, "<rss/>");
+    }
+
+    #[tokio::test]
+    async fn test_cyrillic_and_central_european() {
+        let feed = |label: &str, text: &[u8]| {
+            let mut feed = format!("<?xml version=\"1.0\" encoding=\"{}\"?><a>", label).into_bytes();
+            feed.extend_from_slice(text);
+            feed.extend_from_slice(b"</a>");
+            feed
+        };
+        let (text, encoding) = decode(feed("windows-1251", &[0xCF, 0xF0, 0xE8, 0xE2, 0xE5, 0xF2, 0x20, 0xB8])).await;
+        assert!(text.ends_with("<a>\u{41F}\u{440}\u{438}\u{432}\u{435}\u{442} \u{451}</a>"), "{text}");
+        assert_eq!(encoding, Some(Encoding::Windows1251));
+        let (text, _) = decode(feed("KOI8-R", &[0xF0, 0xD2, 0xC9, 0xD7, 0xC5, 0xD4])).await;
+        assert!(text.ends_with("<a>\u{41F}\u{440}\u{438}\u{432}\u{435}\u{442}</a>"), "{text}");
+        let (text, _) = decode(feed("iso-8859-2", &[0xB1, 0xEA, 0xA3])).await;
+        assert!(text.ends_with("<a>\u{105}\u{119}\u{141}</a>"), "{text}");
+        let (text, _) = decode(feed("windows-1250", &[0x8A, 0xB9, 0x9F])).await;
+        assert!(text.ends_with("<a>\u{160}\u{105}\u{17A}</a>"), "{text}");
+    }
+
+    #[tokio::test]
+    async fn test_unknown_label_reads_ascii() {
+        let mut reader = DecodingReader::new(Cursor::new(b"<?xml encoding=\"x-made-up\"?><a/>".to_vec()));
+        let mut out = String::new();
+        reader.read_to_string(&mut out).await.unwrap();
+        assert_eq!(out, "<?xml encoding=\"x-made-up\"?><a/>");
+        assert_eq!(reader.encoding(), Some(Encoding::Utf8));
+        assert_eq!(reader.unknown_label(), Some("x-made-up"));
+        assert!(!reader.replaced());
+
+        let mut reader = DecodingReader::new(Cursor::new(b"<?xml encoding=\"Shift_JIS\"?><a>\x93\xfa</a>".to_vec()));
+        let mut out = String::new();
+        reader.read_to_string(&mut out).await.unwrap();
+        assert!(out.ends_with("<a>\u{FFFD}\u{FFFD}</a>"), "{out}");
+        assert!(reader.replaced());
+    }
+}
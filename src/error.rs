@@ -0,0 +1,157 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use quick_xml::encoding::EncodingError;
+
+#[cfg(feature = "http")]
+use crate::http::HttpError;
+
+/// A place in the feed. `offset` counts bytes of the UTF-8 text the parser
+/// read; the 1-based line and byte column are only known with
+/// `RssParserBuilder::track_lines`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location {
+    pub offset: u64,
+    pub line: Option<u64>,
+    pub column: Option<u64>,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => write!(f, "line {}, column {}", line, column),
+            _ => write!(f, "byte {}", self.offset),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum RssError {
+    Io(std::io::Error),
+    Xml { error: quick_xml::Error, position: u64 },
+    Encoding(EncodingError),
+    Json(serde_json::Error),
+    #[cfg(feature = "http")]
+    Http(HttpError),
+    UnexpectedEof,
+    UnsupportedFormat(String),
+    /// The feed declared an encoding that isn't supported, such as Shift_JIS,
+    /// and was read as ASCII. A warning, and in strict mode an error once a
+    /// byte that isn't ASCII turns up.
+    UnknownEncoding(String),
+    /// `GradualRssItem::finish` rejected an item.
+    InvalidItem(String),
+    /// The feed has a `<!DOCTYPE>`, see `RssParserBuilder::allow_doctype`.
+    DoctypeNotAllowed { position: u64 },
+    /// A configured limit was hit; `max` is in bytes, or levels for `depth`.
+    LimitExceeded { limit: &'static str, max: u64, position: u64 },
+    /// The input went past `RssParserBuilder::max_feed_size`.
+    FeedTooLarge { max: u64 },
+    /// A read waited longer than `RssParserBuilder::stall_timeout` for data.
+    Stalled { timeout: Duration, position: u64 },
+}
+
+impl RssError {
+    /// The byte offset the error refers to, when it points at a place in the
+    /// feed. `RssParser::locate` turns it into a line and column.
+    pub fn position(&self) -> Option<u64> {
+        match self {
+            RssError::Xml { position, .. }
+            | RssError::DoctypeNotAllowed { position }
+            | RssError::LimitExceeded { position, .. }
+            | RssError::Stalled { position, .. } => Some(*position),
+            _ => None,
+        }
+    }
+
+    /// Whether the error ends the feed. After a malformed item (`Xml`), a
+    /// rejected one (`InvalidItem`) or a per-item `LimitExceeded`, `next`
+    /// goes on with the rest of the feed; a limit that stopped the read
+    /// itself makes the next call return `Ok(None)`.
+    pub fn is_fatal(&self) -> bool {
+        !matches!(self, RssError::Xml { .. } | RssError::InvalidItem(_) | RssError::LimitExceeded { .. })
+    }
+
+    pub(crate) fn from_xml(error: quick_xml::Error, position: u64) -> Self {
+        match error {
+            quick_xml::Error::Io(err) => RssError::Io(
+                Arc::try_unwrap(err).unwrap_or_else(|err| std::io::Error::new(err.kind(), err.to_string())),
+            ),
+            quick_xml::Error::Encoding(err) => RssError::Encoding(err),
+            error => RssError::Xml { error, position },
+        }
+    }
+}
+
+impl fmt::Display for RssError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RssError::Io(err) => write!(f, "I/O error: {}", err),
+            RssError::Xml { error, position } => write!(f, "XML error at byte {}: {}", position, error),
+            RssError::Encoding(err) => write!(f, "encoding error: {}", err),
+            RssError::Json(err) => write!(f, "JSON error: {}", err),
+            #[cfg(feature = "http")]
+            RssError::Http(err) => write!(f, "HTTP error: {}", err),
+            RssError::UnexpectedEof => write!(f, "unexpected end of feed"),
+            RssError::UnsupportedFormat(root) => write!(f, "unsupported feed format with root element <{}>", root),
+            RssError::UnknownEncoding(label) => write!(f, "unsupported encoding {:?}", label),
+            RssError::InvalidItem(reason) => write!(f, "invalid item: {}", reason),
+            RssError::DoctypeNotAllowed { position } => write!(f, "DOCTYPE declaration at byte {} is not allowed", position),
+            RssError::LimitExceeded { limit, max, position } => {
+                let unit = if *limit == "depth" { "levels" } else { "bytes" };
+                write!(f, "{} limit of {} {} exceeded at byte {}", limit, max, unit, position)
+            }
+            RssError::FeedTooLarge { max } => write!(f, "feed is larger than {} bytes", max),
+            RssError::Stalled { timeout, position } => {
+                write!(f, "no data for {:?} while reading at byte {}", timeout, position)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RssError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RssError::Io(err) => Some(err),
+            RssError::Xml { error, .. } => Some(error),
+            RssError::Encoding(err) => Some(err),
+            RssError::Json(err) => Some(err),
+            #[cfg(feature = "http")]
+            RssError::Http(err) => Some(err),
+            RssError::UnexpectedEof
+            | RssError::UnsupportedFormat(_)
+            | RssError::UnknownEncoding(_)
+            | RssError::InvalidItem(_)
+            | RssError::DoctypeNotAllowed { .. }
+            | RssError::LimitExceeded { .. }
+            | RssError::FeedTooLarge { .. }
+            | RssError::Stalled { .. } => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for RssError {
+    fn from(err: std::io::Error) -> Self {
+        RssError::Io(err)
+    }
+}
+
+impl From<EncodingError> for RssError {
+    fn from(err: EncodingError) -> Self {
+        RssError::Encoding(err)
+    }
+}
+
+impl From<serde_json::Error> for RssError {
+    fn from(err: serde_json::Error) -> Self {
+        RssError::Json(err)
+    }
+}
+
+#[cfg(feature = "http")]
+impl From<HttpError> for RssError {
+    fn from(err: HttpError) -> Self {
+        RssError::Http(err)
+    }
+}
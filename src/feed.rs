@@ -0,0 +1,182 @@
+use tokio::io::AsyncRead;
+
+use crate::atom::AtomEntry;
+use crate::error::RssError;
+use crate::feed_item::FeedItem;
+use crate::item::RssItem;
+use crate::rss_parser::{GradualRssChannel, RssParser, XML_KEY_ENTRY, XML_KEY_ITEM};
+use crate::stats::ParserStats;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedFormat {
+    Rss,
+    Atom,
+    Rdf,
+}
+
+impl FeedFormat {
+    fn from_root(tag: &str) -> Option<Self> {
+        let local = tag.rsplit(':').next().unwrap_or(tag);
+        match local {
+            "rss" => Some(FeedFormat::Rss),
+            "feed" => Some(FeedFormat::Atom),
+            "rdf" => Some(FeedFormat::Rdf),
+            _ => None,
+        }
+    }
+}
+
+/// A parser for a feed whose flavor was sniffed from its root element.
+pub enum FeedParser<R> {
+    Rss(RssParser<RssItem, R>),
+    Atom(RssParser<AtomEntry, R>),
+    Rdf(RssParser<RssItem, R>),
+}
+
+impl<R: AsyncRead + Unpin> FeedParser<R> {
+    pub async fn detect(input: R) -> Result<Self, RssError> {
+        Self::from_parser(RssParser::new(input).await?).await
+    }
+
+    // Sniffs the format with a parser that hasn't read anything yet.
+    pub(crate) async fn from_parser(mut parser: RssParser<RssItem, R>) -> Result<Self, RssError> {
+        let root = parser.read_root().await?.ok_or(RssError::UnexpectedEof)?;
+
+        match FeedFormat::from_root(&root) {
+            Some(FeedFormat::Rss) => Ok(FeedParser::Rss(parser)),
+            Some(FeedFormat::Atom) => Ok(FeedParser::Atom(parser.retarget(XML_KEY_ENTRY))),
+            Some(FeedFormat::Rdf) => Ok(FeedParser::Rdf(parser.retarget(XML_KEY_ITEM))),
+            None => Err(RssError::UnsupportedFormat(root)),
+        }
+    }
+
+    /// Parses the next item of whatever flavor and converts it to a `FeedItem`.
+    pub async fn next(&mut self) -> Result<Option<FeedItem>, RssError> {
+        match self {
+            FeedParser::Rss(parser) | FeedParser::Rdf(parser) => Ok(parser.next().await?.map(FeedItem::from)),
+            FeedParser::Atom(parser) => Ok(parser.next().await?.map(FeedItem::from)),
+        }
+    }
+
+    /// See `RssParser::channel`.
+    pub async fn channel<C: GradualRssChannel>(&mut self) -> Result<C, RssError> {
+        match self {
+            FeedParser::Rss(parser) | FeedParser::Rdf(parser) => parser.channel().await,
+            FeedParser::Atom(parser) => parser.channel().await,
+        }
+    }
+
+    /// See `RssParser::parse_channel_only`.
+    pub async fn parse_channel_only<C: GradualRssChannel>(mut self) -> Result<C, RssError> {
+        self.channel().await
+    }
+
+    /// See `RssParser::stats`.
+    pub fn stats(&self) -> ParserStats {
+        match self {
+            FeedParser::Rss(parser) | FeedParser::Rdf(parser) => parser.stats(),
+            FeedParser::Atom(parser) => parser.stats(),
+        }
+    }
+
+    /// See `RssParser::into_inner`.
+    pub fn into_inner(self) -> (R, Vec<u8>) {
+        match self {
+            FeedParser::Rss(parser) | FeedParser::Rdf(parser) => parser.into_inner(),
+            FeedParser::Atom(parser) => parser.into_inner(),
+        }
+    }
+
+    pub fn format(&self) -> FeedFormat {
+        match self {
+            FeedParser::Rss(_) => FeedFormat::Rss,
+            FeedParser::Atom(_) => FeedFormat::Atom,
+            FeedParser::Rdf(_) => FeedFormat::Rdf,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[tokio::test]
+    async fn test_detect_rss() {
+        let feed = r#"<?xml version="1.0"?>
+<!-- generated -->
+<rss version="2.0"><channel><item><title>Hello</title></item></channel></rss>"#;
+        let parser = FeedParser::detect(Cursor::new(feed.as_bytes())).await.unwrap();
+        assert_eq!(parser.format(), FeedFormat::Rss);
+
+        let FeedParser::Rss(mut parser) = parser else {
+            panic!("expected an RSS parser");
+        };
+        assert_eq!(parser.next().await.unwrap().unwrap().title.as_deref(), Some("Hello"));
+    }
+
+    #[tokio::test]
+    async fn test_detect_atom() {
+        let feed = r#"<feed xmlns="http://www.w3.org/2005/Atom"><entry><title>Hi</title></entry></feed>"#;
+        let FeedParser::Atom(mut parser) = FeedParser::detect(Cursor::new(feed.as_bytes())).await.unwrap() else {
+            panic!("expected an Atom parser");
+        };
+        assert_eq!(parser.next().await.unwrap().unwrap().title.as_deref(), Some("Hi"));
+        assert!(parser.next().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_detect_rdf() {
+        let feed = r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#" xmlns="http://purl.org/rss/1.0/">
+    <channel rdf:about="https://example.com/"><title>Site</title></channel>
+    <item rdf:about="https://example.com/1"><title>One</title></item>
+</rdf:RDF>"#;
+        let parser = FeedParser::detect(Cursor::new(feed.as_bytes())).await.unwrap();
+        assert_eq!(parser.format(), FeedFormat::Rdf);
+    }
+
+    #[tokio::test]
+    async fn test_rdf_items_outside_channel() {
+        let feed = r#"<?xml version="1.0"?>
+<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#" xmlns="http://purl.org/rss/1.0/">
+    <channel rdf:about="https://example.com/rss">
+        <title>Site</title>
+        <items>
+            <rdf:Seq>
+                <rdf:li rdf:resource="https://example.com/1"/>
+                <rdf:li rdf:resource="https://example.com/2"/>
+            </rdf:Seq>
+        </items>
+    </channel>
+    <item rdf:about="https://example.com/1">
+        <title>One</title>
+        <link>https://example.com/1</link>
+    </item>
+    <item rdf:about="https://example.com/2"/>
+</rdf:RDF>"#;
+        let FeedParser::Rdf(mut parser) = FeedParser::detect(Cursor::new(feed.as_bytes())).await.unwrap() else {
+            panic!("expected an RDF parser");
+        };
+
+        let first = parser.next().await.unwrap().unwrap();
+        assert_eq!(first.title.as_deref(), Some("One"));
+        assert_eq!(first.link.as_deref(), Some("https://example.com/1"));
+        assert_eq!(first.guid.map(|guid| guid.value).as_deref(), Some("https://example.com/1"));
+
+        let second = parser.next().await.unwrap().unwrap();
+        assert_eq!(second.guid.map(|guid| guid.value).as_deref(), Some("https://example.com/2"));
+        assert!(parser.next().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_detect_unknown_and_empty() {
+        let html = "<html><body>not a feed</body></html>";
+        match FeedParser::detect(Cursor::new(html.as_bytes())).await {
+            Err(RssError::UnsupportedFormat(root)) => assert_eq!(root, "html"),
+            _ => panic!("expected an unsupported format error"),
+        }
+
+        let empty = FeedParser::detect(Cursor::new(Vec::<u8>::new())).await;
+        assert!(matches!(empty, Err(RssError::UnexpectedEof)));
+    }
+}
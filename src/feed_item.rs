@@ -0,0 +1,281 @@
+use serde::Serialize;
+
+use crate::atom::{AtomEntry, Link};
+use crate::enclosure::Enclosure;
+use crate::item::{Category, Guid, RssItem};
+use crate::person::Person;
+
+/// A format-neutral item that RSS 2.0, RSS 1.0 and Atom entries all map onto.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct FeedItem {
+    pub id: Option<String>,
+    pub title: Option<String>,
+    /// Every link of the item; Atom links as written, for RSS the `<link>` as
+    /// `alternate` and the enclosure as `enclosure`.
+    pub links: Vec<Link>,
+    pub summary: Option<String>,
+    pub content: Option<String>,
+    pub authors: Vec<Person>,
+    pub categories: Vec<Category>,
+    pub published: Option<String>,
+    pub updated: Option<String>,
+    pub rights: Option<String>,
+    pub language: Option<String>,
+}
+
+impl FeedItem {
+    /// The canonical alternate link, see `Link::canonical`.
+    pub fn link(&self) -> Option<&str> {
+        Link::canonical(&self.links).map(|link| link.href.as_str())
+    }
+
+    /// The summary (else the content) as plain text, see `RssItem::summary_text`.
+    pub fn summary_text(&self, max_chars: usize) -> Option<String> {
+        let html = self.summary.as_deref().or(self.content.as_deref())?;
+        Some(crate::html::html_to_text(html, Some(max_chars)))
+    }
+}
+
+#[cfg(feature = "sanitize")]
+impl FeedItem {
+    /// `summary` reduced to markup that is safe to render, see `sanitize_html`.
+    pub fn summary_sanitized(&self) -> Option<String> {
+        self.summary.as_deref().map(crate::html::sanitize_html)
+    }
+
+    /// `content` reduced to markup that is safe to render, see `sanitize_html`.
+    pub fn content_sanitized(&self) -> Option<String> {
+        self.content.as_deref().map(crate::html::sanitize_html)
+    }
+}
+
+impl From<RssItem> for FeedItem {
+    fn from(item: RssItem) -> Self {
+        let alternate = item.link.as_ref().map(|href| Link {
+            rel: "alternate".to_string(),
+            href: href.clone(),
+            mime_type: None,
+            title: None,
+        });
+        let enclosure = item.enclosure.as_ref().map(|Enclosure { url, mime_type, .. }| Link {
+            rel: "enclosure".to_string(),
+            href: url.clone(),
+            mime_type: mime_type.clone(),
+            title: None,
+        });
+        FeedItem {
+            id: item.identity(),
+            title: item.title,
+            links: alternate.into_iter().chain(enclosure).collect(),
+            summary: item.description,
+            content: item.content,
+            authors: item.author.into_iter().collect(),
+            categories: item.categories,
+            published: item.pub_date,
+            updated: None,
+            rights: item.rights,
+            language: item.language,
+        }
+    }
+}
+
+impl From<AtomEntry> for FeedItem {
+    fn from(entry: AtomEntry) -> Self {
+        FeedItem {
+            id: entry.id,
+            title: entry.title,
+            links: entry.links,
+            summary: entry.summary,
+            content: entry.content,
+            authors: entry.authors,
+            categories: entry.categories,
+            published: entry.published,
+            updated: entry.updated,
+            rights: entry.rights,
+            language: entry.language,
+        }
+    }
+}
+
+/// The reverse of `From<RssItem>`: the canonical link becomes `<link>`, the
+/// first `enclosure` link the enclosure, the first author `<author>` and the
+/// published (else updated) date `pubDate`. The id is kept as the guid, a
+/// permalink when it equals the link.
+impl From<FeedItem> for RssItem {
+    fn from(item: FeedItem) -> Self {
+        let link = item.link().map(String::from);
+        let enclosure = item.links.iter().find(|link| link.rel == "enclosure").map(|link| Enclosure {
+            url: link.href.clone(),
+            length: None,
+            mime_type: link.mime_type.clone(),
+        });
+        RssItem {
+            guid: item.id.map(|value| Guid {
+                is_permalink: link.as_ref() == Some(&value),
+                value,
+            }),
+            title: item.title,
+            link,
+            description: item.summary,
+            content: item.content,
+            author: item.authors.into_iter().next(),
+            categories: item.categories,
+            enclosure,
+            pub_date: item.published.or(item.updated),
+            rights: item.rights,
+            language: item.language,
+            ..RssItem::default()
+        }
+    }
+}
+
+impl From<FeedItem> for AtomEntry {
+    fn from(item: FeedItem) -> Self {
+        let mut entry = AtomEntry::default();
+        entry.link = item.link().map(String::from);
+        entry.id = item.id;
+        entry.title = item.title;
+        entry.links = item.links;
+        entry.summary = item.summary;
+        entry.content = item.content;
+        entry.authors = item.authors;
+        entry.categories = item.categories;
+        entry.published = item.published;
+        entry.updated = item.updated;
+        entry.rights = item.rights;
+        entry.language = item.language;
+        entry
+    }
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    use super::*;
+    use crate::FeedParser;
+    use std::io::Cursor;
+
+    async fn collect(feed: &'static str) -> Vec<FeedItem> {
+        let mut parser = FeedParser::detect(Cursor::new(feed.as_bytes())).await.unwrap();
+        let mut items = Vec::new();
+        while let Some(item) = parser.next().await.unwrap() {
+            items.push(item);
+        }
+        items
+    }
+
+    #[tokio::test]
+    async fn test_rss_maps_to_feed_item() {
+        let items = collect(
+            r#"<rss><channel><item>
+                <title>RSS</title>
+                <link>https://example.com/rss</link>
+                <description>Summary</description>
+                <content:encoded><![CDATA[<p>Body</p>]]></content:encoded>
+                <author>a@example.com</author>
+                <pubDate>Mon, 01 Jan 2024 00:00:00 GMT</pubDate>
+            </item></channel></rss>"#,
+        )
+        .await;
+
+        assert_eq!(
+            items,
+            vec![FeedItem {
+                id: Some("https://example.com/rss".to_string()),
+                title: Some("RSS".to_string()),
+                links: vec![Link {
+                    rel: "alternate".to_string(),
+                    href: "https://example.com/rss".to_string(),
+                    mime_type: None,
+                    title: None,
+                }],
+                summary: Some("Summary".to_string()),
+                content: Some("<p>Body</p>".to_string()),
+                authors: vec![Person {
+                    email: Some("a@example.com".to_string()),
+                    ..Person::default()
+                }],
+                categories: Vec::new(),
+                published: Some("Mon, 01 Jan 2024 00:00:00 GMT".to_string()),
+                updated: None,
+                rights: None,
+                language: None,
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rdf_maps_to_feed_item() {
+        let items = collect(
+            r#"<rdf:RDF><channel><title>C</title></channel>
+                <item rdf:about="urn:1"><title>RDF</title><link>https://example.com/1</link></item>
+            </rdf:RDF>"#,
+        )
+        .await;
+
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].id.as_deref(), Some("urn:1"));
+        assert_eq!(items[0].link(), Some("https://example.com/1"));
+    }
+
+    #[tokio::test]
+    async fn test_dublin_core_maps_to_feed_item() {
+        let items = collect(
+            r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#" xmlns:dc="http://purl.org/dc/elements/1.1/">
+                <item rdf:about="urn:dc">
+                    <title>DC</title>
+                    <dc:creator>Ada</dc:creator>
+                    <dc:date>2024-01-01T00:00:00Z</dc:date>
+                    <dc:subject>Science</dc:subject>
+                    <dc:subject>History</dc:subject>
+                    <dc:rights>CC BY 4.0</dc:rights>
+                </item>
+            </rdf:RDF>"#,
+        )
+        .await;
+
+        assert_eq!(items[0].authors, vec![Person::named("Ada")]);
+        assert_eq!(items[0].published.as_deref(), Some("2024-01-01T00:00:00Z"));
+        let categories: Vec<&str> = items[0].categories.iter().map(|category| category.name.as_str()).collect();
+        assert_eq!(categories, vec!["Science", "History"]);
+        assert_eq!(items[0].rights.as_deref(), Some("CC BY 4.0"));
+    }
+
+    #[tokio::test]
+    async fn test_atom_maps_to_feed_item() {
+        let items = collect(
+            r#"<feed xml:lang="en-GB"><entry>
+                <id>urn:atom</id>
+                <title>Atom</title>
+                <link href="https://example.org/a" title="A"/>
+                <summary>Short</summary>
+                <content>Long</content>
+                <author><name>Jo</name></author>
+                <published>2024-01-01T00:00:00Z</published>
+                <updated>2024-01-02T00:00:00Z</updated>
+            </entry></feed>"#,
+        )
+        .await;
+
+        assert_eq!(
+            items,
+            vec![FeedItem {
+                id: Some("urn:atom".to_string()),
+                title: Some("Atom".to_string()),
+                links: vec![Link {
+                    rel: "alternate".to_string(),
+                    href: "https://example.org/a".to_string(),
+                    mime_type: None,
+                    title: Some("A".to_string()),
+                }],
+                summary: Some("Short".to_string()),
+                content: Some("Long".to_string()),
+                authors: vec![Person::named("Jo")],
+                categories: Vec::new(),
+                published: Some("2024-01-01T00:00:00Z".to_string()),
+                updated: Some("2024-01-02T00:00:00Z".to_string()),
+                rights: None,
+                language: Some("en-GB".to_string()),
+            }]
+        );
+    }
+}
@@ -0,0 +1,196 @@
+//! Ready-made predicates for `RssStreamExt::filter_items`. Any
+//! `FnMut(&T) -> bool` works as well, such as a closure around a regex.
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use tokio_stream::Stream;
+
+#[cfg(feature = "dates")]
+use crate::aggregate::Dated;
+use crate::atom::AtomEntry;
+#[cfg(feature = "dates")]
+use crate::date::DateTime;
+use crate::error::RssError;
+use crate::feed_item::FeedItem;
+use crate::item::RssItem;
+
+/// The fields the ready-made predicates look at.
+pub trait Filterable {
+    fn title(&self) -> Option<&str>;
+    /// The description, or summary.
+    fn summary(&self) -> Option<&str>;
+    fn categories(&self) -> Vec<&str>;
+}
+
+impl Filterable for RssItem {
+    fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    fn summary(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    fn categories(&self) -> Vec<&str> {
+        self.categories.iter().map(|category| category.name.as_str()).collect()
+    }
+}
+
+impl Filterable for AtomEntry {
+    fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    fn summary(&self) -> Option<&str> {
+        self.summary.as_deref()
+    }
+
+    fn categories(&self) -> Vec<&str> {
+        self.categories.iter().map(|category| category.name.as_str()).collect()
+    }
+}
+
+impl Filterable for FeedItem {
+    fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    fn summary(&self) -> Option<&str> {
+        self.summary.as_deref()
+    }
+
+    fn categories(&self) -> Vec<&str> {
+        self.categories.iter().map(|category| category.name.as_str()).collect()
+    }
+}
+
+/// Items whose title or summary contains any of `words`, ignoring case.
+pub fn keywords<T: Filterable>(words: &[&str]) -> impl Fn(&T) -> bool + Send + 'static {
+    let words = lowercase(words);
+    move |item| [item.title(), item.summary()].into_iter().flatten().any(|text| contains_any(text, &words))
+}
+
+/// Items whose title contains any of `words`, ignoring case.
+pub fn title_contains<T: Filterable>(words: &[&str]) -> impl Fn(&T) -> bool + Send + 'static {
+    let words = lowercase(words);
+    move |item| item.title().is_some_and(|title| contains_any(title, &words))
+}
+
+/// Items whose whole title or summary matches `pattern`, ignoring case,
+/// where `*` stands for any run of characters and `?` for one: `*rust 1.*`.
+pub fn matches<T: Filterable>(pattern: &str) -> impl Fn(&T) -> bool + Send + 'static {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    move |item| [item.title(), item.summary()].into_iter().flatten().any(|text| glob(&pattern, text))
+}
+
+/// Items in the category `name`, ignoring case.
+pub fn category<T: Filterable>(name: &str) -> impl Fn(&T) -> bool + Send + 'static {
+    let name = name.to_string();
+    move |item| item.categories().iter().any(|category| category.trim().eq_ignore_ascii_case(&name))
+}
+
+/// Items dated after `after`; undated items are dropped.
+#[cfg(feature = "dates")]
+pub fn published_after<T: Dated>(after: DateTime) -> impl Fn(&T) -> bool + Send + 'static {
+    move |item| item.date().is_some_and(|date| date > after)
+}
+
+fn lowercase(words: &[&str]) -> Vec<String> {
+    words.iter().map(|word| word.to_lowercase()).collect()
+}
+
+fn contains_any(text: &str, words: &[String]) -> bool {
+    let text = text.to_lowercase();
+    words.iter().any(|word| text.contains(word.as_str()))
+}
+
+// Wildcard matching, backtracking to the last `*` on a mismatch.
+fn glob(pattern: &[char], text: &str) -> bool {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// The items of a stream that pass a predicate, from `RssStreamExt::filter_items`.
+///
+/// Errors are passed on.
+pub struct FilterItems<S, F> {
+    pub(crate) stream: S,
+    pub(crate) predicate: F,
+}
+
+impl<S: Unpin, F> Unpin for FilterItems<S, F> {}
+
+impl<S, T, F> Stream for FilterItems<S, F>
+where
+    S: Stream<Item = Result<T, RssError>> + Unpin,
+    F: FnMut(&T) -> bool,
+{
+    type Item = Result<T, RssError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            match Pin::new(&mut this.stream).poll_next(cx) {
+                Poll::Ready(Some(Ok(item))) if !(this.predicate)(&item) => continue,
+                other => return other,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RssParser, RssStreamExt};
+    use std::io::Cursor;
+    use tokio_stream::StreamExt;
+
+    const FEED: &str = r#"<rss><channel>
+<item><title>Rust 1.80 released</title><category>Releases</category><pubDate>Thu, 25 Jul 2024 00:00:00 GMT</pubDate></item>
+<item><title>Gardening tips</title><description>Grow tomatoes, not RUST</description></item>
+<item><title>Weekly news</title><category>news</category></item>
+</channel></rss>"#;
+
+    async fn titles<F: FnMut(&RssItem) -> bool>(predicate: F) -> Vec<String> {
+        let parser = RssParser::<RssItem, _>::new(Cursor::new(FEED.as_bytes())).await.unwrap();
+        parser.into_stream().filter_items(predicate).map(|item| item.unwrap().title.unwrap()).collect().await
+    }
+
+    #[tokio::test]
+    async fn test_filters() {
+        assert_eq!(titles(keywords(&["rust"])).await, vec!["Rust 1.80 released", "Gardening tips"]);
+        assert_eq!(titles(title_contains(&["rust", "weekly"])).await, vec!["Rust 1.80 released", "Weekly news"]);
+        assert_eq!(titles(matches("rust 1.*")).await, vec!["Rust 1.80 released"]);
+        assert_eq!(titles(matches("*tomato?s*")).await, vec!["Gardening tips"]);
+        assert_eq!(titles(category("News")).await, vec!["Weekly news"]);
+        assert_eq!(titles(|item: &RssItem| item.categories.is_empty()).await, vec!["Gardening tips"]);
+        #[cfg(feature = "dates")]
+        {
+            let after = crate::parse_date("2024-01-01T00:00:00Z").unwrap();
+            assert_eq!(titles(published_after(after)).await, vec!["Rust 1.80 released"]);
+        }
+    }
+}
@@ -0,0 +1,429 @@
+// A forgiving HTML tokenizer for the markup found in feeds and web pages.
+// It only splits tags from text; it doesn't build a tree or fix nesting.
+
+use std::borrow::Cow;
+
+use quick_xml::escape::resolve_html5_entity;
+#[cfg(feature = "sanitize")]
+use quick_xml::escape::{escape, partial_escape};
+
+// Elements whose content is text up to their end tag, not markup.
+const RAW_TEXT: [&str; 6] = ["script", "style", "textarea", "title", "xmp", "noscript"];
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum Token<'a> {
+    /// Text as written, entities not decoded yet.
+    Text(&'a str),
+    /// A start tag with its lowercased name and attribute names, and decoded values.
+    Start {
+        name: String,
+        attributes: Vec<(String, String)>,
+        self_closing: bool,
+    },
+    End(String),
+}
+
+pub(crate) struct Tokens<'a> {
+    rest: &'a str,
+    raw_text: Option<String>,
+}
+
+impl<'a> Tokens<'a> {
+    pub(crate) fn new(html: &'a str) -> Self {
+        Tokens { rest: html, raw_text: None }
+    }
+
+    fn take(&mut self, len: usize) -> &'a str {
+        let (taken, rest) = self.rest.split_at(len);
+        self.rest = rest;
+        taken
+    }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        if let Some(name) = self.raw_text.take() {
+            let end = self.rest.to_ascii_lowercase().find(&format!("</{}", name)).unwrap_or(self.rest.len());
+            if end > 0 {
+                return Some(Token::Text(self.take(end)));
+            }
+        }
+        loop {
+            if self.rest.is_empty() {
+                return None;
+            }
+            if !self.rest.starts_with('<') {
+                let end = self.rest.find('<').unwrap_or(self.rest.len());
+                return Some(Token::Text(self.take(end)));
+            }
+            let after = &self.rest[1..];
+            if let Some(comment) = after.strip_prefix("!--") {
+                self.rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+                continue;
+            }
+            if after.starts_with(['!', '?']) {
+                self.rest = after.find('>').map_or("", |end| &after[end + 1..]);
+                continue;
+            }
+            let (closing, after) = match after.strip_prefix('/') {
+                Some(after) => (true, after),
+                None => (false, after),
+            };
+            if !after.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                // A lone `<`, as in `a < b`.
+                return Some(Token::Text(self.take(1)));
+            }
+            let name_end = after.find(|c: char| c.is_ascii_whitespace() || c == '>' || c == '/').unwrap_or(after.len());
+            let name = after[..name_end].to_ascii_lowercase();
+            if closing {
+                self.rest = after.find('>').map_or("", |end| &after[end + 1..]);
+                return Some(Token::End(name));
+            }
+            let (attributes, self_closing, rest) = attributes(&after[name_end..]);
+            self.rest = rest;
+            if !self_closing && RAW_TEXT.contains(&name.as_str()) {
+                self.raw_text = Some(name.clone());
+            }
+            return Some(Token::Start {
+                name,
+                attributes,
+                self_closing,
+            });
+        }
+    }
+}
+
+// The attributes of a tag up to its `>`, whether it ended in `/>`, and what follows it.
+fn attributes(mut text: &str) -> (Vec<(String, String)>, bool, &str) {
+    let mut attributes = Vec::new();
+    let mut self_closing = false;
+    loop {
+        text = text.trim_start();
+        if text.is_empty() {
+            return (attributes, self_closing, text);
+        }
+        if let Some(rest) = text.strip_prefix('>') {
+            return (attributes, self_closing, rest);
+        }
+        if let Some(rest) = text.strip_prefix('/') {
+            self_closing = true;
+            text = rest;
+            continue;
+        }
+        self_closing = false;
+        let name_end = text
+            .find(|c: char| c.is_ascii_whitespace() || matches!(c, '=' | '>' | '/'))
+            .unwrap_or(text.len())
+            .max(1);
+        let name = text[..name_end].to_ascii_lowercase();
+        text = text[name_end..].trim_start();
+        let mut value = String::new();
+        if let Some(rest) = text.strip_prefix('=') {
+            let rest = rest.trim_start();
+            let (raw, after) = match rest.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let rest = &rest[1..];
+                    let end = rest.find(quote).unwrap_or(rest.len());
+                    (&rest[..end], rest.get(end + 1..).unwrap_or_default())
+                }
+                _ => {
+                    let end = rest.find(|c: char| c.is_ascii_whitespace() || c == '>').unwrap_or(rest.len());
+                    (&rest[..end], &rest[end..])
+                }
+            };
+            value = decode_entities(raw).into_owned();
+            text = after;
+        }
+        attributes.push((name, value));
+    }
+}
+
+/// Decodes character references the way browsers do, leaving anything that
+/// isn't one (a bare `&`, an unknown name) as written. Named references
+/// need their `;`, so `?a=1&copy=2` stays intact.
+pub(crate) fn decode_entities(text: &str) -> Cow<'_, str> {
+    if !text.contains('&') {
+        return Cow::Borrowed(text);
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp + 1..];
+        let end = rest.find(|c: char| !c.is_ascii_alphanumeric() && c != '#').unwrap_or(rest.len());
+        let (name, terminated) = (&rest[..end], rest[end..].starts_with(';'));
+        let resolved = match name.strip_prefix('#') {
+            Some(number) => {
+                let code = match number.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                    None => number.parse().ok(),
+                };
+                code.map(|code| char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER).to_string())
+            }
+            None if terminated => resolve_html5_entity(name).map(String::from),
+            None => None,
+        };
+        match resolved {
+            Some(resolved) => {
+                out.push_str(&resolved);
+                rest = &rest[end + usize::from(terminated)..];
+            }
+            None => out.push('&'),
+        }
+    }
+    out.push_str(rest);
+    Cow::Owned(out)
+}
+
+// Elements whose text isn't part of the document's text.
+const HIDDEN_TAGS: [&str; 7] = ["script", "style", "noscript", "template", "title", "head", "xmp"];
+
+/// The text of an HTML fragment: tags stripped, entities decoded and
+/// whitespace collapsed to single spaces. With `max_chars`, longer text is
+/// cut at a word boundary and ends in `…`, within `max_chars` characters.
+pub fn html_to_text(html: &str, max_chars: Option<usize>) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut hidden: Option<String> = None;
+    for token in Tokens::new(html) {
+        match token {
+            Token::Text(_) | Token::Start { .. } if hidden.is_some() => {}
+            Token::Text(raw) => text.push_str(&decode_entities(raw)),
+            Token::Start { name, self_closing, .. } => {
+                if HIDDEN_TAGS.contains(&name.as_str()) && !self_closing {
+                    hidden = Some(name);
+                } else if !INLINE_TAGS.contains(&name.as_str()) {
+                    // Tags such as `<p>` or `<br>` separate words.
+                    text.push(' ');
+                }
+            }
+            Token::End(name) => {
+                if hidden.as_deref() == Some(name.as_str()) {
+                    hidden = None;
+                } else if hidden.is_none() && !INLINE_TAGS.contains(&name.as_str()) {
+                    text.push(' ');
+                }
+            }
+        }
+    }
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    match max_chars {
+        Some(max) => truncate_words(text, max),
+        None => text,
+    }
+}
+
+// Inline elements, whose tags don't separate words.
+const INLINE_TAGS: [&str; 18] = [
+    "a", "abbr", "b", "bdi", "bdo", "cite", "code", "em", "i", "kbd", "mark", "q", "s", "small", "span", "strong", "sub", "sup",
+];
+
+fn truncate_words(text: String, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text;
+    }
+    let Some(max_chars) = max_chars.checked_sub(1) else {
+        return String::new();
+    };
+    let cut = text.char_indices().nth(max_chars).map_or(text.len(), |(index, _)| index);
+    let mut truncated = &text[..cut];
+    // Back up to the last space unless that would drop most of the text.
+    if !text[cut..].starts_with(' ')
+        && let Some(space) = truncated.rfind(' ')
+        && space >= cut / 2
+    {
+        truncated = &truncated[..space];
+    }
+    format!("{}…", truncated.trim_end())
+}
+
+/// The `src` of the first `<img>`, skipping 1x1 tracking pixels.
+pub(crate) fn first_image(html: &str) -> Option<String> {
+    Tokens::new(html).find_map(|token| {
+        let Token::Start { name, attributes, .. } = token else { return None };
+        if name != "img" {
+            return None;
+        }
+        let attr = |wanted: &str| attributes.iter().find(|(name, _)| name == wanted).map(|(_, value)| value.trim());
+        if attr("width").is_some_and(|width| width == "1" || width == "0") || attr("height").is_some_and(|height| height == "1" || height == "0") {
+            return None;
+        }
+        attr("src").filter(|src| !src.is_empty()).map(String::from)
+    })
+}
+
+#[cfg(feature = "sanitize")]
+const ALLOWED_TAGS: [&str; 52] = [
+    "a", "abbr", "b", "bdi", "bdo", "blockquote", "br", "caption", "cite", "code", "col", "colgroup", "dd", "del", "dfn", "div",
+    "dl", "dt", "em", "figcaption", "figure", "h1", "h2", "h3", "h4", "h5", "h6", "hr", "i", "img", "ins", "kbd", "li", "mark",
+    "ol", "p", "pre", "q", "s", "small", "span", "strong", "sub", "sup", "table", "tbody", "td", "tfoot", "th", "thead",
+    "tr", "ul",
+];
+
+// Not allowed, and dropped together with everything inside them.
+#[cfg(feature = "sanitize")]
+const DROPPED_TAGS: [&str; 14] = [
+    "script", "style", "iframe", "object", "embed", "noscript", "template", "svg", "math", "form", "textarea", "select", "title",
+    "xmp",
+];
+
+#[cfg(feature = "sanitize")]
+const VOID_TAGS: [&str; 5] = ["br", "col", "hr", "img", "wbr"];
+
+/// Reduces HTML to markup that is safe to render: an allowlist of
+/// formatting tags and attributes, with scripts, styles, embedded content,
+/// event handlers and `javascript:` URLs removed. Tags that aren't allowed
+/// are dropped but their text kept; links get `rel="noopener noreferrer"`;
+/// unclosed tags are closed.
+#[cfg(feature = "sanitize")]
+pub fn sanitize_html(html: &str) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut open: Vec<String> = Vec::new();
+    // The dropped element being skipped, and how deeply it is nested in itself.
+    let mut dropping: Option<(String, usize)> = None;
+    for token in Tokens::new(html) {
+        if let Some((name, depth)) = &mut dropping {
+            match &token {
+                Token::Start { name: start, self_closing: false, .. } if start == name => *depth += 1,
+                Token::End(end) if end == name => {
+                    *depth -= 1;
+                    if *depth == 0 {
+                        dropping = None;
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+        match token {
+            Token::Text(text) => out.push_str(&partial_escape(decode_entities(text))),
+            Token::Start {
+                name,
+                attributes,
+                self_closing,
+            } => {
+                if DROPPED_TAGS.contains(&name.as_str()) {
+                    if !self_closing {
+                        dropping = Some((name, 1));
+                    }
+                    continue;
+                }
+                if !ALLOWED_TAGS.contains(&name.as_str()) {
+                    continue;
+                }
+                out.push('<');
+                out.push_str(&name);
+                for (attribute, value) in &attributes {
+                    if allowed_attribute(&name, attribute, value) {
+                        out.push_str(&format!(" {}=\"{}\"", attribute, escape(value.as_str())));
+                    }
+                }
+                if name == "a" {
+                    out.push_str(" rel=\"noopener noreferrer\"");
+                }
+                out.push('>');
+                if VOID_TAGS.contains(&name.as_str()) {
+                    continue;
+                }
+                if self_closing {
+                    out.push_str(&format!("</{}>", name));
+                } else {
+                    open.push(name);
+                }
+            }
+            // End tags close what they match, and whatever was left open inside it.
+            Token::End(name) => {
+                if let Some(position) = open.iter().rposition(|open| *open == name) {
+                    for name in open.drain(position..).rev() {
+                        out.push_str(&format!("</{}>", name));
+                    }
+                }
+            }
+        }
+    }
+    for name in open.into_iter().rev() {
+        out.push_str(&format!("</{}>", name));
+    }
+    out
+}
+
+#[cfg(feature = "sanitize")]
+fn allowed_attribute(tag: &str, attribute: &str, value: &str) -> bool {
+    match (tag, attribute) {
+        ("a", "href") | ("img", "src") | ("blockquote" | "q" | "del" | "ins", "cite") => safe_url(value),
+        (_, "title" | "lang" | "dir")
+        | ("img", "alt" | "width" | "height")
+        | ("td" | "th", "colspan" | "rowspan")
+        | ("ol", "start" | "type")
+        | ("del" | "ins", "datetime") => true,
+        _ => false,
+    }
+}
+
+// Relative URLs and the http, https and mailto schemes. Browsers ignore
+// whitespace and control characters inside a scheme, so they are too.
+#[cfg(feature = "sanitize")]
+fn safe_url(url: &str) -> bool {
+    let url: String = url.chars().filter(|c| !c.is_ascii_whitespace() && !c.is_control()).collect();
+    match url.find([':', '/', '?', '#']) {
+        Some(colon) if url[colon..].starts_with(':') => {
+            matches!(url[..colon].to_ascii_lowercase().as_str(), "http" | "https" | "mailto")
+        }
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens() {
+        let html = "a < b<!-- x --><P Class=x>one<br/></p><script>if (a<b) {}</script >";
+        let tokens: Vec<_> = Tokens::new(html).collect();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Text("a "),
+                Token::Text("<"),
+                Token::Text(" b"),
+                Token::Start { name: "p".to_string(), attributes: vec![("class".to_string(), "x".to_string())], self_closing: false },
+                Token::Text("one"),
+                Token::Start { name: "br".to_string(), attributes: vec![], self_closing: true },
+                Token::End("p".to_string()),
+                Token::Start { name: "script".to_string(), attributes: vec![], self_closing: false },
+                Token::Text("if (a<b) {}"),
+                Token::End("script".to_string()),
+            ]
+        );
+        assert_eq!(decode_entities("&lt;&#65;&#x42;&amp &copy;?a=1&copy=2&unknown;"), "<AB&amp ©?a=1&copy=2&unknown;");
+    }
+
+    #[test]
+    fn test_html_to_text() {
+        let html = "<head><title>Page</title></head><p>Caf&eacute; <b>open</b>&nbsp;today.</p><p>Second<br>line</p>\n\n<script>var x = '<p>';</script>";
+        assert_eq!(html_to_text(html, None), "Café open today. Second line");
+        assert_eq!(html_to_text(html, Some(20)), "Café open today.…");
+        assert_eq!(html_to_text("Supercalifragilistic", Some(6)), "Super…");
+        assert_eq!(html_to_text("<em>it</em>al<b>ic</b>", None), "italic");
+    }
+
+    #[cfg(feature = "sanitize")]
+    #[test]
+    fn test_sanitize_html() {
+        let html = r#"<p onclick="steal()">Hi <b>there<script>alert(1)</script></b>
+<a href="JaVa&#x09;script:alert(1)">x</a> <a href="/post?a=1&amp;b=2" target=_blank>post</a>
+<img src="https://example.com/a.png" onerror="x()" alt="A"><iframe src="//evil"><p>inside</p></iframe>
+<custom>kept &amp; escaped &lt;tag&gt;</custom><div><em>unclosed"#;
+        assert_eq!(
+            sanitize_html(html),
+            concat!(
+                "<p>Hi <b>there</b>\n",
+                "<a rel=\"noopener noreferrer\">x</a> <a href=\"/post?a=1&amp;b=2\" rel=\"noopener noreferrer\">post</a>\n",
+                "<img src=\"https://example.com/a.png\" alt=\"A\">\n",
+                "kept &amp; escaped &lt;tag&gt;<div><em>unclosed</em></div></p>",
+            )
+        );
+    }
+}
@@ -0,0 +1,336 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+use tokio::io::AsyncReadExt;
+use tokio::sync::Mutex;
+
+use crate::error::RssError;
+use crate::http::{fetch_uncached, FetchOptions, HttpBody, ResponseHead};
+use crate::item::fnv1a;
+use crate::store::{FileStore, SeenStore};
+
+/// Keeps feed responses by URL for `FetchOptions::cache`, so fetching a feed
+/// again soon after doesn't reach its server.
+///
+/// A response is answered from the cache for as long as its `Cache-Control:
+/// max-age` (or `Expires`) allows. After that it is revalidated with its
+/// `ETag` and `Last-Modified`, and a `304` answers with the kept body again.
+/// `no-store` responses are never kept and `no-cache` ones are always
+/// revalidated. A response with `Vary` only answers requests sending the same
+/// values for those headers, and requests with credentials (`auth`, cookies,
+/// or an `Authorization` or `Cookie` header) bypass the cache entirely.
+/// Responses that are kept are read in full before parsing starts, so bodies
+/// over `max_body_size` are passed through without being kept. Clones share
+/// the same cache.
+#[derive(Clone)]
+pub struct HttpCache {
+    store: Arc<Mutex<Store>>,
+    max_body_size: u64,
+}
+
+const MAX_BODY_BYTES: u64 = 16 * 1024 * 1024;
+
+enum Store {
+    Memory(HashMap<String, (Entry, Vec<u8>)>),
+    // Entries go in the index, bodies in a file each under `bodies`.
+    File { index: FileStore, bodies: PathBuf },
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Entry {
+    etag: Option<String>,
+    last_modified: Option<String>,
+    // Unix seconds.
+    fresh_until: u64,
+    // The request headers named by `Vary`, with the values that were sent.
+    vary: Vec<(String, Option<String>)>,
+    // FNV-1a of the body, so a body file left from another entry is a miss.
+    body_hash: u64,
+}
+
+impl HttpCache {
+    /// A cache in memory, gone with the process.
+    pub fn new() -> Self {
+        HttpCache::with_store(Store::Memory(HashMap::new()))
+    }
+
+    /// A cache kept on disk, so it outlives the process: an index of
+    /// responses in the file at `path`, as a `FileStore`, and their bodies in
+    /// the directory `path` with `.bodies` appended.
+    pub async fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let mut bodies = path.as_os_str().to_owned();
+        bodies.push(".bodies");
+        Ok(HttpCache::with_store(Store::File {
+            index: FileStore::open(path).await?,
+            bodies: bodies.into(),
+        }))
+    }
+
+    fn with_store(store: Store) -> Self {
+        HttpCache {
+            store: Arc::new(Mutex::new(store)),
+            max_body_size: MAX_BODY_BYTES,
+        }
+    }
+
+    /// The largest body kept, 16 MiB by default.
+    pub fn max_body_size(mut self, bytes: u64) -> Self {
+        self.max_body_size = bytes;
+        self
+    }
+
+    pub(crate) async fn fetch(&self, url: &str, options: &FetchOptions) -> Result<(ResponseHead, HttpBody), RssError> {
+        if options.has_credentials() {
+            return fetch_uncached(url, options).await;
+        }
+        let key = format!("http-cache {}", url);
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let kept = self.store.lock().await.get(&key, self.max_body_size).await?.filter(|(entry, _)| {
+            entry.vary.iter().all(|(name, value)| options.sent_header(name) == *value)
+        });
+        if let Some((entry, body)) = kept.as_ref().filter(|(entry, _)| entry.fresh_until > now) {
+            return Ok(entry.response(body.clone(), options, now));
+        }
+
+        // Revalidate with the kept validators, unless the caller sent its own.
+        let mut request = FetchOptions {
+            cache: None,
+            ..options.clone()
+        };
+        let revalidate = kept.is_some() && options.etag.is_none() && options.last_modified.is_none();
+        if revalidate && let Some((entry, _)) = &kept {
+            request.etag = entry.etag.clone();
+            request.last_modified = entry.last_modified.clone();
+        }
+        let (head, mut body) = fetch_uncached(url, &request).await?;
+        if head.status == 304
+            && revalidate
+            && let Some((mut entry, kept_body)) = kept
+        {
+            entry.fresh_until = now + fresh_for(&head);
+            self.store.lock().await.put(key, &entry, None).await?;
+            return Ok(entry.response(kept_body, options, now));
+        }
+
+        let vary: Vec<String> = head
+            .header("vary")
+            .map(|vary| vary.split(',').map(|name| name.trim().to_ascii_lowercase()).filter(|name| !name.is_empty()).collect())
+            .unwrap_or_default();
+        let entry = Entry {
+            etag: head.header("etag").map(String::from),
+            last_modified: head.header("last-modified").map(String::from),
+            fresh_until: now + fresh_for(&head),
+            vary: vary.iter().map(|name| (name.clone(), options.sent_header(name))).collect(),
+            body_hash: 0,
+        };
+        let useful = entry.fresh_until > now || entry.etag.is_some() || entry.last_modified.is_some();
+        if head.status != 200 || has_directive(&head, "no-store") || !useful || vary.iter().any(|name| name == "*") {
+            return Ok((head, body));
+        }
+        let length = head.header("content-length").and_then(|length| length.trim().parse::<u64>().ok());
+        if length.is_some_and(|length| length > self.max_body_size) {
+            return Ok((head, body));
+        }
+        let mut bytes = Vec::new();
+        (&mut body).take(self.max_body_size.saturating_add(1)).read_to_end(&mut bytes).await?;
+        if bytes.len() as u64 > self.max_body_size {
+            return Ok((head, body.unread(bytes)));
+        }
+        let entry = Entry { body_hash: fnv1a(&bytes), ..entry };
+        self.store.lock().await.put(key, &entry, Some(bytes.clone())).await?;
+        Ok((head, HttpBody::cached(bytes)))
+    }
+}
+
+impl Default for HttpCache {
+    fn default() -> Self {
+        HttpCache::new()
+    }
+}
+
+impl fmt::Debug for HttpCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HttpCache").finish_non_exhaustive()
+    }
+}
+
+impl Store {
+    // A body file over `max_body_size`, kept under a larger limit, is a miss.
+    async fn get(&self, key: &str, max_body_size: u64) -> io::Result<Option<(Entry, Vec<u8>)>> {
+        let (index, bodies) = match self {
+            Store::Memory(entries) => return Ok(entries.get(key).cloned()),
+            Store::File { index, bodies } => (index, bodies),
+        };
+        let Some(entry) = index.get(key).await?.and_then(|json| serde_json::from_str::<Entry>(&json).ok()) else {
+            return Ok(None);
+        };
+        let path = bodies.join(body_file(key));
+        let body = match fs::metadata(&path).await {
+            Ok(metadata) if metadata.len() > max_body_size => return Ok(None),
+            Ok(_) => fs::read(&path).await,
+            Err(err) => Err(err),
+        };
+        let body = match body {
+            Ok(body) => body,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        Ok((fnv1a(&body) == entry.body_hash).then_some((entry, body)))
+    }
+
+    // `body` is `None` when only the entry changed, as after a `304`.
+    async fn put(&mut self, key: String, entry: &Entry, body: Option<Vec<u8>>) -> io::Result<()> {
+        let (index, bodies) = match self {
+            Store::Memory(entries) => {
+                if let Some(body) = body {
+                    entries.insert(key, (entry.clone(), body));
+                } else if let Some(kept) = entries.get_mut(&key) {
+                    kept.0 = entry.clone();
+                }
+                return Ok(());
+            }
+            Store::File { index, bodies } => (index, bodies),
+        };
+        if let Some(body) = body {
+            fs::create_dir_all(&*bodies).await?;
+            let path = bodies.join(body_file(&key));
+            let mut partial = path.clone().into_os_string();
+            partial.push(".tmp");
+            fs::write(&partial, body).await?;
+            fs::rename(&partial, &path).await?;
+        }
+        index.put(key, serde_json::to_string(entry)?).await
+    }
+}
+
+fn body_file(key: &str) -> String {
+    format!("{:016x}", fnv1a(key.as_bytes()))
+}
+
+impl Entry {
+    // The kept response, or a `304` when the caller's validators match it.
+    fn response(&self, body: Vec<u8>, options: &FetchOptions, now: u64) -> (ResponseHead, HttpBody) {
+        let mut headers = vec![("cache-control".to_string(), format!("max-age={}", self.fresh_until.saturating_sub(now)))];
+        if let Some(etag) = &self.etag {
+            headers.push(("etag".to_string(), etag.clone()));
+        }
+        if let Some(last_modified) = &self.last_modified {
+            headers.push(("last-modified".to_string(), last_modified.clone()));
+        }
+        let unchanged = (options.etag.is_some() && options.etag == self.etag)
+            || (options.last_modified.is_some() && options.last_modified == self.last_modified);
+        let (status, body) = if unchanged { (304, Vec::new()) } else { (200, body) };
+        (ResponseHead { status, headers }, HttpBody::cached(body))
+    }
+}
+
+// Seconds the response may be answered from the cache before revalidating.
+fn fresh_for(head: &ResponseHead) -> u64 {
+    if has_directive(head, "no-cache") {
+        return 0;
+    }
+    head.freshness().unwrap_or_default().as_secs()
+}
+
+fn has_directive(head: &ResponseHead, name: &str) -> bool {
+    head.header("cache-control")
+        .is_some_and(|control| control.split(',').any(|directive| directive.trim().eq_ignore_ascii_case(name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::tests::serve;
+    use crate::{FetchResult, RssItem, RssParser};
+
+    const FEED: &str = "<rss><channel><item><title>Cached</title></item></channel></rss>";
+
+    fn ok(headers: &str) -> String {
+        format!("HTTP/1.1 200 OK\r\n{}Content-Length: {}\r\n\r\n{}", headers, FEED.len(), FEED)
+    }
+
+    async fn title(url: &str, options: &FetchOptions) -> Result<Option<String>, RssError> {
+        match RssParser::<RssItem, _>::from_url_with(url, options).await? {
+            FetchResult::Modified { mut parser, .. } => Ok(parser.next().await?.and_then(|item| item.title)),
+            FetchResult::NotModified => Ok(None),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fresh_responses_come_from_cache() {
+        // The server answers once; later fetches would be refused.
+        let base = serve(vec![ok("Cache-Control: max-age=60\r\nETag: \"v1\"\r\n")]).await;
+        let options = FetchOptions::new().cache(HttpCache::new());
+        assert_eq!(title(&base, &options).await.unwrap().as_deref(), Some("Cached"));
+        assert_eq!(title(&base, &options).await.unwrap().as_deref(), Some("Cached"));
+        assert_eq!(title(&base, &options.clone().etag("\"v1\"")).await.unwrap(), None);
+
+        let base = serve(vec![ok("Cache-Control: no-store, max-age=60\r\n")]).await;
+        let options = FetchOptions::new().cache(HttpCache::new());
+        assert!(title(&base, &options).await.is_ok());
+        assert!(title(&base, &options).await.is_err());
+
+        // Responses to requests with credentials are never shared.
+        let base = serve(vec![ok("Cache-Control: max-age=60\r\n")]).await;
+        let options = FetchOptions::new().cache(HttpCache::new()).bearer_auth("token");
+        assert!(title(&base, &options).await.is_ok());
+        assert!(title(&base, &options).await.is_err());
+
+        // Too large to keep, with or without a Content-Length, but read in full.
+        let chunked = format!("HTTP/1.1 200 OK\r\nCache-Control: max-age=60\r\nTransfer-Encoding: chunked\r\n\r\n{:x}\r\n{}\r\n0\r\n\r\n", FEED.len(), FEED);
+        for response in [ok("Cache-Control: max-age=60\r\n"), chunked] {
+            let base = serve(vec![response]).await;
+            let options = FetchOptions::new().cache(HttpCache::new().max_body_size(16));
+            assert_eq!(title(&base, &options).await.unwrap().as_deref(), Some("Cached"));
+            assert!(title(&base, &options).await.is_err());
+        }
+
+        // A response that has aged past its max-age is stale already.
+        let base = serve(vec![ok("Cache-Control: max-age=60\r\nAge: 60\r\n")]).await;
+        let options = FetchOptions::new().cache(HttpCache::new());
+        assert!(title(&base, &options).await.is_ok());
+        assert!(title(&base, &options).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_vary_matches_request_headers() {
+        let base = serve(vec![
+            ok("Cache-Control: max-age=60\r\nVary: User-Agent\r\n"),
+            ok("Cache-Control: max-age=60\r\n"),
+        ])
+        .await;
+        let options = FetchOptions::new().cache(HttpCache::new());
+        assert!(title(&base, &options).await.is_ok());
+        assert!(title(&base, &options).await.is_ok());
+        // Another User-Agent misses the kept response and reaches the server.
+        assert!(title(&base, &options.clone().user_agent("feedbot/2.0")).await.is_ok());
+        assert!(title(&base, &options.clone().user_agent("feedbot/3.0")).await.is_ok());
+
+        let base = serve(vec![ok("Cache-Control: max-age=60\r\nVary: *\r\n")]).await;
+        assert!(title(&base, &options).await.is_ok());
+        assert!(title(&base, &options).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_stale_responses_are_revalidated() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("http-cache.jsonl");
+        let base = serve(vec![ok("ETag: \"v2\"\r\n"), "HTTP/1.1 304 Not Modified\r\n\r\n".to_string()]).await;
+
+        let options = FetchOptions::new().cache(HttpCache::open(&path).await.unwrap());
+        assert_eq!(title(&base, &options).await.unwrap().as_deref(), Some("Cached"));
+        // Reopened from disk, the kept body answers the server's 304.
+        let options = FetchOptions::new().cache(HttpCache::open(&path).await.unwrap());
+        assert_eq!(title(&base, &options).await.unwrap().as_deref(), Some("Cached"));
+        // The body lives beside the index, not in it.
+        assert!(!std::fs::read_to_string(&path).unwrap().contains("Cached"));
+        assert_eq!(std::fs::read_dir(dir.path().join("http-cache.jsonl.bodies")).unwrap().count(), 1);
+    }
+}
@@ -0,0 +1,401 @@
+use serde::Serialize;
+
+use crate::channel::ChannelInfo;
+#[cfg(feature = "dates")]
+use crate::date::{parse_date, DateTime};
+use crate::enclosure::{Enclosure, MimeType};
+use crate::html::{first_image, html_to_text};
+#[cfg(feature = "sanitize")]
+use crate::html::sanitize_html;
+use crate::media::Media;
+use crate::ns;
+use crate::person::Person;
+use crate::rss_parser::{GradualRssItem, XmlNode};
+use crate::tag::TagId;
+use crate::url::{absolute, url_text};
+
+/// An item's `<guid>`. Unless `isPermaLink="false"`, the value is also a URL
+/// to the item.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Guid {
+    pub value: String,
+    pub is_permalink: bool,
+}
+
+/// A `<category>`, with the `domain` naming the taxonomy it belongs to.
+/// Atom categories map their `term` to `name` and `scheme` to `domain`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Category {
+    pub name: String,
+    pub domain: Option<String>,
+}
+
+/// The standard RSS 2.0 `<item>` elements, ready to use as `RssParser::<RssItem, _>`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RssItem {
+    pub title: Option<String>,
+    pub link: Option<String>,
+    pub description: Option<String>,
+    /// The full body from `<content:encoded>`, usually HTML.
+    pub content: Option<String>,
+    pub author: Option<Person>,
+    pub categories: Vec<Category>,
+    pub comments: Option<String>,
+    pub enclosure: Option<Enclosure>,
+    pub guid: Option<Guid>,
+    pub pub_date: Option<String>,
+    pub source: Option<String>,
+    pub rights: Option<String>,
+    /// The item's `xml:lang` or `dc:language`, else the channel's `<language>`.
+    pub language: Option<String>,
+    /// `<itunes:image href>`, the episode's artwork.
+    pub image: Option<String>,
+    pub media: Media,
+}
+
+impl RssItem {
+    /// A stable key for recognising the item across fetches: the guid, then
+    /// the link, then a hash of the title (or description).
+    pub fn identity(&self) -> Option<String> {
+        if let Some(guid) = &self.guid {
+            return Some(guid.value.clone());
+        }
+        if let Some(link) = &self.link {
+            return Some(link.clone());
+        }
+        let text = self.title.as_ref().or(self.description.as_ref())?;
+        Some(format!("hash:{:016x}", fnv1a(text.as_bytes())))
+    }
+}
+
+impl RssItem {
+    /// A representative image for the item: a Media RSS thumbnail or image,
+    /// else the `itunes:image`, an image enclosure, or the first `<img>` of
+    /// the content or description.
+    pub fn thumbnail(&self) -> Option<String> {
+        if let Some(url) = self.media.thumbnail().or(self.image.as_deref()) {
+            return Some(url.to_string());
+        }
+        if let Some(enclosure) = &self.enclosure
+            && enclosure.mime_type.as_ref().is_some_and(MimeType::is_image)
+        {
+            return Some(enclosure.url.clone());
+        }
+        let html = [self.content.as_deref(), self.description.as_deref()];
+        let image = html.into_iter().flatten().find_map(first_image)?;
+        Some(absolute(self.link.as_deref(), image))
+    }
+
+    /// The description (else the content) as plain text of at most
+    /// `max_chars` characters, for notifications and search indexes. See `html_to_text`.
+    pub fn summary_text(&self, max_chars: usize) -> Option<String> {
+        let html = self.description.as_deref().or(self.content.as_deref())?;
+        Some(html_to_text(html, Some(max_chars)))
+    }
+}
+
+#[cfg(feature = "sanitize")]
+impl RssItem {
+    /// `description` reduced to markup that is safe to render, see `sanitize_html`.
+    pub fn description_sanitized(&self) -> Option<String> {
+        self.description.as_deref().map(sanitize_html)
+    }
+
+    /// `content` reduced to markup that is safe to render, see `sanitize_html`.
+    pub fn content_sanitized(&self) -> Option<String> {
+        self.content.as_deref().map(sanitize_html)
+    }
+}
+
+// FNV-1a, chosen over `DefaultHasher` because identities are persisted and
+// must not change between Rust releases.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(feature = "dates")]
+impl RssItem {
+    /// `pub_date` parsed by `parse_date`, if it holds a recognisable date.
+    pub fn parsed_pub_date(&self) -> Option<DateTime> {
+        self.pub_date.as_deref().and_then(parse_date)
+    }
+}
+
+impl GradualRssItem for RssItem {
+    fn init() -> Self {
+        RssItem::default()
+    }
+
+    fn populate(&mut self, node: XmlNode) {
+        // An `<item>` nested in this one, as some extensions have, and what
+        // it holds describe something else.
+        if node.path.split('/').any(|tag| tag.eq_ignore_ascii_case("item")) {
+            return;
+        }
+        self.media.populate(&node);
+        if node.path.is_empty() && self.language.is_none() {
+            self.language = node.lang.clone();
+        }
+        if node.extension_name(ns::CONTENT, "content") == Some("encoded") {
+            self.content = node.into_text();
+            return;
+        }
+        if node.extension_name(ns::ITUNES, "itunes") == Some("image") && !node.path.contains('/') {
+            self.image = node.attr("href").map(|href| absolute(node.base.as_deref(), href.to_string()));
+            return;
+        }
+
+        // Dublin Core fills in for the core elements many RSS 1.0 and WordPress
+        // feeds leave out; the core element wins when both are present.
+        match node.extension_name(ns::DUBLIN_CORE, "dc") {
+            Some("creator") if self.author.is_none() => self.author = node.into_text().as_deref().map(Person::named),
+            Some("date") if self.pub_date.is_none() => self.pub_date = node.into_text(),
+            Some("subject") => self.categories.extend(node.into_text().map(|name| Category { name, domain: None })),
+            Some("rights") => self.rights = node.into_text(),
+            Some("language") => self.language = node.into_text(),
+            Some(_) => {}
+            None => self.populate_core(node),
+        }
+    }
+
+    fn with_channel(&mut self, channel: &ChannelInfo) {
+        if self.author.is_none() {
+            self.author = channel.author.clone();
+        }
+        if self.language.is_none() {
+            self.language = channel.language.clone();
+        }
+        let base = channel.link.as_deref();
+        self.link = self.link.take().map(|link| absolute(base, link));
+        if let Some(enclosure) = &mut self.enclosure {
+            enclosure.url = absolute(base, std::mem::take(&mut enclosure.url));
+        }
+    }
+}
+
+impl RssItem {
+    fn populate_core(&mut self, node: XmlNode) {
+        match node.tag_id {
+            TagId::Title => self.title = node.into_text(),
+            TagId::Link => self.link = url_text(node),
+            TagId::Description => self.description = node.into_text(),
+            TagId::Author => self.author = node.into_text().as_deref().and_then(Person::parse),
+            TagId::Category => {
+                let domain = node.attr("domain").map(String::from);
+                self.categories.extend(node.into_text().map(|name| Category { name, domain }));
+            }
+            TagId::Comments => self.comments = node.into_text(),
+            TagId::Enclosure => self.enclosure = Enclosure::from_node(&node),
+            TagId::Guid => {
+                let is_permalink = !node.attr("isPermaLink").is_some_and(|value| value.trim().eq_ignore_ascii_case("false"));
+                self.guid = node.into_text().map(|value| Guid { value, is_permalink });
+            }
+            TagId::PubDate => self.pub_date = node.into_text(),
+            TagId::Source => self.source = node.into_text(),
+            // RSS 1.0 identifies items by their `rdf:about` URI.
+            TagId::Item if self.guid.is_none() => {
+                self.guid = node.attr("rdf:about").map(|about| Guid {
+                    value: about.to_string(),
+                    is_permalink: false,
+                })
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    use super::*;
+    use crate::RssParser;
+    use std::io::Cursor;
+
+    const FEED: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0">
+    <channel>
+        <title>Podcast</title>
+        <item>
+            <title>Episode 1</title>
+            <link>https://example.com/ep1</link>
+            <description><![CDATA[<p>Show notes</p>]]></description>
+            <content:encoded xmlns:content="http://purl.org/rss/1.0/modules/content/"><![CDATA[<p>Full show notes</p>]]></content:encoded>
+            <author>host@example.com (The Host)</author>
+            <category domain="https://example.com/topics">Tech</category>
+            <category>News</category>
+            <comments>https://example.com/ep1#comments</comments>
+            <enclosure url="https://example.com/ep1.mp3" length="12345" type="audio/mpeg"/>
+            <guid>https://example.com/ep1</guid>
+            <pubDate>Mon, 01 Jan 2024 00:00:00 GMT</pubDate>
+            <source url="https://other.example.com/rss">Other Feed</source>
+        </item>
+    </channel>
+</rss>"#;
+
+    #[tokio::test]
+    async fn test_builtin_item_fields() {
+        let mut parser = RssParser::<RssItem, _>::new(Cursor::new(FEED.as_bytes())).await.unwrap();
+        let item = parser.next().await.unwrap().unwrap();
+
+        assert_eq!(item.title.as_deref(), Some("Episode 1"));
+        assert_eq!(item.link.as_deref(), Some("https://example.com/ep1"));
+        assert_eq!(item.description.as_deref(), Some("<p>Show notes</p>"));
+        assert_eq!(item.content.as_deref(), Some("<p>Full show notes</p>"));
+        let author = item.author.as_ref().unwrap();
+        assert_eq!(author.name.as_deref(), Some("The Host"));
+        assert_eq!(author.email.as_deref(), Some("host@example.com"));
+        assert_eq!(
+            item.categories,
+            vec![
+                Category {
+                    name: "Tech".to_string(),
+                    domain: Some("https://example.com/topics".to_string())
+                },
+                Category {
+                    name: "News".to_string(),
+                    domain: None
+                },
+            ]
+        );
+        assert_eq!(item.comments.as_deref(), Some("https://example.com/ep1#comments"));
+        let enclosure = item.enclosure.as_ref().unwrap();
+        assert_eq!(enclosure.url, "https://example.com/ep1.mp3");
+        assert_eq!(enclosure.length, Some(12345));
+        assert_eq!(enclosure.mime_type.as_ref().map(|mime| mime.essence()).as_deref(), Some("audio/mpeg"));
+        assert_eq!(
+            item.guid,
+            Some(Guid {
+                value: "https://example.com/ep1".to_string(),
+                is_permalink: true
+            })
+        );
+        assert_eq!(item.pub_date.as_deref(), Some("Mon, 01 Jan 2024 00:00:00 GMT"));
+        assert_eq!(item.source.as_deref(), Some("Other Feed"));
+
+        assert!(parser.next().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_builtin_item_missing_fields() {
+        let feed = "<rss><channel><item><title>Only a title</title></item></channel></rss>";
+        let mut parser = RssParser::<RssItem, _>::new(Cursor::new(feed.as_bytes())).await.unwrap();
+        let item = parser.next().await.unwrap().unwrap();
+
+        assert_eq!(
+            item,
+            RssItem {
+                title: Some("Only a title".to_string()),
+                ..RssItem::default()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_guid_and_identity() {
+        let feed = r#"<rss><channel>
+            <item><guid isPermaLink="false">post-42</guid><link>https://example.com/42</link></item>
+            <item><link>https://example.com/43</link></item>
+            <item><title>Only a title</title></item>
+            <item></item>
+        </channel></rss>"#;
+        let mut parser = RssParser::<RssItem, _>::new(Cursor::new(feed.as_bytes())).await.unwrap();
+
+        let first = parser.next().await.unwrap().unwrap();
+        assert!(!first.guid.as_ref().unwrap().is_permalink);
+        assert_eq!(first.identity().as_deref(), Some("post-42"));
+        assert_eq!(parser.next().await.unwrap().unwrap().identity().as_deref(), Some("https://example.com/43"));
+        assert_eq!(
+            parser.next().await.unwrap().unwrap().identity().as_deref(),
+            Some("hash:fe0d2683cdae7342")
+        );
+        assert!(parser.next().await.unwrap().unwrap().identity().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_dublin_core_fallbacks() {
+        let feed = r#"<rss xmlns:dc="http://purl.org/dc/elements/1.1/"><channel>
+            <item><dc:creator>Writer</dc:creator><author>core@example.com</author><dc:subject>Extra</dc:subject></item>
+            <item><author>core@example.com</author><dc:creator>Ignored</dc:creator><dc:date>2024-03-01</dc:date></item>
+        </channel></rss>"#;
+        let mut parser = RssParser::<RssItem, _>::new(Cursor::new(feed.as_bytes())).await.unwrap();
+
+        let first = parser.next().await.unwrap().unwrap();
+        assert_eq!(first.author.unwrap().email.as_deref(), Some("core@example.com"));
+        assert_eq!(first.categories[0].name, "Extra");
+
+        let second = parser.next().await.unwrap().unwrap();
+        assert_eq!(second.author.unwrap().email.as_deref(), Some("core@example.com"));
+        assert_eq!(second.pub_date.as_deref(), Some("2024-03-01"));
+    }
+
+    #[tokio::test]
+    async fn test_language() {
+        let feed = r#"<rss xmlns:dc="http://purl.org/dc/elements/1.1/"><channel><language>en</language>
+            <item xml:lang="fr"><title>Bonjour</title></item>
+            <item><dc:language>de</dc:language></item>
+            <item><title>Hello</title></item>
+        </channel></rss>"#;
+        let mut parser = RssParser::<RssItem, _>::new(Cursor::new(feed.as_bytes())).await.unwrap();
+        for expected in ["fr", "de", "en"] {
+            assert_eq!(parser.next().await.unwrap().unwrap().language.as_deref(), Some(expected));
+        }
+    }
+
+    #[cfg(feature = "dates")]
+    #[tokio::test]
+    async fn test_parsed_pub_date() {
+        let mut parser = RssParser::<RssItem, _>::new(Cursor::new(FEED.as_bytes())).await.unwrap();
+        let item = parser.next().await.unwrap().unwrap();
+        assert_eq!(item.parsed_pub_date().map(|date| date.timestamp()), Some(1_704_067_200));
+        assert!(RssItem::default().parsed_pub_date().is_none());
+    }
+
+    #[cfg(feature = "urls")]
+    #[tokio::test]
+    async fn test_relative_urls() {
+        let feed = r#"<rss><channel><link>https://example.com/blog/</link>
+            <item><link>posts/1</link><enclosure url="../media/1.mp3"/></item>
+            <item xml:base="https://cdn.example.net/2024/"><link>/about</link><enclosure url="2.mp3"/></item>
+            <item><link>https://other.example/x</link></item>
+        </channel></rss>"#;
+        let mut parser = RssParser::<RssItem, _>::new(Cursor::new(feed.as_bytes())).await.unwrap();
+
+        let first = parser.next().await.unwrap().unwrap();
+        assert_eq!(first.link.as_deref(), Some("https://example.com/blog/posts/1"));
+        assert_eq!(first.enclosure.unwrap().url, "https://example.com/media/1.mp3");
+
+        let second = parser.next().await.unwrap().unwrap();
+        assert_eq!(second.link.as_deref(), Some("https://cdn.example.net/about"));
+        assert_eq!(second.enclosure.unwrap().url, "https://cdn.example.net/2024/2.mp3");
+
+        assert_eq!(parser.next().await.unwrap().unwrap().link.as_deref(), Some("https://other.example/x"));
+    }
+
+    #[tokio::test]
+    async fn test_thumbnail() {
+        let feed = r#"<rss xmlns:media="http://search.yahoo.com/mrss/" xmlns:itunes="http://www.itunes.com/dtds/podcast-1.0.dtd"><channel>
+            <item>
+                <media:content url="https://example.com/video.mp4" type="video/mp4">
+                    <media:thumbnail url="https://example.com/still.jpg"/>
+                </media:content>
+                <itunes:image href="https://example.com/art.png"/>
+            </item>
+            <item><itunes:image href="https://example.com/art.png"/></item>
+            <item><enclosure url="https://example.com/photo.jpg" type="image/jpeg"/></item>
+            <item>
+                <enclosure url="https://example.com/ep.mp3" type="audio/mpeg"/>
+                <description><![CDATA[<img src="https://t.example/pixel.gif" width="1" height="1"><p><img src='https://example.com/inline.png'></p>]]></description>
+            </item>
+            <item><description>No images</description></item>
+        </channel></rss>"#;
+        let mut parser = RssParser::<RssItem, _>::new(Cursor::new(feed.as_bytes())).await.unwrap();
+        let mut thumbnails = Vec::new();
+        while let Some(item) = parser.next().await.unwrap() {
+            thumbnails.push(item.thumbnail());
+        }
+        let expected = ["https://example.com/still.jpg", "https://example.com/art.png", "https://example.com/photo.jpg", "https://example.com/inline.png"];
+        assert_eq!(thumbnails[..4], expected.map(|url| Some(url.to_string())));
+        assert_eq!(thumbnails[4], None);
+    }
+}
@@ -0,0 +1,292 @@
+use std::collections::VecDeque;
+
+use serde_json::Value;
+use tokio::fs::File;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::error::RssError;
+use crate::rss_parser::{GradualRssItem, XmlNode};
+
+const JSON_KEY_ITEMS: &[u8] = b"items";
+const READ_CHUNK: usize = 8 * 1024;
+
+/// Streams items out of a JSON Feed (https://jsonfeed.org) document.
+///
+/// Each entry of the top-level `items` array is handed to `populate` one key
+/// at a time: strings, numbers and booleans become the node value, arrays
+/// produce one node per element and objects (`authors`, `attachments`) arrive
+/// as a node whose attributes are the object's scalar fields.
+pub struct JsonFeedParser<T, R> {
+    reader: R,
+    scanner: ItemScanner,
+    ready: VecDeque<Vec<u8>>,
+    eof: bool,
+    _phantom: std::marker::PhantomData<T>,
+}
+
+impl<T: GradualRssItem, R: AsyncRead + Unpin> JsonFeedParser<T, R> {
+    pub async fn new(input: R) -> std::io::Result<Self> {
+        let obj = JsonFeedParser {
+            reader: input,
+            scanner: ItemScanner::default(),
+            ready: VecDeque::new(),
+            eof: false,
+            _phantom: std::marker::PhantomData,
+        };
+        Ok(obj)
+    }
+
+    pub async fn next(&mut self) -> Result<Option<T>, RssError> {
+        let mut chunk = vec![0u8; READ_CHUNK];
+        loop {
+            if let Some(raw) = self.ready.pop_front() {
+                let value: Value = serde_json::from_slice(&raw)?;
+                return build_item::<T>(&value).finish().map(Some).map_err(RssError::InvalidItem);
+            }
+            if self.eof {
+                return Ok(None);
+            }
+
+            let read = self.reader.read(&mut chunk).await?;
+            if read == 0 {
+                self.eof = true;
+                if self.scanner.in_item() {
+                    return Err(RssError::UnexpectedEof);
+                }
+                continue;
+            }
+            self.scanner.feed(&chunk[..read], &mut self.ready);
+        }
+    }
+}
+
+impl<T: GradualRssItem> JsonFeedParser<T, File> {
+    pub async fn from_file(path: &str) -> std::io::Result<Self> {
+        let file = File::open(path).await?;
+        Self::new(file).await
+    }
+}
+
+fn build_item<T: GradualRssItem>(value: &Value) -> T {
+    let mut item = T::init();
+    if let Value::Object(fields) = value {
+        for (key, value) in fields {
+            populate_value(&mut item, &key.to_lowercase(), value);
+        }
+    }
+    item
+}
+
+fn populate_value<T: GradualRssItem>(item: &mut T, tag: &str, value: &Value) {
+    match value {
+        Value::Null => {}
+        Value::Array(values) => {
+            for value in values {
+                populate_value(item, tag, value);
+            }
+        }
+        Value::Object(fields) => {
+            let mut node = XmlNode::new(tag.to_string());
+            node.attributes = fields
+                .iter()
+                .filter_map(|(key, value)| scalar_to_string(value).map(|value| (key.to_lowercase(), value)))
+                .collect();
+            item.populate(node);
+        }
+        scalar => {
+            let mut node = XmlNode::new(tag.to_string());
+            node.value = scalar_to_string(scalar);
+            item.populate(node);
+        }
+    }
+}
+
+fn scalar_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+// Splits the raw bytes of every object in the top-level `items` array out of
+// the document as it arrives, without holding on to the rest of the feed.
+#[derive(Default)]
+struct ItemScanner {
+    depth: u32,
+    in_string: bool,
+    escaped: bool,
+    expect_key: bool,
+    key: Vec<u8>,
+    last_key: Vec<u8>,
+    in_items: bool,
+    item: Option<Vec<u8>>,
+}
+
+impl ItemScanner {
+    fn in_item(&self) -> bool {
+        self.item.is_some()
+    }
+
+    fn feed(&mut self, bytes: &[u8], ready: &mut VecDeque<Vec<u8>>) {
+        for &byte in bytes {
+            if let Some(item) = self.item.as_mut() {
+                item.push(byte);
+            }
+
+            if self.in_string {
+                if self.escaped {
+                    self.escaped = false;
+                } else if byte == b'\\' {
+                    self.escaped = true;
+                } else if byte == b'"' {
+                    self.in_string = false;
+                    if self.depth == 1 && self.expect_key {
+                        self.last_key = std::mem::take(&mut self.key);
+                    }
+                } else if self.depth == 1 && self.expect_key {
+                    self.key.push(byte);
+                }
+                continue;
+            }
+
+            match byte {
+                b'"' => {
+                    self.in_string = true;
+                    self.key.clear();
+                }
+                b':' if self.depth == 1 => self.expect_key = false,
+                b',' if self.depth == 1 => self.expect_key = true,
+                b'{' | b'[' => {
+                    if self.depth == 1 && byte == b'[' && self.last_key == JSON_KEY_ITEMS {
+                        self.in_items = true;
+                    }
+                    if self.in_items && self.depth == 2 && byte == b'{' {
+                        self.item = Some(vec![byte]);
+                    }
+                    self.depth += 1;
+                    if self.depth == 1 {
+                        self.expect_key = true;
+                    }
+                }
+                b'}' | b']' => {
+                    self.depth = self.depth.saturating_sub(1);
+                    if self.in_items
+                        && self.depth == 2
+                        && let Some(item) = self.item.take()
+                    {
+                        ready.push_back(item);
+                    }
+                    if self.depth == 1 {
+                        self.in_items = false;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[derive(Debug, Default)]
+    struct JsonItem {
+        id: Option<String>,
+        title: Option<String>,
+        content_html: Option<String>,
+        tags: Vec<String>,
+        authors: Vec<String>,
+        attachment: Option<(String, String)>,
+    }
+
+    impl GradualRssItem for JsonItem {
+        fn init() -> Self {
+            JsonItem::default()
+        }
+
+        fn populate(&mut self, node: XmlNode) {
+            match node.tag.as_str() {
+                "id" => self.id = node.value,
+                "title" => self.title = node.value,
+                "content_html" => self.content_html = node.value,
+                "tags" => self.tags.extend(node.value),
+                "authors" => self.authors.extend(node.attr("name").map(String::from)),
+                "attachments" => {
+                    if let (Some(url), Some(size)) = (node.attr("url"), node.attr("size_in_bytes")) {
+                        self.attachment = Some((url.to_string(), size.to_string()));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    const FEED: &str = r#"{
+        "version": "https://jsonfeed.org/version/1.1",
+        "title": "My \"Example\" Feed {not an item}",
+        "items": [
+            {
+                "id": "2",
+                "title": "Braces } and [brackets] in \"strings\"",
+                "content_html": "<p>Hello, world!</p>",
+                "tags": ["rust", "feeds"],
+                "authors": [{ "name": "Ada", "url": "https://example.org/ada" }],
+                "attachments": [{ "url": "https://example.org/a.mp3", "mime_type": "audio/mpeg", "size_in_bytes": 1024 }]
+            },
+            { "id": "1", "content_text": "Plain" }
+        ],
+        "home_page_url": "https://example.org/"
+    }"#;
+
+    #[tokio::test]
+    async fn test_json_feed_items() {
+        let mut parser = JsonFeedParser::<JsonItem, _>::new(Cursor::new(FEED.as_bytes())).await.unwrap();
+
+        let first = parser.next().await.unwrap().unwrap();
+        assert_eq!(first.id.as_deref(), Some("2"));
+        assert_eq!(first.title.as_deref(), Some("Braces } and [brackets] in \"strings\""));
+        assert_eq!(first.content_html.as_deref(), Some("<p>Hello, world!</p>"));
+        assert_eq!(first.tags, vec!["rust".to_string(), "feeds".to_string()]);
+        assert_eq!(first.authors, vec!["Ada".to_string()]);
+        assert_eq!(
+            first.attachment,
+            Some(("https://example.org/a.mp3".to_string(), "1024".to_string()))
+        );
+
+        let second = parser.next().await.unwrap().unwrap();
+        assert_eq!(second.id.as_deref(), Some("1"));
+        assert!(second.title.is_none());
+
+        assert!(parser.next().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_json_feed_split_across_reads() {
+        let (client, mut server) = tokio::io::duplex(7);
+        let writer = tokio::spawn(async move {
+            use tokio::io::AsyncWriteExt;
+            server.write_all(FEED.as_bytes()).await.unwrap();
+        });
+
+        let mut parser = JsonFeedParser::<JsonItem, _>::new(client).await.unwrap();
+        let mut ids = Vec::new();
+        while let Some(item) = parser.next().await.unwrap() {
+            ids.extend(item.id);
+        }
+        writer.await.unwrap();
+        assert_eq!(ids, vec!["2".to_string(), "1".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_json_feed_truncated() {
+        let truncated = r#"{"items": [{"id": "1"}, {"id": "2", "title": "cut"#;
+        let mut parser = JsonFeedParser::<JsonItem, _>::new(Cursor::new(truncated.as_bytes())).await.unwrap();
+
+        assert!(parser.next().await.unwrap().is_some());
+        assert!(matches!(parser.next().await, Err(RssError::UnexpectedEof)));
+    }
+}
@@ -1,4 +1,173 @@
+#[cfg(feature = "tokio")]
+mod aggregate;
+mod async_read;
+mod atom;
+#[cfg(feature = "blocking")]
+mod blocking;
+mod builder;
+#[cfg(feature = "tokio")]
+mod byte_stream;
+mod capture;
+mod channel;
+mod checkpoint;
+#[cfg(feature = "tokio")]
+mod convert;
+#[cfg(feature = "dates")]
+mod date;
+#[cfg(feature = "tokio")]
+mod dedup;
+#[cfg(feature = "tokio")]
+mod diff;
+mod discover;
+mod dynamic;
+mod enclosure;
+mod encoding;
+mod error;
+#[cfg(feature = "tokio")]
+mod feed;
+mod feed_item;
+#[cfg(feature = "tokio")]
+pub mod filters;
+#[cfg(feature = "http")]
+mod hmac;
+mod html;
+#[cfg(feature = "http")]
+mod http;
+#[cfg(feature = "http")]
+mod http_cache;
+mod item;
+#[cfg(feature = "tokio")]
+mod json_feed;
+mod limits;
+mod media;
+mod metrics;
+mod node_ref;
+pub mod ns;
+#[cfg(feature = "tokio")]
+mod opml;
+#[cfg(feature = "http")]
+mod paging;
+mod person;
+#[cfg(feature = "tokio")]
+mod poll_read;
+#[cfg(feature = "http")]
+mod poller;
+#[cfg(feature = "tokio")]
+mod range;
+#[cfg(feature = "tokio")]
+mod read_ahead;
 mod rss_parser;
+mod serde_item;
+#[cfg(feature = "tokio")]
+mod sitemap;
+mod stats;
+#[cfg(feature = "tokio")]
+mod store;
+#[cfg(feature = "tokio")]
+mod stream;
+mod tag;
+#[cfg(feature = "tokio")]
+mod throttle;
+#[cfg(feature = "tokio")]
+mod tls;
+mod url;
+#[cfg(feature = "validate")]
+mod validate;
+#[cfg(feature = "http")]
+mod websub;
+#[cfg(feature = "tokio")]
+mod writer;
 
+#[cfg(feature = "tokio")]
+pub use crate::aggregate::{Aggregator, FeedId};
+#[cfg(all(feature = "dates", feature = "tokio"))]
+pub use crate::aggregate::{Dated, Timeline};
+pub use crate::atom::{AtomEntry, Link};
+#[cfg(feature = "blocking")]
+pub use crate::blocking::SyncRssParser;
+pub use crate::builder::{ParseMode, RssParserBuilder};
+#[cfg(feature = "tokio")]
+pub use crate::byte_stream::ByteStream;
+pub use crate::channel::{ChannelImage, ChannelInfo};
+pub use crate::checkpoint::Checkpoint;
+#[cfg(feature = "tokio")]
+pub use crate::convert::{convert, TargetFormat, Transform};
+#[cfg(feature = "dates")]
+pub use crate::date::{parse_date, DateTime};
+#[cfg(feature = "tokio")]
+pub use crate::dedup::{Dedup, DedupStore, Identify, LruSet, RssStreamExt, SeenSet};
+#[cfg(feature = "tokio")]
+pub use crate::diff::{Diff, ItemChange, Snapshot};
+#[cfg(feature = "http")]
+pub use crate::discover::{discover, discover_with};
+pub use crate::discover::{discover_links, DiscoveredFeed};
+pub use crate::enclosure::{Enclosure, MimeType};
+pub use crate::encoding::Encoding;
+pub use crate::error::{Location, RssError};
+#[cfg(feature = "tokio")]
+pub use crate::feed::FeedFormat;
+#[cfg(feature = "tokio")]
+pub use crate::feed::FeedParser;
+pub use crate::feed_item::FeedItem;
+#[cfg(feature = "tokio")]
+pub use crate::filters::{FilterItems, Filterable};
+#[cfg(feature = "http")]
+pub use crate::http::{Auth, FetchOptions, FetchResult, HttpBody, HttpError, RetryPolicy};
+#[cfg(feature = "http")]
+pub use crate::http_cache::HttpCache;
+pub use crate::html::html_to_text;
+#[cfg(feature = "sanitize")]
+pub use crate::html::sanitize_html;
+pub use crate::item::{Category, Guid, RssItem};
+#[cfg(feature = "tokio")]
+pub use crate::json_feed::JsonFeedParser;
+pub use crate::media::{Media, MediaContent, MediaGroup, MediaThumbnail};
+pub use crate::metrics::{Metrics, NoopMetrics};
+#[cfg(feature = "prometheus")]
+pub use crate::metrics::PrometheusMetrics;
+#[cfg(feature = "tokio")]
+pub use crate::opml::{Opml, Outline};
+#[cfg(feature = "http")]
+pub use crate::paging::Pages;
+pub use crate::person::Person;
+#[cfg(feature = "tokio")]
+pub use crate::poll_read::PollRead;
+#[cfg(feature = "http")]
+pub use crate::poller::Poller;
+#[cfg(all(feature = "dates", feature = "tokio"))]
+pub use crate::range::{Since, Until};
+#[cfg(feature = "tokio")]
+pub use crate::range::TakeItems;
+#[cfg(feature = "tokio")]
+pub use crate::read_ahead::ReadAhead;
+#[cfg(feature = "tokio")]
+pub use crate::rss_parser::parse_all;
 pub use crate::rss_parser::RssParser;
-pub use crate::rss_parser::GradualRssItem;
\ No newline at end of file
+pub use crate::rss_parser::GradualRssChannel;
+pub use crate::rss_parser::GradualRssItem;
+pub use crate::rss_parser::XmlNode;
+pub use crate::node_ref::XmlNodeRef;
+pub use crate::serde_item::Deserialized;
+#[cfg(feature = "tokio")]
+pub use crate::sitemap::{SitemapEntry, SitemapParser};
+pub use crate::stats::ParserStats;
+#[cfg(feature = "tokio")]
+pub use crate::store::{FileStore, MemoryStore, SeenStore};
+#[cfg(feature = "tokio")]
+pub use crate::stream::RssStream;
+pub use crate::tag::TagId;
+#[cfg(feature = "tokio")]
+pub use crate::throttle::Throttle;
+#[cfg(feature = "tokio")]
+pub use crate::tls::{BoxedConnection, Connection, TlsConnector, TlsFuture};
+#[cfg(feature = "urls")]
+pub use crate::url::resolve_url;
+#[cfg(feature = "validate")]
+pub use crate::validate::{validate, Issue, Severity, ValidationReport};
+#[cfg(feature = "http")]
+pub use crate::websub::{CallbackRequest, CallbackResponse, Notification, WebSubSubscriber};
+#[cfg(feature = "tokio")]
+pub use crate::writer::{write_ndjson, AtomWriter, JsonFeedWriter, RssWriter};
+
+#[cfg(feature = "derive")]
+pub use rss_parser_derive::GradualRssItem;
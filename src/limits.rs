@@ -0,0 +1,127 @@
+#[cfg(feature = "tokio")]
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll, ready};
+use std::time::Duration;
+
+#[cfg(feature = "tokio")]
+use tokio::time::Sleep;
+
+use crate::async_read::{AsyncRead, ReadBuf};
+
+/// Why a `Guard` refused to read on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Trip {
+    ElementSize,
+    FeedSize,
+    Stalled,
+}
+
+/// Enforces the limits that have to be checked while reading rather than per
+/// event:
+///
+/// - a span from the last `checkpoint` (the position of the event being read),
+///   so a single multi-gigabyte text node or tag can't be buffered;
+/// - the total number of bytes read;
+/// - how long a read may wait for the input without receiving anything.
+pub(crate) struct Guard<R> {
+    inner: R,
+    max_span: Option<u64>,
+    max_total: Option<u64>,
+    stall_timeout: Option<Duration>,
+    #[cfg(feature = "tokio")]
+    stall: Option<Pin<Box<Sleep>>>,
+    read: u64,
+    checkpoint: u64,
+    tripped: Option<Trip>,
+}
+
+impl<R> Guard<R> {
+    pub(crate) fn new(inner: R, max_span: Option<u64>, max_total: Option<u64>, stall_timeout: Option<Duration>) -> Self {
+        Guard {
+            inner,
+            max_span,
+            max_total,
+            stall_timeout,
+            #[cfg(feature = "tokio")]
+            stall: None,
+            read: 0,
+            checkpoint: 0,
+            tripped: None,
+        }
+    }
+
+    pub(crate) fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub(crate) fn into_inner(self) -> R {
+        self.inner
+    }
+
+    pub(crate) fn checkpoint(&mut self, position: u64) {
+        self.checkpoint = position;
+    }
+
+    /// The limit that failed the last read, if any.
+    pub(crate) fn tripped(&self) -> Option<Trip> {
+        self.tripped
+    }
+
+    fn trip(&mut self, trip: Trip) -> Poll<std::io::Result<()>> {
+        self.tripped = Some(trip);
+        let kind = match trip {
+            Trip::Stalled => std::io::ErrorKind::TimedOut,
+            Trip::ElementSize | Trip::FeedSize => std::io::ErrorKind::InvalidData,
+        };
+        Poll::Ready(Err(std::io::Error::new(kind, format!("{:?} limit exceeded", trip))))
+    }
+
+    // Ready once a read has been pending for `timeout`.
+    #[cfg(feature = "tokio")]
+    fn poll_stall(&mut self, timeout: Duration, cx: &mut Context<'_>) -> Poll<()> {
+        let stall = self.stall.get_or_insert_with(|| Box::pin(tokio::time::sleep(timeout)));
+        ready!(stall.as_mut().poll(cx));
+        self.stall = None;
+        Poll::Ready(())
+    }
+
+    // Without a timer nothing wakes a stalled read; blocking inputs never stall.
+    #[cfg(not(feature = "tokio"))]
+    fn poll_stall(&mut self, _timeout: Duration, _cx: &mut Context<'_>) -> Poll<()> {
+        Poll::Pending
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for Guard<R> {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        if this.max_span.is_some_and(|max| this.read - this.checkpoint > max) {
+            return this.trip(Trip::ElementSize);
+        }
+        if this.max_total.is_some_and(|max| this.read > max) {
+            return this.trip(Trip::FeedSize);
+        }
+
+        let filled = buf.filled().len();
+        match Pin::new(&mut this.inner).poll_read(cx, buf) {
+            Poll::Ready(result) => {
+                #[cfg(feature = "tokio")]
+                {
+                    this.stall = None;
+                }
+                result?;
+            }
+            Poll::Pending => {
+                let Some(timeout) = this.stall_timeout else {
+                    return Poll::Pending;
+                };
+                ready!(this.poll_stall(timeout, cx));
+                return this.trip(Trip::Stalled);
+            }
+        }
+        this.read += (buf.filled().len() - filled) as u64;
+        Poll::Ready(Ok(()))
+    }
+}
+
@@ -0,0 +1,224 @@
+use serde::Serialize;
+
+use crate::enclosure::MimeType;
+use crate::ns;
+use crate::rss_parser::XmlNode;
+
+/// `<media:thumbnail>`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MediaThumbnail {
+    pub url: String,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// Position in the media the thumbnail was taken from, as written (NTP time).
+    pub time: Option<String>,
+}
+
+/// `<media:content>`, with the title, description and thumbnails nested in it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct MediaContent {
+    pub url: Option<String>,
+    pub mime_type: Option<MimeType>,
+    /// `image`, `audio`, `video`, `document` or `executable`.
+    pub medium: Option<String>,
+    pub file_size: Option<u64>,
+    /// Length in seconds.
+    pub duration: Option<u64>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub lang: Option<String>,
+    pub is_default: bool,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub thumbnails: Vec<MediaThumbnail>,
+}
+
+/// `<media:group>`: alternative renditions of the same media.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct MediaGroup {
+    pub contents: Vec<MediaContent>,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub thumbnails: Vec<MediaThumbnail>,
+}
+
+/// The Media RSS (https://www.rssboard.org/media-rss) elements of one item.
+///
+/// Feed every node of the item to `populate`; anything outside the Media RSS
+/// namespace is ignored. Elements are delivered innermost first, so titles and
+/// thumbnails nested in `<media:content>` or `<media:group>` are held back
+/// until their parent arrives.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Media {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub thumbnails: Vec<MediaThumbnail>,
+    pub contents: Vec<MediaContent>,
+    pub groups: Vec<MediaGroup>,
+    #[serde(skip)]
+    pending_content: MediaContent,
+    #[serde(skip)]
+    pending_group: MediaGroup,
+}
+
+impl Media {
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none()
+            && self.description.is_none()
+            && self.thumbnails.is_empty()
+            && self.contents.is_empty()
+            && self.groups.is_empty()
+    }
+
+    /// The URL of the first thumbnail, whether of the item, a group or a
+    /// content, else of the first content that is an image.
+    pub fn thumbnail(&self) -> Option<&str> {
+        let contents = || self.contents.iter().chain(self.groups.iter().flat_map(|group| &group.contents));
+        let thumbnails = self
+            .thumbnails
+            .iter()
+            .chain(self.groups.iter().flat_map(|group| &group.thumbnails))
+            .chain(contents().flat_map(|content| &content.thumbnails));
+        if let Some(thumbnail) = thumbnails.into_iter().next() {
+            return Some(&thumbnail.url);
+        }
+        contents()
+            .find(|content| content.medium.as_deref() == Some("image") || content.mime_type.as_ref().is_some_and(MimeType::is_image))
+            .and_then(|content| content.url.as_deref())
+    }
+
+    pub fn populate(&mut self, node: &XmlNode) {
+        let Some(local_name) = node.extension_name(ns::MEDIA, "media") else {
+            return;
+        };
+        // Compared by local name, so undeclared prefixes work too.
+        let parent = node.parent().map_or("", |parent| parent.rsplit(':').next().unwrap_or(parent));
+
+        match local_name {
+            "title" | "description" | "thumbnail" => {
+                let (title, description, thumbnails) = match parent {
+                    "content" => (
+                        &mut self.pending_content.title,
+                        &mut self.pending_content.description,
+                        &mut self.pending_content.thumbnails,
+                    ),
+                    "group" => (
+                        &mut self.pending_group.title,
+                        &mut self.pending_group.description,
+                        &mut self.pending_group.thumbnails,
+                    ),
+                    _ => (&mut self.title, &mut self.description, &mut self.thumbnails),
+                };
+                match local_name {
+                    "title" => *title = node.clone().into_text(),
+                    "description" => *description = node.clone().into_text(),
+                    _ => thumbnails.extend(thumbnail(node)),
+                }
+            }
+            "content" => {
+                let mut content = std::mem::take(&mut self.pending_content);
+                content.url = node.attr("url").map(String::from);
+                content.mime_type = node.attr("type").and_then(MimeType::parse);
+                content.medium = node.attr("medium").map(String::from);
+                content.file_size = parsed(node, "fileSize");
+                content.duration = parsed(node, "duration");
+                content.width = parsed(node, "width");
+                content.height = parsed(node, "height");
+                content.lang = node.attr("lang").map(String::from);
+                content.is_default = node.attr("isDefault") == Some("true");
+                match parent {
+                    "group" => self.pending_group.contents.push(content),
+                    _ => self.contents.push(content),
+                }
+            }
+            "group" => self.groups.push(std::mem::take(&mut self.pending_group)),
+            _ => {}
+        }
+    }
+}
+
+fn thumbnail(node: &XmlNode) -> Option<MediaThumbnail> {
+    Some(MediaThumbnail {
+        url: node.attr("url")?.to_string(),
+        width: parsed(node, "width"),
+        height: parsed(node, "height"),
+        time: node.attr("time").map(String::from),
+    })
+}
+
+fn parsed<N: std::str::FromStr>(node: &XmlNode, attr: &str) -> Option<N> {
+    node.attr(attr).and_then(|value| value.trim().parse().ok())
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    use super::*;
+    use crate::{GradualRssItem, RssParser};
+    use std::io::Cursor;
+
+    #[derive(Default)]
+    struct MediaItem(Media);
+
+    impl GradualRssItem for MediaItem {
+        fn init() -> Self {
+            MediaItem::default()
+        }
+
+        fn populate(&mut self, node: XmlNode) {
+            self.0.populate(&node);
+        }
+    }
+
+    const FEED: &str = r#"<rss xmlns:m="http://search.yahoo.com/mrss/"><channel>
+        <item>
+            <title>Clip</title>
+            <m:title>Clip title</m:title>
+            <m:thumbnail url="https://example.com/item.jpg" width="120" height="90"/>
+            <m:content url="https://example.com/a.mp4" type="video/mp4" medium="video" fileSize="1000" duration="61" width="640" height="360" isDefault="true">
+                <m:title>Rendition A</m:title>
+                <m:thumbnail url="https://example.com/a.jpg"/>
+            </m:content>
+            <m:group>
+                <m:title>Alternatives</m:title>
+                <m:content url="https://example.com/lo.mp4" type="video/mp4"/>
+                <m:content url="https://example.com/hi.mp4" type="video/mp4">
+                    <m:description>High quality</m:description>
+                </m:content>
+                <m:thumbnail url="https://example.com/group.jpg" time="12:05:01.123"/>
+            </m:group>
+        </item>
+        <item><media:content url="https://example.com/undeclared.mp3"/></item>
+    </channel></rss>"#;
+
+    #[tokio::test]
+    async fn test_media_rss() {
+        let mut parser = RssParser::<MediaItem, _>::new(Cursor::new(FEED.as_bytes())).await.unwrap();
+        let media = parser.next().await.unwrap().unwrap().0;
+
+        assert_eq!(media.title.as_deref(), Some("Clip title"));
+        assert_eq!(media.thumbnails.len(), 1);
+        assert_eq!(media.thumbnails[0].width, Some(120));
+
+        let content = &media.contents[0];
+        assert_eq!(media.contents.len(), 1);
+        assert_eq!(content.url.as_deref(), Some("https://example.com/a.mp4"));
+        assert!(content.mime_type.as_ref().unwrap().is_video());
+        assert_eq!(content.medium.as_deref(), Some("video"));
+        assert_eq!((content.file_size, content.duration), (Some(1000), Some(61)));
+        assert_eq!((content.width, content.height), (Some(640), Some(360)));
+        assert!(content.is_default);
+        assert_eq!(content.title.as_deref(), Some("Rendition A"));
+        assert_eq!(content.thumbnails[0].url, "https://example.com/a.jpg");
+
+        let group = &media.groups[0];
+        assert_eq!(group.title.as_deref(), Some("Alternatives"));
+        assert_eq!(group.contents.len(), 2);
+        assert!(group.contents[0].description.is_none());
+        assert_eq!(group.contents[1].description.as_deref(), Some("High quality"));
+        assert_eq!(group.thumbnails[0].time.as_deref(), Some("12:05:01.123"));
+
+        let second = parser.next().await.unwrap().unwrap().0;
+        assert_eq!(second.contents[0].url.as_deref(), Some("https://example.com/undeclared.mp3"));
+        assert!(second.title.is_none());
+    }
+}
@@ -0,0 +1,137 @@
+use std::fmt;
+use std::sync::Arc;
+#[cfg(feature = "prometheus")]
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::error::RssError;
+
+/// Receives counts from a parser (`RssParserBuilder::metrics`) or a poller
+/// (`Poller::metrics`). Every method does nothing unless overridden.
+pub trait Metrics: Send + Sync {
+    /// An item was built, `latency` after its start tag was read.
+    fn item_parsed(&self, latency: Duration) {
+        let _ = latency;
+    }
+
+    /// Parsing failed or skipped a malformed item.
+    fn parse_error(&self, error: &RssError) {
+        let _ = error;
+    }
+
+    fn fetch_failed(&self, url: &str, error: &RssError) {
+        let _ = (url, error);
+    }
+}
+
+/// Metrics that go nowhere.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopMetrics;
+
+impl Metrics for NoopMetrics {}
+
+// Lets the options holding it stay `Debug`.
+#[derive(Clone)]
+pub(crate) struct MetricsHandle(pub(crate) Arc<dyn Metrics>);
+
+impl fmt::Debug for MetricsHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Metrics")
+    }
+}
+
+/// Upper bounds of the latency histogram's buckets, in seconds.
+#[cfg(feature = "prometheus")]
+const BUCKETS: [f64; 9] = [0.0001, 0.0005, 0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0];
+
+/// Counts everything in memory and renders it in the Prometheus text
+/// exposition format, for a `/metrics` endpoint.
+#[cfg(feature = "prometheus")]
+#[derive(Debug, Default)]
+pub struct PrometheusMetrics {
+    items_parsed: AtomicU64,
+    parse_errors: AtomicU64,
+    fetch_failures: AtomicU64,
+    // Not cumulative; `render` adds them up.
+    buckets: [AtomicU64; BUCKETS.len()],
+    latency_nanos: AtomicU64,
+}
+
+#[cfg(feature = "prometheus")]
+impl PrometheusMetrics {
+    pub fn new() -> Self {
+        PrometheusMetrics::default()
+    }
+
+    pub fn render(&self) -> String {
+        let count = self.items_parsed.load(Ordering::Relaxed);
+        let mut out = String::new();
+        for (name, help, value) in [
+            ("rss_parser_items_parsed_total", "Items parsed.", count),
+            ("rss_parser_parse_errors_total", "Parse errors, including skipped items.", self.parse_errors.load(Ordering::Relaxed)),
+            ("rss_parser_fetch_failures_total", "Failed feed fetches.", self.fetch_failures.load(Ordering::Relaxed)),
+        ] {
+            out.push_str(&format!("# HELP {} {}\n# TYPE {} counter\n{} {}\n", name, help, name, name, value));
+        }
+
+        let name = "rss_parser_item_parse_seconds";
+        out.push_str(&format!("# HELP {} Time from an item's start tag to the finished item.\n# TYPE {} histogram\n", name, name));
+        let mut cumulative = 0;
+        for (bound, bucket) in BUCKETS.iter().zip(&self.buckets) {
+            cumulative += bucket.load(Ordering::Relaxed);
+            out.push_str(&format!("{}_bucket{{le=\"{}\"}} {}\n", name, bound, cumulative));
+        }
+        let seconds = self.latency_nanos.load(Ordering::Relaxed) as f64 / 1e9;
+        out.push_str(&format!("{}_bucket{{le=\"+Inf\"}} {}\n{}_sum {}\n{}_count {}\n", name, count, name, seconds, name, count));
+        out
+    }
+}
+
+#[cfg(feature = "prometheus")]
+impl Metrics for PrometheusMetrics {
+    fn item_parsed(&self, latency: Duration) {
+        self.items_parsed.fetch_add(1, Ordering::Relaxed);
+        let nanos = u64::try_from(latency.as_nanos()).unwrap_or(u64::MAX);
+        self.latency_nanos.fetch_add(nanos, Ordering::Relaxed);
+        let seconds = latency.as_secs_f64();
+        if let Some(bucket) = BUCKETS.iter().position(|bound| seconds <= *bound) {
+            self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn parse_error(&self, _error: &RssError) {
+        self.parse_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn fetch_failed(&self, _url: &str, _error: &RssError) {
+        self.fetch_failures.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(all(test, feature = "prometheus", feature = "tokio"))]
+mod tests {
+    use super::*;
+    use crate::{ParseMode, RssItem, RssParser};
+    use std::io::Cursor;
+
+    #[tokio::test]
+    async fn test_prometheus_metrics() {
+        let metrics = Arc::new(PrometheusMetrics::new());
+        let feed = "<rss><channel><item><title>1</wrong></item><item><title>2</title></item><item/></channel></rss>";
+        let mut parser: RssParser<RssItem, _> = RssParser::builder()
+            .mode(ParseMode::Lenient)
+            .metrics(metrics.clone())
+            .build(Cursor::new(feed.as_bytes()))
+            .await
+            .unwrap();
+        while parser.next().await.unwrap().is_some() {}
+
+        let text = metrics.render();
+        assert!(text.contains("rss_parser_items_parsed_total 2\n"));
+        assert!(text.contains("rss_parser_parse_errors_total 1\n"));
+        assert!(text.contains("rss_parser_fetch_failures_total 0\n"));
+        assert!(text.contains("# TYPE rss_parser_item_parse_seconds histogram\n"));
+        assert!(text.contains("rss_parser_item_parse_seconds_bucket{le=\"+Inf\"} 2\n"));
+        assert!(text.contains("rss_parser_item_parse_seconds_count 2\n"));
+    }
+}
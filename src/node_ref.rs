@@ -0,0 +1,121 @@
+use std::borrow::Cow;
+
+use crate::builder::ParserOptions;
+use crate::rss_parser::XmlNode;
+use crate::tag::TagId;
+
+/// An element lent to `GradualRssItem::populate_ref`, borrowing from the
+/// parser instead of owning its strings. The text is only copied when
+/// whitespace collapsing has to rewrite it.
+#[derive(Debug, Clone)]
+pub struct XmlNodeRef<'a> {
+    pub tag: &'a str,
+    pub tag_id: &'a TagId,
+    pub local_name: &'a str,
+    pub namespace: Option<&'a str>,
+    /// As `XmlNode::path`.
+    pub path: &'a str,
+    pub value: Option<Cow<'a, str>>,
+    pub cdata: Option<Cow<'a, str>>,
+    pub attributes: &'a [(String, String)],
+    pub base: Option<&'a str>,
+    pub lang: Option<&'a str>,
+    pub inner_xml: Option<&'a str>,
+}
+
+impl<'a> XmlNodeRef<'a> {
+    pub(crate) fn new(node: &'a XmlNode, path: &'a str, options: &ParserOptions) -> Self {
+        let (mut value, mut cdata) = (node.value.as_deref().map(Cow::Borrowed), node.cdata.as_deref().map(Cow::Borrowed));
+        if !node.preserves_space() {
+            if options.collapse_whitespace {
+                value = value.map(collapse);
+            }
+            if options.trim_whitespace || options.collapse_whitespace {
+                value = value.and_then(trim);
+                cdata = cdata.and_then(trim);
+            }
+        }
+        XmlNodeRef {
+            tag: &node.tag,
+            tag_id: &node.tag_id,
+            local_name: &node.local_name,
+            namespace: node.namespace.as_deref(),
+            path,
+            value,
+            cdata,
+            attributes: &node.attributes,
+            base: node.base.as_deref(),
+            lang: node.lang.as_deref(),
+            inner_xml: node.inner_xml.as_deref(),
+        }
+    }
+
+    /// See `XmlNode::tag_is`.
+    pub fn tag_is(&self, tag: &str) -> bool {
+        self.tag.eq_ignore_ascii_case(tag)
+    }
+
+    /// See `XmlNode::path_is`.
+    pub fn path_is(&self, path: &str) -> bool {
+        self.path.eq_ignore_ascii_case(path)
+    }
+
+    /// See `XmlNode::is`.
+    pub fn is(&self, namespace: &str, local_name: &str) -> bool {
+        self.namespace == Some(namespace) && self.local_name.eq_ignore_ascii_case(local_name)
+    }
+
+    /// See `XmlNode::attr`.
+    pub fn attr(&self, name: &str) -> Option<&'a str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// See `XmlNode::into_text`.
+    pub fn text(&self) -> Option<&str> {
+        match (&self.value, &self.cdata) {
+            (Some(value), Some(cdata)) if value.trim().is_empty() => Some(cdata),
+            (value, cdata) => value.as_deref().or(cdata.as_deref()),
+        }
+    }
+
+    /// Copies the element into an `XmlNode`, as `populate` gets it.
+    pub fn into_owned(self) -> XmlNode {
+        let mut node = XmlNode::new(self.tag.to_string());
+        node.namespace = self.namespace.map(str::to_string);
+        node.path = self.path.to_string();
+        node.value = self.value.map(Cow::into_owned);
+        node.cdata = self.cdata.map(Cow::into_owned);
+        node.attributes = self.attributes.to_vec();
+        node.base = self.base.map(str::to_string);
+        node.lang = self.lang.map(str::to_string);
+        node.inner_xml = self.inner_xml.map(str::to_string);
+        node
+    }
+}
+
+// As `split_whitespace` joined with single spaces, borrowing when that only
+// trims the text.
+fn collapse(text: Cow<'_, str>) -> Cow<'_, str> {
+    let mut previous = ' ';
+    let collapsed = text.trim().chars().all(|c| {
+        let ok = !c.is_whitespace() || (c == ' ' && previous != ' ');
+        previous = c;
+        ok
+    });
+    match (collapsed, text) {
+        (true, Cow::Borrowed(text)) => Cow::Borrowed(text.trim()),
+        (_, text) => Cow::Owned(text.split_whitespace().collect::<Vec<_>>().join(" ")),
+    }
+}
+
+fn trim(text: Cow<'_, str>) -> Option<Cow<'_, str>> {
+    let text = match text {
+        Cow::Borrowed(text) => Cow::Borrowed(text.trim()),
+        Cow::Owned(text) if text.trim().len() == text.len() => Cow::Owned(text),
+        Cow::Owned(text) => Cow::Owned(text.trim().to_string()),
+    };
+    (!text.is_empty()).then_some(text)
+}
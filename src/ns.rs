@@ -0,0 +1,12 @@
+//! Namespace URIs of the feed formats and extensions seen in the wild, for use
+//! with `XmlNode::is`.
+
+pub const ATOM: &str = "http://www.w3.org/2005/Atom";
+pub const RSS_1_0: &str = "http://purl.org/rss/1.0/";
+pub const RDF: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+pub const DUBLIN_CORE: &str = "http://purl.org/dc/elements/1.1/";
+pub const CONTENT: &str = "http://purl.org/rss/1.0/modules/content/";
+pub const MEDIA: &str = "http://search.yahoo.com/mrss/";
+pub const ITUNES: &str = "http://www.itunes.com/dtds/podcast-1.0.dtd";
+pub const SYNDICATION: &str = "http://purl.org/rss/1.0/modules/syndication/";
+pub const XML: &str = "http://www.w3.org/XML/1998/namespace";
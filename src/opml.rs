@@ -0,0 +1,284 @@
+use quick_xml::escape::resolve_html5_entity;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::reader::Reader;
+use tokio::fs::File;
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+
+use crate::encoding::DecodingReader;
+use crate::error::RssError;
+use crate::writer::Markup;
+
+/// An OPML subscription list, the format feed readers import and export.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Opml {
+    pub title: Option<String>,
+    pub date_created: Option<String>,
+    pub date_modified: Option<String>,
+    pub owner_name: Option<String>,
+    pub owner_email: Option<String>,
+    pub outlines: Vec<Outline>,
+}
+
+/// An `<outline>`: a feed when it has an `xml_url`, otherwise a folder of
+/// the outlines in `children`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Outline {
+    pub text: Option<String>,
+    pub title: Option<String>,
+    /// The `type` attribute, `rss` for feeds.
+    pub kind: Option<String>,
+    pub xml_url: Option<String>,
+    pub html_url: Option<String>,
+    pub children: Vec<Outline>,
+}
+
+impl Outline {
+    /// A subscription to the feed at `xml_url`.
+    pub fn feed(title: &str, xml_url: &str) -> Self {
+        Outline {
+            text: Some(title.to_string()),
+            kind: Some("rss".to_string()),
+            xml_url: Some(xml_url.to_string()),
+            ..Default::default()
+        }
+    }
+
+    pub fn folder(title: &str, children: Vec<Outline>) -> Self {
+        Outline {
+            text: Some(title.to_string()),
+            children,
+            ..Default::default()
+        }
+    }
+
+    pub fn is_feed(&self) -> bool {
+        self.xml_url.is_some()
+    }
+
+    /// `text`, or `title` for lists that only set that.
+    pub fn name(&self) -> Option<&str> {
+        self.text.as_deref().or(self.title.as_deref())
+    }
+
+    fn from_start(start: &BytesStart) -> Self {
+        let mut outline = Outline::default();
+        for attr in start.attributes().flatten() {
+            let Ok(value) = attr.unescape_value() else { continue };
+            let value = Some(value.into_owned());
+            match String::from_utf8_lossy(attr.key.as_ref()).to_lowercase().as_str() {
+                "text" => outline.text = value,
+                "title" => outline.title = value,
+                "type" => outline.kind = value,
+                "xmlurl" => outline.xml_url = value,
+                "htmlurl" => outline.html_url = value,
+                _ => {}
+            }
+        }
+        outline
+    }
+
+    fn write(&self, markup: &mut Markup) {
+        let attrs = [
+            ("text", self.text.as_deref().or(self.title.as_deref()).or(Some(""))),
+            ("title", self.title.as_deref()),
+            ("type", self.kind.as_deref()),
+            ("xmlUrl", self.xml_url.as_deref()),
+            ("htmlUrl", self.html_url.as_deref()),
+        ];
+        if self.children.is_empty() {
+            markup.empty("outline", &attrs);
+        } else {
+            markup.open("outline", &attrs);
+            for child in &self.children {
+                child.write(markup);
+            }
+            markup.close("outline");
+        }
+    }
+}
+
+impl Opml {
+    /// Reads an OPML document, building the outline tree as the elements
+    /// stream in.
+    pub async fn parse<R: AsyncRead + Unpin>(input: R) -> Result<Self, RssError> {
+        let mut reader = Reader::from_reader(BufReader::new(DecodingReader::new(input)));
+        let mut buf = Vec::new();
+        let mut opml = Opml::default();
+        // Open outlines, innermost last.
+        let mut open: Vec<Outline> = Vec::new();
+        let mut root_seen = false;
+        let mut head_field: Option<String> = None;
+        let mut text = String::new();
+        loop {
+            let position = reader.buffer_position();
+            let event = reader
+                .read_event_into_async(&mut buf)
+                .await
+                .map_err(|err| RssError::from_xml(err, position))?;
+            match event {
+                Event::Start(ref start) | Event::Empty(ref start) => {
+                    let tag = String::from_utf8_lossy(start.local_name().as_ref()).to_lowercase();
+                    if !root_seen {
+                        if tag != "opml" {
+                            return Err(RssError::UnsupportedFormat(tag));
+                        }
+                        root_seen = true;
+                    } else if tag == "outline" {
+                        let outline = Outline::from_start(start);
+                        if matches!(event, Event::Start(_)) {
+                            open.push(outline);
+                        } else {
+                            attach(&mut opml, &mut open, outline);
+                        }
+                    } else if open.is_empty() && matches!(event, Event::Start(_)) {
+                        head_field = Some(tag);
+                        text.clear();
+                    }
+                }
+                Event::End(ref end) => {
+                    let tag = String::from_utf8_lossy(end.local_name().as_ref()).to_lowercase();
+                    if tag == "outline" {
+                        if let Some(outline) = open.pop() {
+                            attach(&mut opml, &mut open, outline);
+                        }
+                    } else if head_field.as_deref() == Some(tag.as_str()) {
+                        let value = Some(text.trim().to_string()).filter(|value| !value.is_empty());
+                        match tag.as_str() {
+                            "title" => opml.title = value,
+                            "datecreated" => opml.date_created = value,
+                            "datemodified" => opml.date_modified = value,
+                            "ownername" => opml.owner_name = value,
+                            "owneremail" => opml.owner_email = value,
+                            _ => {}
+                        }
+                        head_field = None;
+                    }
+                }
+                Event::Text(content) if head_field.is_some() => text.push_str(&content.decode()?),
+                Event::CData(content) if head_field.is_some() => text.push_str(&content.decode()?),
+                Event::GeneralRef(entity) if head_field.is_some() => {
+                    let name = entity.decode()?;
+                    match entity.resolve_char_ref().ok().flatten() {
+                        Some(c) => text.push(c),
+                        None => text.push_str(resolve_html5_entity(&name).unwrap_or_default()),
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+            buf.clear();
+        }
+        if !root_seen {
+            return Err(RssError::UnexpectedEof);
+        }
+        // Outlines left open by a truncated document are kept.
+        while let Some(outline) = open.pop() {
+            attach(&mut opml, &mut open, outline);
+        }
+        Ok(opml)
+    }
+
+    pub async fn from_file(path: &str) -> Result<Self, RssError> {
+        Self::parse(File::open(path).await?).await
+    }
+
+    /// Every feed in the list, folders flattened, in document order.
+    pub fn feeds(&self) -> Vec<&Outline> {
+        fn collect<'a>(outlines: &'a [Outline], feeds: &mut Vec<&'a Outline>) {
+            for outline in outlines {
+                if outline.is_feed() {
+                    feeds.push(outline);
+                }
+                collect(&outline.children, feeds);
+            }
+        }
+        let mut feeds = Vec::new();
+        collect(&self.outlines, &mut feeds);
+        feeds
+    }
+
+    /// Writes the list as an OPML 2.0 document and returns the writer.
+    pub async fn write<W: AsyncWrite + Unpin>(&self, mut out: W) -> std::io::Result<W> {
+        let mut markup = Markup::default();
+        markup.out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        markup.open("opml", &[("version", Some("2.0"))]);
+        markup.open("head", &[]);
+        markup.text("title", self.title.as_deref());
+        markup.text("dateCreated", self.date_created.as_deref());
+        markup.text("dateModified", self.date_modified.as_deref());
+        markup.text("ownerName", self.owner_name.as_deref());
+        markup.text("ownerEmail", self.owner_email.as_deref());
+        markup.close("head");
+        markup.open("body", &[]);
+        for outline in &self.outlines {
+            outline.write(&mut markup);
+        }
+        markup.close("body");
+        markup.close("opml");
+        out.write_all(markup.out.as_bytes()).await?;
+        out.flush().await?;
+        Ok(out)
+    }
+}
+
+// Adds a finished outline to its parent, or to the body at the top level.
+fn attach(opml: &mut Opml, open: &mut [Outline], outline: Outline) {
+    match open.last_mut() {
+        Some(parent) => parent.children.push(outline),
+        None => opml.outlines.push(outline),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    const OPML: &str = r#"<?xml version="1.0"?>
+<opml version="2.0">
+  <head><title>My &amp; feeds</title><ownerName>Jo</ownerName></head>
+  <body>
+    <outline text="Tech">
+      <outline text="Rust Blog" type="rss" xmlUrl="https://blog.rust-lang.org/feed.xml" htmlUrl="https://blog.rust-lang.org/"/>
+      <outline title="Nested">
+        <outline text="Deep" type="rss" xmlUrl="https://example.com/deep.xml"/>
+      </outline>
+    </outline>
+    <outline text="Top" type="rss" xmlUrl="https://example.com/top.xml?a=1&amp;b=2"/>
+  </body>
+</opml>"#;
+
+    #[tokio::test]
+    async fn test_parse_opml() {
+        let opml = Opml::parse(Cursor::new(OPML)).await.unwrap();
+        assert_eq!(opml.title.as_deref(), Some("My & feeds"));
+        assert_eq!(opml.owner_name.as_deref(), Some("Jo"));
+        assert_eq!(opml.outlines.len(), 2);
+        let tech = &opml.outlines[0];
+        assert!(!tech.is_feed());
+        assert_eq!(tech.children[1].name(), Some("Nested"));
+        let urls: Vec<_> = opml.feeds().iter().map(|feed| feed.xml_url.as_deref().unwrap()).collect();
+        assert_eq!(
+            urls,
+            vec!["https://blog.rust-lang.org/feed.xml", "https://example.com/deep.xml", "https://example.com/top.xml?a=1&b=2"]
+        );
+
+        let err = Opml::parse(Cursor::new("<rss/>")).await.unwrap_err();
+        assert!(matches!(err, RssError::UnsupportedFormat(root) if root == "rss"));
+    }
+
+    #[tokio::test]
+    async fn test_opml_round_trip() {
+        let opml = Opml {
+            title: Some("Subscriptions".to_string()),
+            outlines: vec![
+                Outline::folder("News", vec![Outline::feed("A <feed>", "http://a.example/rss")]),
+                Outline::feed("B", "http://b.example/atom"),
+            ],
+            ..Default::default()
+        };
+        let written = opml.write(Vec::new()).await.unwrap();
+        assert!(String::from_utf8_lossy(&written).contains("xmlUrl=\"http://a.example/rss\""));
+        assert_eq!(Opml::parse(Cursor::new(written)).await.unwrap(), opml);
+    }
+}
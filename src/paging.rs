@@ -0,0 +1,196 @@
+use std::collections::HashSet;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use tokio_stream::Stream;
+
+use crate::channel::ChannelInfo;
+use crate::error::RssError;
+use crate::feed::FeedParser;
+use crate::feed_item::FeedItem;
+use crate::http::{fetch, FetchOptions, HttpBody};
+use crate::url::resolve_url;
+
+type PageFuture = Pin<Box<dyn Future<Output = (Walk, Option<Result<FeedItem, RssError>>)> + Send>>;
+
+/// The items of a feed and of every page before it, following RFC 5005
+/// `rel="next"` (paged feeds) or `rel="prev-archive"` (archived feeds) links
+/// from one fetch to the next.
+///
+/// Pages are read in link order; a page linking back to one already read ends
+/// the walk. A failed fetch is yielded as an error and ends it too.
+pub struct Pages {
+    walk: Option<Walk>,
+    pending: Option<PageFuture>,
+}
+
+struct Walk {
+    url: String,
+    next_url: Option<String>,
+    parser: Option<FeedParser<HttpBody>>,
+    visited: HashSet<String>,
+    pages_left: Option<usize>,
+    options: FetchOptions,
+}
+
+impl Pages {
+    pub fn new(url: &str) -> Self {
+        Pages {
+            walk: Some(Walk {
+                url: url.to_string(),
+                next_url: Some(url.to_string()),
+                parser: None,
+                visited: HashSet::new(),
+                pages_left: None,
+                options: FetchOptions::default(),
+            }),
+            pending: None,
+        }
+    }
+
+    /// Fetches every page with `options`, e.g. with a `TlsConnector` for
+    /// `https://` feeds. Their validators are left out, since they belong to
+    /// one page.
+    pub fn options(mut self, options: FetchOptions) -> Self {
+        if let Some(walk) = &mut self.walk {
+            walk.options = FetchOptions {
+                etag: None,
+                last_modified: None,
+                ..options
+            };
+        }
+        self
+    }
+
+    /// Stops after `max` pages, the first included.
+    pub fn max_pages(mut self, max: usize) -> Self {
+        if let Some(walk) = &mut self.walk {
+            walk.pages_left = Some(max);
+        }
+        self
+    }
+}
+
+impl Walk {
+    async fn next(mut self) -> (Self, Option<Result<FeedItem, RssError>>) {
+        loop {
+            if let Some(parser) = &mut self.parser {
+                match parser.next().await {
+                    Ok(Some(item)) => return (self, Some(Ok(item))),
+                    Ok(None) => {
+                        let channel = parser.channel::<ChannelInfo>().await;
+                        self.parser = None;
+                        match channel {
+                            Ok(channel) => {
+                                let link = channel.next_page.or(channel.prev_archive);
+                                self.next_url = link.and_then(|link| resolve_url(&self.url, &link));
+                            }
+                            Err(err) => return (self, Some(Err(err))),
+                        }
+                    }
+                    // The page goes on after an item it couldn't read or build.
+                    Err(err) if !err.is_fatal() => return (self, Some(Err(err))),
+                    Err(err) => {
+                        self.parser = None;
+                        return (self, Some(Err(err)));
+                    }
+                }
+                continue;
+            }
+
+            let Some(url) = self.next_url.take() else {
+                return (self, None);
+            };
+            if self.pages_left == Some(0) || !self.visited.insert(url.clone()) {
+                return (self, None);
+            }
+            self.pages_left = self.pages_left.map(|left| left - 1);
+            let parser = match fetch(&url, &self.options).await {
+                Ok((_, body)) => FeedParser::detect(body).await,
+                Err(err) => Err(err),
+            };
+            match parser {
+                Ok(parser) => {
+                    self.url = url;
+                    self.parser = Some(parser);
+                }
+                Err(err) => return (self, Some(Err(err))),
+            }
+        }
+    }
+}
+
+// The walk is only ever moved into and out of the boxed future, never pinned in place.
+impl Unpin for Pages {}
+
+impl Stream for Pages {
+    type Item = Result<FeedItem, RssError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        if this.pending.is_none() {
+            let Some(walk) = this.walk.take() else {
+                return Poll::Ready(None);
+            };
+            this.pending = Some(Box::pin(walk.next()));
+        }
+
+        let pending = this.pending.as_mut().expect("pending future was just set");
+        match pending.as_mut().poll(cx) {
+            Poll::Ready((walk, item)) => {
+                this.pending = None;
+                this.walk = Some(walk);
+                Poll::Ready(item)
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::tests::serve;
+    use tokio_stream::StreamExt;
+
+    fn ok(body: &str) -> String {
+        format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}", body.len(), body)
+    }
+
+    #[tokio::test]
+    async fn test_follows_paging_links() {
+        let first = r#"<feed xmlns="http://www.w3.org/2005/Atom">
+  <link rel="next" href="/feed?page=2"/>
+  <entry><title>Three</title></entry>
+</feed>"#;
+        let second = r#"<rss xmlns:atom="http://www.w3.org/2005/Atom"><channel>
+  <item><title>Two</title></item>
+  <item><title>One</title></item>
+  <atom:link rel="prev-archive" href="feed"/>
+</channel></rss>"#;
+        let base = serve(vec![ok(first), ok(second)]).await;
+
+        let titles: Vec<_> = Pages::new(&format!("{}/feed", base))
+            .map(|item| item.unwrap().title.unwrap())
+            .collect()
+            .await;
+        // The second page links back to the first, which isn't fetched again.
+        assert_eq!(titles, vec!["Three", "Two", "One"]);
+
+        let base = serve(vec![ok(first)]).await;
+        let items: Vec<_> = Pages::new(&format!("{}/feed", base)).max_pages(1).collect().await;
+        assert_eq!(items.len(), 1);
+
+        // `https://` pages go through the connector in the options.
+        let base = serve(vec![ok(first), ok(second)]).await.replace("http://", "https://");
+        let tls = crate::tls::tests::Passthrough::default();
+        let titles: Vec<_> = Pages::new(&format!("{}/feed", base))
+            .options(FetchOptions::new().tls(tls))
+            .map(|item| item.unwrap().title.unwrap())
+            .collect()
+            .await;
+        assert_eq!(titles, vec!["Three", "Two", "One"]);
+    }
+}
@@ -0,0 +1,83 @@
+use serde::Serialize;
+
+/// An author or contributor, from RSS `<author>`, `dc:creator` or Atom `<author>`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Person {
+    pub name: Option<String>,
+    pub email: Option<String>,
+    pub uri: Option<String>,
+}
+
+impl Person {
+    /// Reads the free-form RSS author conventions: `email (Name)`,
+    /// `Name <email>`, a bare email address or a bare name.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        if text.is_empty() {
+            return None;
+        }
+        let non_empty = |part: &str| Some(part.trim().to_string()).filter(|part| !part.is_empty());
+
+        if let Some((email, rest)) = text.split_once('(')
+            && let Some(name) = rest.strip_suffix(')')
+        {
+            return Some(Person {
+                name: non_empty(name),
+                email: non_empty(email),
+                uri: None,
+            });
+        }
+        if let Some((name, rest)) = text.split_once('<')
+            && let Some(email) = rest.strip_suffix('>')
+        {
+            return Some(Person {
+                name: non_empty(name).map(|name| name.trim_matches('"').to_string()),
+                email: non_empty(email),
+                uri: None,
+            });
+        }
+
+        let person = match text.contains('@') && !text.contains(char::is_whitespace) {
+            true => Person {
+                email: Some(text.to_string()),
+                ..Person::default()
+            },
+            false => Person::named(text),
+        };
+        Some(person)
+    }
+
+    pub fn named(name: &str) -> Self {
+        Person {
+            name: Some(name.trim().to_string()),
+            ..Person::default()
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.name.is_none() && self.email.is_none() && self.uri.is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn person(name: Option<&str>, email: Option<&str>) -> Option<Person> {
+        Some(Person {
+            name: name.map(String::from),
+            email: email.map(String::from),
+            uri: None,
+        })
+    }
+
+    #[test]
+    fn test_rss_author_conventions() {
+        assert_eq!(Person::parse("host@example.com (The Host)"), person(Some("The Host"), Some("host@example.com")));
+        assert_eq!(Person::parse("\"Jo Bloggs\" <jo@example.com>"), person(Some("Jo Bloggs"), Some("jo@example.com")));
+        assert_eq!(Person::parse(" jo@example.com "), person(None, Some("jo@example.com")));
+        assert_eq!(Person::parse("Jo Bloggs"), person(Some("Jo Bloggs"), None));
+        assert_eq!(Person::parse("(Nameless)"), person(Some("Nameless"), None));
+        assert_eq!(Person::parse("   "), None);
+    }
+}
@@ -0,0 +1,67 @@
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use tokio::io::{AsyncRead, ReadBuf};
+
+/// Turns a `poll_read` function into an input for the parser, to read from
+/// `futures::io::AsyncRead` types (async-std, smol and others) without a
+/// compatibility crate:
+///
+/// `PollRead::new(move |cx, buf| Pin::new(&mut file).poll_read(cx, buf))`
+///
+/// The parser only uses Tokio's `AsyncRead` trait, not its runtime, so it
+/// runs on any executor; only `http`, `ReadAhead`, `stall_timeout` and the
+/// `from_file`/`from_tcp` constructors need a Tokio runtime.
+pub struct PollRead<F> {
+    poll_read: F,
+}
+
+impl<F> PollRead<F>
+where
+    F: FnMut(&mut Context<'_>, &mut [u8]) -> Poll<io::Result<usize>>,
+{
+    pub fn new(poll_read: F) -> Self {
+        PollRead { poll_read }
+    }
+}
+
+impl<F> AsyncRead for PollRead<F>
+where
+    F: FnMut(&mut Context<'_>, &mut [u8]) -> Poll<io::Result<usize>> + Unpin,
+{
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let read = std::task::ready!((this.poll_read)(cx, buf.initialize_unfilled()))?;
+        buf.advance(read);
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RssItem, RssParser};
+
+    #[tokio::test]
+    async fn test_poll_read() {
+        let feed = b"<rss><channel><item><title>Polled</title></item></channel></rss>";
+        let mut position = 0;
+        let mut ready = false;
+        // Hands out three bytes at a time, each after one `Pending`.
+        let input = PollRead::new(move |cx: &mut Context<'_>, buf: &mut [u8]| {
+            ready = !ready;
+            if !ready {
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            let count = buf.len().min(3).min(feed.len() - position);
+            buf[..count].copy_from_slice(&feed[position..position + count]);
+            position += count;
+            Poll::Ready(Ok(count))
+        });
+        let mut parser = RssParser::<RssItem, _>::new(input).await.unwrap();
+        assert_eq!(parser.next().await.unwrap().unwrap().title.as_deref(), Some("Polled"));
+        assert!(parser.next().await.unwrap().is_none());
+    }
+}
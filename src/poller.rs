@@ -0,0 +1,519 @@
+use std::collections::BTreeSet;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tokio::sync::{mpsc, Mutex};
+use tokio_stream::wrappers::ReceiverStream;
+
+use crate::aggregate::FeedId;
+use crate::channel::ChannelInfo;
+use crate::dedup::Identify;
+use crate::error::RssError;
+use crate::metrics::{Metrics, NoopMetrics};
+use crate::http::{FetchOptions, FetchResult, HttpBody, HttpError, RetryPolicy};
+use crate::item::fnv1a;
+use crate::ns;
+use crate::rss_parser::{GradualRssChannel, GradualRssItem, RssParser, XmlNode};
+use crate::store::{MemoryStore, SeenStore};
+use crate::tls::TlsConnector;
+
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(15 * 60);
+const DEFAULT_BUFFER: usize = 64;
+
+/// Fetches a list of feeds over and over and delivers the items it hasn't
+/// delivered before, as `(FeedId, Result<T, RssError>)`.
+///
+/// Each feed is fetched every `interval` (or its own, from `add_every`), or
+/// less often if its channel's `<ttl>` or `sy:updatePeriod`, or the response's
+/// `Cache-Control` or `Expires`, asks for it, plus a random delay of up to
+/// `jitter` so feeds added together don't stay in lockstep. With `adaptive`
+/// the interval also follows each feed's activity. A fetch that would fall in
+/// the channel's `<skipHours>` or `<skipDays>` waits until they are over. Requests are
+/// conditional on the last response's `ETag` and `Last-Modified`. The
+/// validators and the identities of delivered items are kept in the
+/// `SeenStore`, so with a `FileStore` a restarted poller carries on where it
+/// left off instead of delivering every item again. Once a fetch is read
+/// through, only the identities still in the feed are kept, so an item that
+/// drops out of it and comes back is delivered again.
+///
+/// A failed fetch is retried as the `retry` policy says, then delivered as an
+/// error and tried again at the next interval, or once the `Retry-After` of a
+/// rate-limited response has passed if that is later.
+pub struct Poller<T, K = MemoryStore> {
+    feeds: Vec<Feed>,
+    interval: Duration,
+    jitter: Duration,
+    adaptive: Option<(Duration, Duration)>,
+    options: FetchOptions,
+    metrics: Arc<dyn Metrics>,
+    store: K,
+    _item: PhantomData<fn() -> T>,
+}
+
+struct Feed {
+    id: FeedId,
+    url: String,
+    interval: Option<Duration>,
+}
+
+impl<T> Poller<T> {
+    pub fn new() -> Self {
+        Poller {
+            feeds: Vec::new(),
+            interval: DEFAULT_INTERVAL,
+            jitter: Duration::ZERO,
+            adaptive: None,
+            options: FetchOptions::default(),
+            metrics: Arc::new(NoopMetrics),
+            store: MemoryStore::new(),
+            _item: PhantomData,
+        }
+    }
+}
+
+impl<T> Default for Poller<T> {
+    fn default() -> Self {
+        Poller::new()
+    }
+}
+
+impl<T, K> Poller<T, K>
+where
+    T: GradualRssItem + Identify + Send + 'static,
+    K: SeenStore + Send + Sync + 'static,
+{
+    /// How often feeds are fetched, 15 minutes unless set.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Halves a feed's interval after a fetch with new items and doubles it
+    /// after one without, staying between `min` and `max`. The feed's own
+    /// limits (`<ttl>` and the like) still apply on top.
+    pub fn adaptive(mut self, min: Duration, max: Duration) -> Self {
+        self.adaptive = Some((min, max.max(min)));
+        self
+    }
+
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.options.retry = Some(retry);
+        self
+    }
+
+    /// For `https://` feeds, see `FetchOptions::tls`.
+    pub fn tls(mut self, connector: impl TlsConnector + 'static) -> Self {
+        self.options.tls = Some(Arc::new(connector));
+        self
+    }
+
+    /// Fetches every feed with `options`: user agent, credentials, headers,
+    /// proxy, cache and so on. Their `etag` and `last_modified` are replaced
+    /// by each feed's own. Replaces an earlier `retry` or `tls`.
+    pub fn fetch_options(mut self, options: FetchOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Reports failed fetches, and the items and errors of every parse, to `metrics`.
+    pub fn metrics(mut self, metrics: Arc<dyn Metrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    pub fn store<S: SeenStore>(self, store: S) -> Poller<T, S> {
+        Poller {
+            feeds: self.feeds,
+            interval: self.interval,
+            jitter: self.jitter,
+            adaptive: self.adaptive,
+            options: self.options,
+            metrics: self.metrics,
+            store,
+            _item: PhantomData,
+        }
+    }
+
+    pub fn add(&mut self, url: impl Into<String>) -> FeedId {
+        self.add_feed(url.into(), None)
+    }
+
+    /// Adds a feed fetched every `interval` instead of the poller's.
+    pub fn add_every(&mut self, url: impl Into<String>, interval: Duration) -> FeedId {
+        self.add_feed(url.into(), Some(interval))
+    }
+
+    fn add_feed(&mut self, url: String, interval: Option<Duration>) -> FeedId {
+        let id = FeedId(self.feeds.len());
+        self.feeds.push(Feed { id, url, interval });
+        id
+    }
+
+    /// Starts polling on the current runtime, one task per feed, and returns
+    /// the receiving end. Polling stops once the receiver is dropped.
+    pub fn spawn(self, buffer: usize) -> mpsc::Receiver<(FeedId, Result<T, RssError>)> {
+        let (sender, receiver) = mpsc::channel(buffer.max(1));
+        let shared = Arc::new(Shared {
+            interval: self.interval,
+            jitter: self.jitter,
+            adaptive: self.adaptive,
+            options: self.options,
+            metrics: self.metrics,
+            store: Mutex::new(self.store),
+        });
+        for feed in self.feeds {
+            tokio::spawn(run(feed, shared.clone(), sender.clone()));
+        }
+        receiver
+    }
+
+    /// `spawn` as a `Stream`.
+    pub fn into_stream(self) -> ReceiverStream<(FeedId, Result<T, RssError>)> {
+        ReceiverStream::new(self.spawn(DEFAULT_BUFFER))
+    }
+}
+
+struct Shared<K> {
+    interval: Duration,
+    jitter: Duration,
+    adaptive: Option<(Duration, Duration)>,
+    options: FetchOptions,
+    metrics: Arc<dyn Metrics>,
+    store: Mutex<K>,
+}
+
+// What the feed and its server said about how often to fetch it.
+#[derive(Default)]
+struct Hints {
+    schedule: Schedule,
+    freshness: Option<Duration>,
+}
+
+impl Hints {
+    fn min_interval(&self) -> Duration {
+        self.schedule.min_interval().unwrap_or_default().max(self.freshness.unwrap_or_default())
+    }
+}
+
+type Sender<T> = mpsc::Sender<(FeedId, Result<T, RssError>)>;
+
+async fn run<T, K>(feed: Feed, shared: Arc<Shared<K>>, sender: Sender<T>)
+where
+    T: GradualRssItem + Identify + Send + 'static,
+    K: SeenStore + Send + Sync,
+{
+    let mut hints = Hints::default();
+    let mut interval = feed.interval.unwrap_or(shared.interval);
+    loop {
+        let new = match poll(&feed, &shared, &sender, &mut hints).await {
+            Ok(new) => new,
+            Err(err) => {
+                let _ = sender.send((feed.id, Err(err))).await;
+                0
+            }
+        };
+        if let Some((min, max)) = shared.adaptive {
+            interval = adapt(interval, new, min, max);
+        }
+        let mut wait = interval.max(hints.min_interval()) + jitter(&feed.url, shared.jitter);
+        let due = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default() + wait;
+        wait += hints.schedule.postpone(due.as_secs());
+        tokio::select! {
+            _ = tokio::time::sleep(wait) => {}
+            _ = sender.closed() => return,
+        }
+    }
+}
+
+fn adapt(interval: Duration, new: usize, min: Duration, max: Duration) -> Duration {
+    let interval = if new > 0 { interval / 2 } else { interval.saturating_mul(2) };
+    interval.clamp(min, max)
+}
+
+// Fetches the feed once and sends the items not sent before, returning how many.
+async fn poll<T, K>(feed: &Feed, shared: &Shared<K>, sender: &Sender<T>, hints: &mut Hints) -> Result<usize, RssError>
+where
+    T: GradualRssItem + Identify + Send + 'static,
+    K: SeenStore + Send + Sync,
+{
+    let etag_key = format!("etag {}", feed.url);
+    let last_modified_key = format!("last-modified {}", feed.url);
+    let options = {
+        let store = shared.store.lock().await;
+        FetchOptions {
+            etag: store.get(&etag_key).await?,
+            last_modified: store.get(&last_modified_key).await?,
+            ..shared.options.clone()
+        }
+    };
+    let (result, freshness) = match RssParser::<T, HttpBody>::fetch(&feed.url, &options).await {
+        Ok(fetched) => fetched,
+        Err(err) => {
+            shared.metrics.fetch_failed(&feed.url, &err);
+            if let RssError::Http(HttpError::RateLimited {
+                retry_after: Some(after),
+                ..
+            }) = &err
+            {
+                hints.freshness = Some(*after);
+            }
+            return Err(err);
+        }
+    };
+    hints.freshness = freshness;
+    let FetchResult::Modified {
+        mut parser,
+        etag,
+        last_modified,
+    } = result
+    else {
+        return Ok(0);
+    };
+    parser.set_metrics(shared.metrics.clone());
+    hints.schedule = parser.channel().await?;
+
+    // The identities of delivered items, all under one key per feed.
+    let seen_key = format!("items {}", feed.url);
+    let stored = shared.store.lock().await.get(&seen_key).await?;
+    let mut seen: BTreeSet<String> = stored.as_deref().and_then(|json| serde_json::from_str(json).ok()).unwrap_or_default();
+    let mut current = BTreeSet::new();
+    let mut new = 0;
+    // Whether the feed was read to the end.
+    let finished = loop {
+        let item = match parser.next().await {
+            Ok(Some(item)) => item,
+            Ok(None) => break Ok(true),
+            Err(err) if !err.is_fatal() => {
+                if sender.send((feed.id, Err(err))).await.is_err() {
+                    break Ok(false);
+                }
+                continue;
+            }
+            Err(err) => break Err(err),
+        };
+        let identity = item.identity();
+        if let Some(identity) = &identity {
+            current.insert(identity.clone());
+            if seen.contains(identity) {
+                continue;
+            }
+        }
+        if sender.send((feed.id, Ok(item))).await.is_err() {
+            break Ok(false);
+        }
+        new += 1;
+        seen.extend(identity);
+    };
+
+    // Identities no longer in a feed read to the end are dropped.
+    let keep = if matches!(finished, Ok(true)) { current } else { seen };
+    let keep = serde_json::to_string(&keep)?;
+    let mut store = shared.store.lock().await;
+    if stored.as_deref() != Some(&keep) {
+        store.put(seen_key, keep).await?;
+    }
+    // Only once every item is through, so an interrupted poll is repeated.
+    if !finished? {
+        return Ok(new);
+    }
+    if let Some(etag) = etag {
+        store.put(etag_key, etag).await?;
+    }
+    if let Some(last_modified) = last_modified {
+        store.put(last_modified_key, last_modified).await?;
+    }
+    Ok(new)
+}
+
+// Up to `max`, different for every call.
+fn jitter(url: &str, max: Duration) -> Duration {
+    if max.is_zero() {
+        return Duration::ZERO;
+    }
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    let seed = fnv1a(format!("{}{}", url, now.as_nanos()).as_bytes());
+    max.mul_f64((seed % 1000) as f64 / 1000.0)
+}
+
+// The channel elements that decide when to fetch again.
+#[derive(Default)]
+struct Schedule {
+    info: ChannelInfo,
+    update_period: Option<Duration>,
+    update_frequency: Option<u32>,
+}
+
+impl Schedule {
+    fn min_interval(&self) -> Option<Duration> {
+        let ttl = self.info.ttl.map(|minutes| Duration::from_secs(minutes as u64 * 60));
+        let update = self.update_period.map(|period| period / self.update_frequency.unwrap_or(1).max(1));
+        ttl.max(update)
+    }
+
+    // How long a fetch due at `due` (Unix seconds) has to wait to get out of
+    // the skipped hours and days. Nothing, if every hour of the week is skipped.
+    fn postpone(&self, due: u64) -> Duration {
+        // 1970-01-01 was a Thursday.
+        const DAYS: [&str; 7] = ["Thursday", "Friday", "Saturday", "Sunday", "Monday", "Tuesday", "Wednesday"];
+        let skipped = |at: u64| {
+            let hour = (at / 3600 % 24) as u8;
+            let day = DAYS[(at / 86_400 % 7) as usize];
+            self.info.skip_hours.contains(&hour) || self.info.skip_days.iter().any(|skip| skip.eq_ignore_ascii_case(day))
+        };
+        let mut at = due;
+        for _ in 0..7 * 24 {
+            if !skipped(at) {
+                return Duration::from_secs(at - due);
+            }
+            at = (at / 3600 + 1) * 3600;
+        }
+        Duration::ZERO
+    }
+}
+
+impl GradualRssChannel for Schedule {
+    fn init() -> Self {
+        Schedule::default()
+    }
+
+    fn populate(&mut self, node: XmlNode) {
+        let name = match node.extension_name(ns::SYNDICATION, "sy") {
+            Some(name) if !node.path.contains('/') => name.to_ascii_lowercase(),
+            _ => return self.info.populate(node),
+        };
+        let text = node.value.as_deref().map(str::trim);
+        match name.as_str() {
+            "updateperiod" => {
+                let hours = match text {
+                    Some("hourly") => 1,
+                    Some("daily") => 24,
+                    Some("weekly") => 7 * 24,
+                    Some("monthly") => 30 * 24,
+                    Some("yearly") => 365 * 24,
+                    _ => return,
+                };
+                self.update_period = Some(Duration::from_secs(hours * 3600));
+            }
+            "updatefrequency" => self.update_frequency = text.and_then(|text| text.parse().ok()),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::tests::serve;
+    use crate::RssItem;
+    use tokio_stream::StreamExt;
+
+    fn response(guids: &[&str]) -> String {
+        let items: String = guids.iter().map(|guid| format!("<item><guid>{}</guid></item>", guid)).collect();
+        let feed = format!("<rss><channel>{}</channel></rss>", items);
+        format!("HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: {}\r\n\r\n{}", feed.len(), feed)
+    }
+
+    #[tokio::test]
+    async fn test_poller_delivers_new_items() {
+        let base = serve(vec![response(&["1", "2"]), response(&["3", "2", "1"]), "HTTP/1.1 304 Not Modified\r\n\r\n".to_string()]).await;
+
+        let mut poller = Poller::<RssItem>::new().interval(Duration::from_millis(10));
+        let feed = poller.add(format!("{}/feed", base));
+        let items: Vec<(FeedId, String)> = poller
+            .into_stream()
+            .take(3)
+            .map(|(id, item)| (id, item.unwrap().guid.unwrap().value))
+            .collect()
+            .await;
+        assert_eq!(items, vec![(feed, "1".to_string()), (feed, "2".to_string()), (feed, "3".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn test_poller_forgets_items_gone_from_the_feed() {
+        let base = serve(vec![response(&["1", "2"]), response(&["2", "3"]), response(&["1", "3"])]).await;
+
+        let mut poller = Poller::<RssItem>::new().interval(Duration::from_millis(10));
+        poller.add(format!("{}/feed", base));
+        let guids: Vec<String> = poller.into_stream().take(4).map(|(_, item)| item.unwrap().guid.unwrap().value).collect().await;
+        assert_eq!(guids, ["1", "2", "3", "1"]);
+    }
+
+    #[tokio::test]
+    async fn test_poller_fetch_options() {
+        let (base, request) = crate::http::tests::capture(false).await;
+        let options = FetchOptions::new().user_agent("feedbot/2.0").header("X-Api-Key", "k1").etag("\"ignored\"");
+        let mut poller = Poller::<RssItem>::new().fetch_options(options);
+        poller.add(base);
+        assert!(poller.into_stream().next().await.unwrap().1.is_ok());
+        let request = request.await.unwrap();
+        assert!(request.contains("User-Agent: feedbot/2.0\r\n"), "{request}");
+        assert!(request.contains("X-Api-Key: k1\r\n"), "{request}");
+        assert!(!request.contains("If-None-Match"), "{request}");
+    }
+
+    #[tokio::test]
+    async fn test_poller_waits_out_rate_limits() {
+        let limited = "HTTP/1.1 429 Too Many Requests\r\nRetry-After: 3600\r\nContent-Length: 0\r\n\r\n".to_string();
+        let base = serve(vec![limited]).await;
+
+        let retry = RetryPolicy::new(2).backoff(Duration::from_millis(1), Duration::from_secs(1));
+        let mut poller = Poller::<RssItem>::new().interval(Duration::from_millis(10)).retry(retry);
+        poller.add(format!("{}/feed", base));
+        let mut stream = poller.into_stream();
+        // The hour-long Retry-After is too long to retry on the spot...
+        let (_, first) = stream.next().await.unwrap();
+        assert!(matches!(first, Err(RssError::Http(HttpError::RateLimited { status: 429, .. }))));
+        // ...and the next poll waits it out rather than coming back in 10ms.
+        let next = tokio::time::timeout(Duration::from_millis(200), stream.next()).await;
+        assert!(next.is_err());
+    }
+
+    #[test]
+    fn test_adaptive_interval() {
+        let minutes = |n: u64| Duration::from_secs(n * 60);
+        assert_eq!(adapt(minutes(20), 3, minutes(5), minutes(60)), minutes(10));
+        assert_eq!(adapt(minutes(8), 1, minutes(5), minutes(60)), minutes(5));
+        assert_eq!(adapt(minutes(40), 0, minutes(5), minutes(60)), minutes(60));
+    }
+
+    #[tokio::test]
+    async fn test_schedule_hints() {
+        let feed = r#"<rss xmlns:sy="http://purl.org/rss/1.0/modules/syndication/"><channel>
+<ttl>30</ttl><sy:updatePeriod>hourly</sy:updatePeriod><sy:updateFrequency>2</sy:updateFrequency>
+<image><ttl>999</ttl></image>
+<item/></channel></rss>"#;
+        let mut parser = RssParser::<RssItem, _>::new(std::io::Cursor::new(feed.as_bytes())).await.unwrap();
+        let schedule: Schedule = parser.channel().await.unwrap();
+        assert_eq!(schedule.info.ttl, Some(30));
+        assert_eq!(schedule.min_interval(), Some(Duration::from_secs(30 * 60)));
+
+        let daily = Schedule {
+            update_period: Some(Duration::from_secs(86_400)),
+            ..Schedule::default()
+        };
+        assert_eq!(daily.min_interval(), Some(Duration::from_secs(86_400)));
+    }
+
+    #[test]
+    fn test_skip_hours_and_days() {
+        // Monday 2024-01-01 00:30 GMT.
+        let monday = 1_704_067_200 + 1800;
+        let mut schedule = Schedule::default();
+        assert_eq!(schedule.postpone(monday), Duration::ZERO);
+
+        schedule.info.skip_hours = vec![0, 1];
+        assert_eq!(schedule.postpone(monday), Duration::from_secs(5400));
+
+        schedule.info.skip_days = vec!["Monday".to_string()];
+        assert_eq!(schedule.postpone(monday), Duration::from_secs(86_400 - 1800 + 7200));
+
+        schedule.info.skip_hours = (0..24).collect();
+        assert_eq!(schedule.postpone(monday), Duration::ZERO);
+    }
+}
@@ -0,0 +1,185 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use tokio_stream::Stream;
+
+#[cfg(feature = "dates")]
+use crate::aggregate::Dated;
+#[cfg(feature = "dates")]
+use crate::date::DateTime;
+use crate::error::RssError;
+
+/// The first items of a stream, from `RssStreamExt::take_items`.
+///
+/// Errors are passed on without counting. Once the last item is out the
+/// inner stream is dropped, so nothing more is read from its source.
+pub struct TakeItems<S> {
+    stream: Option<S>,
+    left: usize,
+}
+
+impl<S> TakeItems<S> {
+    pub(crate) fn new(stream: S, count: usize) -> Self {
+        TakeItems {
+            stream: (count > 0).then_some(stream),
+            left: count,
+        }
+    }
+}
+
+impl<S: Unpin> Unpin for TakeItems<S> {}
+
+impl<S, T> Stream for TakeItems<S>
+where
+    S: Stream<Item = Result<T, RssError>> + Unpin,
+{
+    type Item = Result<T, RssError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let Some(stream) = &mut this.stream else {
+            return Poll::Ready(None);
+        };
+        let next = Pin::new(stream).poll_next(cx);
+        match &next {
+            Poll::Ready(Some(Ok(_))) => {
+                this.left -= 1;
+                if this.left == 0 {
+                    this.stream = None;
+                }
+            }
+            Poll::Ready(None) => this.stream = None,
+            _ => {}
+        }
+        next
+    }
+}
+
+/// The items of a stream from a date on, from `RssStreamExt::since`.
+///
+/// Feeds list their newest items first, so the stream ends at the first item
+/// dated before the cutoff, without reading the rest of the feed. Undated
+/// items and errors are passed on.
+#[cfg(feature = "dates")]
+pub struct Since<S> {
+    stream: Option<S>,
+    since: DateTime,
+}
+
+#[cfg(feature = "dates")]
+impl<S> Since<S> {
+    pub(crate) fn new(stream: S, since: DateTime) -> Self {
+        Since {
+            stream: Some(stream),
+            since,
+        }
+    }
+}
+
+#[cfg(feature = "dates")]
+impl<S: Unpin> Unpin for Since<S> {}
+
+#[cfg(feature = "dates")]
+impl<S, T> Stream for Since<S>
+where
+    S: Stream<Item = Result<T, RssError>> + Unpin,
+    T: Dated,
+{
+    type Item = Result<T, RssError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let Some(stream) = &mut this.stream else {
+            return Poll::Ready(None);
+        };
+        match Pin::new(stream).poll_next(cx) {
+            Poll::Ready(Some(Ok(item))) if item.date().is_some_and(|date| date < this.since) => {
+                this.stream = None;
+                Poll::Ready(None)
+            }
+            Poll::Ready(None) => {
+                this.stream = None;
+                Poll::Ready(None)
+            }
+            next => next,
+        }
+    }
+}
+
+/// The items of a stream dated up to a date, from `RssStreamExt::until`.
+///
+/// Later items are skipped; undated items and errors are passed on.
+#[cfg(feature = "dates")]
+pub struct Until<S> {
+    stream: S,
+    until: DateTime,
+}
+
+#[cfg(feature = "dates")]
+impl<S> Until<S> {
+    pub(crate) fn new(stream: S, until: DateTime) -> Self {
+        Until { stream, until }
+    }
+}
+
+#[cfg(feature = "dates")]
+impl<S: Unpin> Unpin for Until<S> {}
+
+#[cfg(feature = "dates")]
+impl<S, T> Stream for Until<S>
+where
+    S: Stream<Item = Result<T, RssError>> + Unpin,
+    T: Dated,
+{
+    type Item = Result<T, RssError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            match Pin::new(&mut this.stream).poll_next(cx) {
+                Poll::Ready(Some(Ok(item))) if item.date().is_some_and(|date| date > this.until) => continue,
+                next => return next,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{RssItem, RssParser, RssStreamExt};
+    use std::io::Cursor;
+    use tokio_stream::StreamExt;
+
+    const FEED: &str = r#"<rss><channel>
+<item><title>March</title><pubDate>Fri, 01 Mar 2024 00:00:00 GMT</pubDate></item>
+<item><title>Undated</title></item>
+<item><title>February</title><pubDate>Thu, 01 Feb 2024 00:00:00 GMT</pubDate></item>
+<item><title>January</title><pubDate>Mon, 01 Jan 2024 00:00:00 GMT</pubDate></item>
+<item><title>December</title><pubDate>Fri, 01 Dec 2023 00:00:00 GMT</pubDate></item>
+</channel></rss>"#;
+
+    fn titles(items: Vec<Result<RssItem, crate::RssError>>) -> Vec<String> {
+        items.into_iter().map(|item| item.unwrap().title.unwrap()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_take_items() {
+        let parser = RssParser::<RssItem, _>::new(Cursor::new(FEED.as_bytes())).await.unwrap();
+        let items: Vec<_> = parser.into_stream().take_items(2).collect().await;
+        assert_eq!(titles(items), vec!["March", "Undated"]);
+    }
+
+    #[cfg(feature = "dates")]
+    #[tokio::test]
+    async fn test_since_and_until() {
+        use crate::parse_date;
+
+        let parser = RssParser::<RssItem, _>::new(Cursor::new(FEED.as_bytes())).await.unwrap();
+        let window = parser
+            .into_stream()
+            .since(parse_date("2024-01-15T00:00:00Z").unwrap())
+            .until(parse_date("2024-02-15T00:00:00Z").unwrap());
+        let items: Vec<_> = window.collect().await;
+        assert_eq!(titles(items), vec!["Undated", "February"]);
+    }
+}
@@ -0,0 +1,111 @@
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use tokio::io::{AsyncRead, AsyncReadExt, ReadBuf};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+const DEFAULT_CHUNK_SIZE: usize = 8 * 1024;
+
+/// Reads its input on a separate task, up to `chunks` chunks ahead of the
+/// parser, so a slow network and the parsing overlap instead of taking
+/// turns. Wrap the input in it before building the parser:
+/// `RssParser::builder().build(ReadAhead::new(body, 16))`.
+///
+/// The task runs on the current Tokio runtime and is cancelled when the
+/// `ReadAhead` is dropped.
+pub struct ReadAhead {
+    chunks: mpsc::Receiver<io::Result<Vec<u8>>>,
+    chunk: Vec<u8>,
+    position: usize,
+    task: JoinHandle<()>,
+}
+
+impl ReadAhead {
+    pub fn new<R: AsyncRead + Unpin + Send + 'static>(input: R, chunks: usize) -> Self {
+        Self::with_chunk_size(input, chunks, DEFAULT_CHUNK_SIZE)
+    }
+
+    /// Reads chunks of up to `chunk_size` bytes, 8 KiB unless set.
+    pub fn with_chunk_size<R: AsyncRead + Unpin + Send + 'static>(mut input: R, chunks: usize, chunk_size: usize) -> Self {
+        let (sender, receiver) = mpsc::channel(chunks.max(1));
+        let chunk_size = chunk_size.max(1);
+        let task = tokio::spawn(async move {
+            loop {
+                let mut chunk = vec![0; chunk_size];
+                let read = match input.read(&mut chunk).await {
+                    Ok(0) => return,
+                    Ok(read) => read,
+                    Err(err) => {
+                        let _ = sender.send(Err(err)).await;
+                        return;
+                    }
+                };
+                chunk.truncate(read);
+                if sender.send(Ok(chunk)).await.is_err() {
+                    return;
+                }
+            }
+        });
+        ReadAhead {
+            chunks: receiver,
+            chunk: Vec::new(),
+            position: 0,
+            task,
+        }
+    }
+}
+
+impl AsyncRead for ReadAhead {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        while this.position == this.chunk.len() {
+            match this.chunks.poll_recv(cx) {
+                Poll::Ready(Some(Ok(chunk))) => {
+                    this.chunk = chunk;
+                    this.position = 0;
+                }
+                Poll::Ready(Some(Err(err))) => return Poll::Ready(Err(err)),
+                // The input has ended.
+                Poll::Ready(None) => return Poll::Ready(Ok(())),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+        let count = buf.remaining().min(this.chunk.len() - this.position);
+        buf.put_slice(&this.chunk[this.position..this.position + count]);
+        this.position += count;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl Drop for ReadAhead {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RssItem, RssParser};
+    use std::io::Cursor;
+
+    #[tokio::test]
+    async fn test_read_ahead() {
+        let mut feed = String::from("<rss><channel>");
+        for index in 0..100 {
+            feed.push_str(&format!("<item><title>Item {}</title></item>", index));
+        }
+        feed.push_str("</channel></rss>");
+
+        let input = ReadAhead::with_chunk_size(Cursor::new(feed.into_bytes()), 2, 7);
+        let mut parser = RssParser::<RssItem, _>::new(input).await.unwrap();
+        let mut count = 0;
+        while let Some(item) = parser.next().await.unwrap() {
+            assert_eq!(item.title, Some(format!("Item {}", count)));
+            count += 1;
+        }
+        assert_eq!(count, 100);
+    }
+}
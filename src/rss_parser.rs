@@ -1,103 +1,1213 @@
+use std::time::Instant;
+
+use quick_xml::escape::resolve_html5_entity;
+use quick_xml::errors::IllFormedError;
 use quick_xml::events::*;
+use quick_xml::name::ResolveResult;
 use quick_xml::reader::*;
+#[cfg(not(feature = "tokio"))]
+use std::io::BufReader;
+#[cfg(feature = "tokio")]
 use tokio::fs::File;
-use tokio::io::AsyncRead;
-use tokio::io::BufReader;
+#[cfg(feature = "tokio")]
+use tokio::io::{BufReader, Stdin};
+#[cfg(feature = "tokio")]
 use tokio::net::TcpStream;
+#[cfg(feature = "tokio")]
+use tokio::sync::mpsc;
+#[cfg(all(unix, feature = "tokio"))]
+use tokio::net::UnixStream;
+#[cfg(feature = "tokio")]
 use tokio_stream::Stream;
 
+use crate::async_read::AsyncRead;
+use crate::builder::{ParseMode, ParserOptions, RssParserBuilder};
+#[cfg(feature = "tokio")]
+use crate::byte_stream::ByteStream;
+use crate::capture::Recorder;
+use crate::channel::ChannelInfo;
+use crate::checkpoint::Checkpoint;
+use crate::encoding::{DecodingReader, Encoding};
+use crate::error::{Location, RssError};
+use crate::limits::{Guard, Trip};
+use crate::node_ref::XmlNodeRef;
+use crate::stats::ParserStats;
+#[cfg(feature = "http")]
+use crate::http::{FetchOptions, FetchResult, HttpBody, HttpError};
+#[cfg(feature = "tokio")]
+use crate::stream::RssStream;
+use crate::tag::{TagId, TagTable};
+#[cfg(feature = "tokio")]
+use crate::tls::{BoxedConnection, TlsConnector};
+use crate::url::child_base;
+
+#[derive(Debug, Clone)]
 pub struct XmlNode {
     pub tag: String,
+    /// The tag as a `TagId`, for matching without string comparisons.
+    pub tag_id: TagId,
+    /// The tag without its namespace prefix.
+    pub local_name: String,
+    /// The namespace URI the tag's prefix (or the default namespace) resolves to.
+    pub namespace: Option<String>,
+    /// Slash-separated tags from the item down to this element, e.g. `author/name`.
+    /// Direct children of the item have just their tag; the item element itself
+    /// has an empty path.
+    pub path: String,
     pub value: Option<String>,
     pub cdata: Option<String>,
+    pub attributes: Vec<(String, String)>,
+    /// The `xml:base` in scope, resolved against the enclosing ones.
+    pub base: Option<String>,
+    /// The `xml:lang` in scope, from this element or the nearest ancestor.
+    pub lang: Option<String>,
+    /// The markup between the start and end tags exactly as written, for
+    /// elements that carry XML themselves (Atom `xhtml` content, extensions).
+    /// Only set with `RssParserBuilder::capture_inner_xml`, and never for the
+    /// root, `<channel>` or `<feed>` elements.
+    pub inner_xml: Option<String>,
+    inner_start: Option<u64>,
+    // `xml:space="preserve"` is in scope.
+    preserve_space: bool,
+    // The text hit `max_element_size` in lenient mode; the rest is dropped.
+    truncated: bool,
 }
 
 impl XmlNode {
-    fn new(tag: String) -> Self {
+    pub(crate) fn new(tag: String) -> Self {
         XmlNode {
+            local_name: tag.rsplit(':').next().unwrap_or(&tag).to_string(),
+            path: tag.clone(),
+            tag_id: TagId::of(&tag),
             tag,
+            ..XmlNode::blank()
+        }
+    }
+
+    fn blank() -> Self {
+        XmlNode {
+            tag: String::new(),
+            tag_id: TagId::empty(),
+            local_name: String::new(),
+            path: String::new(),
+            namespace: None,
             value: None,
             cdata: None,
+            attributes: Vec::new(),
+            base: None,
+            lang: None,
+            inner_xml: None,
+            inner_start: None,
+            preserve_space: false,
+            truncated: false,
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    fn from_start(start: &BytesStart, options: &ParserOptions) -> Self {
+        Self::fill(XmlNode::blank(), start, options, &mut TagTable::default())
+    }
+
+    // Sets up a blank node, possibly a recycled one, for a start tag.
+    fn fill(mut node: XmlNode, start: &BytesStart, options: &ParserOptions, tags: &mut TagTable) -> Self {
+        options.normalize_into(start.name().as_ref(), &mut node.tag);
+        node.local_name.push_str(node.tag.rsplit(':').next().unwrap_or(&node.tag));
+        node.tag_id = tags.id(&node.tag);
+        for attr in start.attributes().flatten() {
+            let key = options.normalize_name(attr.key.as_ref());
+            if let Ok(value) = attr.unescape_value() {
+                node.attributes.push((key, value.into_owned()));
+            }
+        }
+        node
+    }
+
+    // Blanks the node for `fill`, keeping the capacity of its strings. The
+    // text buffers are handed back separately.
+    fn reset(&mut self) -> [Option<String>; 2] {
+        self.tag.clear();
+        self.local_name.clear();
+        self.namespace = None;
+        self.path.clear();
+        self.attributes.clear();
+        self.base = None;
+        self.lang = None;
+        self.inner_xml = None;
+        self.inner_start = None;
+        self.preserve_space = false;
+        self.truncated = false;
+        [self.value.take(), self.cdata.take()]
+    }
+
+    pub(crate) fn preserves_space(&self) -> bool {
+        self.preserve_space
+    }
+
+    /// Compares the tag ignoring ASCII case, for use with `lowercase_tags(false)`.
+    pub fn tag_is(&self, tag: &str) -> bool {
+        self.tag.eq_ignore_ascii_case(tag)
+    }
+
+    /// Compares the path ignoring ASCII case.
+    pub fn path_is(&self, path: &str) -> bool {
+        self.path.eq_ignore_ascii_case(path)
+    }
+
+    /// Matches the element by namespace URI and local name, whatever prefix the feed used.
+    pub fn is(&self, namespace: &str, local_name: &str) -> bool {
+        self.namespace.as_deref() == Some(namespace) && self.local_name.eq_ignore_ascii_case(local_name)
+    }
+
+    // The local name of an extension element, also accepting the conventional
+    // prefix when the feed forgot to declare the namespace.
+    pub(crate) fn extension_name(&self, namespace: &str, prefix: &str) -> Option<&str> {
+        match self.namespace.as_deref() {
+            Some(bound) if bound == namespace => Some(self.local_name.as_str()),
+            None => self.tag.strip_prefix(prefix)?.strip_prefix(':'),
+            Some(_) => None,
         }
     }
+
+    /// The element's text, falling back to its CDATA section when the text is
+    /// missing or only the whitespace around it.
+    pub fn into_text(self) -> Option<String> {
+        match self.value {
+            Some(value) if self.cdata.is_some() && value.trim().is_empty() => self.cdata,
+            value => value.or(self.cdata),
+        }
+    }
+
+    /// The tag of the enclosing element, or `None` for the item's direct
+    /// children and the item itself.
+    pub fn parent(&self) -> Option<&str> {
+        let (ancestors, _) = self.path.rsplit_once('/')?;
+        Some(ancestors.rsplit('/').next().unwrap_or(ancestors))
+    }
+
+    /// Looks up an attribute by name, ignoring ASCII case.
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
 }
 
-const XML_KEY_ITEM: &str = "item";
+pub(crate) const XML_KEY_ITEM: &str = "item";
+pub(crate) const XML_KEY_ENTRY: &str = "entry";
 
+/// Builds an item from its elements.
+///
+/// `populate` is called once per element when it closes, so children arrive
+/// before their parents and the item element itself comes last. Each node
+/// carries its attributes and its `path` within the item
+/// (`media:group/media:content`); use `path` or `parent()` to tell elements
+/// with the same tag apart.
 pub trait GradualRssItem {
     fn init() -> Self;
     fn populate(&mut self, node: XmlNode);
+
+    /// Set to deliver elements to `populate_ref` instead of `populate`, which
+    /// saves copying text the item doesn't keep and lets the parser reuse
+    /// each element's buffers for the next one.
+    const BORROWED: bool = false;
+
+    /// Called instead of `populate` when `BORROWED` is set.
+    fn populate_ref(&mut self, node: XmlNodeRef<'_>) {
+        self.populate(node.into_owned());
+    }
+
+    /// Called once the item is complete, with the channel metadata read so far
+    /// (the channel elements before the item), so links can be resolved
+    /// against the channel and feed-wide defaults such as the author inherited.
+    fn with_channel(&mut self, _channel: &ChannelInfo) {}
+
+    /// Called after `with_channel`. Returning an error (say, for a
+    /// missing mandatory field) reports `RssError::InvalidItem` instead of
+    /// yielding the item; parsing can carry on with the next one.
+    fn finish(self) -> Result<Self, String>
+    where
+        Self: Sized,
+    {
+        Ok(self)
+    }
+}
+
+/// Builds feed-level metadata from the `<channel>` (or Atom `<feed>`) children.
+///
+/// Nested elements are delivered too, innermost first, with `path` relative
+/// to the channel (`image/url`), so match on `path` rather than `tag`.
+pub trait GradualRssChannel {
+    fn init() -> Self;
+    fn populate(&mut self, node: XmlNode);
+}
+
+const XML_KEY_CHANNEL: &str = "channel";
+// How many delivered nodes and text buffers are kept for reuse.
+const SPARE_LIMIT: usize = 64;
+const XML_KEY_FEED: &str = "feed";
+
+enum Step<T> {
+    Item(T),
+    ItemStarted,
+    Eof,
+    Continue,
 }
 
+type Source<R> = BufReader<Recorder<Guard<DecodingReader<R>>>>;
+type StopHook<T> = Box<dyn FnMut(&T) -> bool + Send>;
+
 pub struct RssParser<T, R> {
-    reader: Reader<BufReader<R>>,
-    _phantom: std::marker::PhantomData<T>,
+    reader: NsReader<Source<R>>,
+    options: ParserOptions,
+    buf: Vec<u8>,
+    node_stack: Vec<XmlNode>,
+    processing: Option<T>,
+    item_depth: usize,
+    peeked: Option<T>,
+    channel_nodes: Vec<XmlNode>,
+    item_seen: bool,
+    item_start: u64,
+    warnings: Vec<RssError>,
+    resyncing: bool,
+    raw_item_start: Option<u64>,
+    raw_item: Option<String>,
+    // Built from `channel_nodes` when an item needs it, dropped when they change.
+    channel_info: Option<ChannelInfo>,
+    // Open elements past `max_depth`, which are not put on `node_stack`.
+    skipped_depth: usize,
+    encoding_checked: bool,
+    // With `track_lines`: the line and the offset it starts at, as of `lines_at`.
+    line: u64,
+    line_start: u64,
+    lines_at: u64,
+    item_location: Option<Location>,
+    stop_when: Option<StopHook<T>>,
+    // A stop condition was met; the rest of the feed is not read.
+    stopped: bool,
+    // Hit by `next_chunk` after it had items to return; returned by the next call.
+    deferred_error: Option<RssError>,
+    // A guard or I/O error was returned; the input is not read any further.
+    failed: bool,
+    started: Instant,
+    items_returned: u64,
+    items_skipped: u64,
+    item_started: Instant,
+    // With `T::BORROWED`: delivered nodes and text buffers kept for reuse,
+    // and the path of the node being delivered.
+    spare_nodes: Vec<XmlNode>,
+    spare_text: Vec<String>,
+    path: String,
+    tags: TagTable,
+    // Where in the input a resumed parser started, and the namespaces its
+    // restored ancestors declared, innermost first.
+    input_base: u64,
+    inherited_namespaces: Vec<(String, String)>,
 }
 
 impl<T: GradualRssItem, R: AsyncRead + Unpin> RssParser<T, R> {
+    /// `input` can be a file, a response body or any connected stream: a
+    /// socket, pipe, in-process duplex or proxy tunnel. It must end (or
+    /// close) after the document.
     pub async fn new(input: R) -> std::io::Result<Self> {
-        let buffer = BufReader::new(input);
-        let reader = Reader::from_reader(buffer);
-        let obj = RssParser {
+        Self::with_item_tag(input, XML_KEY_ITEM).await
+    }
+
+    /// Parses Atom feeds, yielding one item per `<entry>`.
+    pub async fn new_atom(input: R) -> std::io::Result<Self> {
+        Self::with_item_tag(input, XML_KEY_ENTRY).await
+    }
+
+    /// Yields one item per element named `item_tag` (matched case-insensitively).
+    pub async fn with_item_tag(input: R, item_tag: &str) -> std::io::Result<Self> {
+        RssParserBuilder::new().item_tag(item_tag).build(input).await
+    }
+
+    pub(crate) fn from_options(input: R, options: ParserOptions) -> Self {
+        Self::from_decoder(DecodingReader::new(input), options)
+    }
+
+    fn from_decoder(decoder: DecodingReader<R>, mut options: ParserOptions) -> Self {
+        options.item_tag = options.normalize_tag(&options.item_tag);
+        // The read-ahead buffer may hold a full capacity beyond the current event.
+        let max_span = options.max_element_size.map(|max| max.saturating_add(options.buffer_capacity as u64));
+        let guard = Guard::new(decoder, max_span, options.max_feed_size, options.stall_timeout);
+        let recorder = Recorder::new(guard, options.capture_inner_xml || options.capture_raw_items || options.track_lines);
+        let buffer = BufReader::with_capacity(options.buffer_capacity, recorder);
+        let reader = NsReader::from_reader(buffer);
+        RssParser {
             reader,
-            _phantom: std::marker::PhantomData,
+            options,
+            buf: Vec::new(),
+            node_stack: Vec::with_capacity(16),
+            processing: None,
+            item_depth: 0,
+            peeked: None,
+            channel_nodes: Vec::new(),
+            item_seen: false,
+            item_start: 0,
+            warnings: Vec::new(),
+            resyncing: false,
+            raw_item_start: None,
+            raw_item: None,
+            channel_info: None,
+            skipped_depth: 0,
+            encoding_checked: false,
+            line: 1,
+            line_start: 0,
+            lines_at: 0,
+            item_location: None,
+            stop_when: None,
+            stopped: false,
+            deferred_error: None,
+            failed: false,
+            started: Instant::now(),
+            items_returned: 0,
+            items_skipped: 0,
+            item_started: Instant::now(),
+            spare_nodes: Vec::new(),
+            spare_text: Vec::new(),
+            path: String::new(),
+            tags: TagTable::default(),
+            input_base: 0,
+            inherited_namespaces: Vec::new(),
+        }
+    }
+
+    /// Carries on from `checkpoint` with `input`, which must pick the same
+    /// document up at `checkpoint.offset()`: the rest of an interrupted
+    /// download, say, fetched again with a range request. The parser is set
+    /// up as it was, with the same options and channel metadata. Offsets it
+    /// reports in errors and locations count from where it resumed.
+    pub fn resume(checkpoint: Checkpoint, input: R) -> Self {
+        if checkpoint.offset == 0 {
+            return Self::from_options(input, checkpoint.options);
+        }
+        let namespaces = checkpoint.namespaces();
+        let mut parser = Self::from_decoder(DecodingReader::resumed(input), checkpoint.options);
+        // The end tags of the restored ancestors have no start tag in this input.
+        parser.reader.config_mut().allow_unmatched_ends = true;
+        parser.node_stack = checkpoint.ancestors;
+        parser.channel_nodes = checkpoint.channel_nodes;
+        parser.item_seen = checkpoint.item_seen;
+        parser.items_returned = checkpoint.items;
+        parser.input_base = checkpoint.offset;
+        parser.inherited_namespaces = namespaces;
+        parser
+    }
+
+    /// Where parsing stands between items, for `resume` to pick up from if the
+    /// input breaks off. A peeked item is parsed again after resuming.
+    ///
+    /// `None` for feeds transcoded from another encoding, whose offsets don't
+    /// map back onto the input, and inside elements past `max_depth`.
+    pub fn checkpoint(&self) -> Option<Checkpoint> {
+        let decoder = self.reader.get_ref().get_ref().get_ref().get_ref();
+        if decoder.encoding().is_some_and(|encoding| encoding != Encoding::Utf8) || self.skipped_depth > 0 {
+            return None;
+        }
+        let position = match (&self.peeked, self.item_location) {
+            (Some(_), Some(location)) => location.offset,
+            _ => self.reader.buffer_position(),
+        };
+        // What a discarded item left open is not carried over.
+        let depth = if self.resyncing { self.item_depth } else { self.node_stack.len() };
+        let ancestors = self.node_stack[..depth.min(self.node_stack.len())]
+            .iter()
+            .map(|node| XmlNode {
+                value: None,
+                cdata: None,
+                inner_start: None,
+                ..node.clone()
+            })
+            .collect();
+        Some(Checkpoint {
+            offset: self.input_base + decoder.bom_len() as u64 + position,
+            ancestors,
+            channel_nodes: self.channel_nodes.clone(),
+            options: self.options.clone(),
+            items: self.items_returned,
+            item_seen: self.item_seen,
+        })
+    }
+
+    /// Ends the feed at the first item `should_stop` accepts, which is not
+    /// returned: pollers can stop at the newest item they already have
+    /// instead of parsing the whole document.
+    pub fn stop_when(mut self, should_stop: impl FnMut(&T) -> bool + Send + 'static) -> Self {
+        self.stop_when = Some(Box::new(should_stop));
+        self
+    }
+
+    #[cfg(feature = "http")]
+    pub(crate) fn set_metrics(&mut self, metrics: std::sync::Arc<dyn crate::metrics::Metrics>) {
+        self.options.metrics = Some(crate::metrics::MetricsHandle(metrics));
+    }
+
+    /// The encoding the feed was transcoded from, once parsing has started.
+    pub fn encoding(&self) -> Option<Encoding> {
+        self.reader.get_ref().get_ref().get_ref().get_ref().encoding()
+    }
+
+    /// Problems that did not stop parsing, oldest first: items dropped and text
+    /// truncated in lenient mode, and an unrecognised declared encoding (read as
+    /// ASCII, other bytes replaced with U+FFFD) in either mode. Grows as parsing
+    /// goes on.
+    pub fn warnings(&self) -> &[RssError] {
+        &self.warnings
+    }
+
+    /// How far parsing has got, for monitoring long-running jobs.
+    pub fn stats(&self) -> ParserStats {
+        ParserStats {
+            bytes_read: self.reader.buffer_position(),
+            items: self.items_returned,
+            items_skipped: self.items_skipped,
+            warnings: self.warnings.len(),
+            elapsed: self.started.elapsed(),
+        }
+    }
+
+    /// Where the start tag of the item last returned by `next` begins.
+    pub fn item_location(&self) -> Option<Location> {
+        self.item_location
+    }
+
+    /// Turns a byte offset from `RssError::position` into a location, with the
+    /// line and column when `track_lines` is on and the offset is within the
+    /// event read last.
+    pub fn locate(&self, offset: u64) -> Location {
+        let mut location = Location {
+            offset,
+            line: None,
+            column: None,
         };
-        Ok(obj)
+        if self.options.track_lines
+            && let Some(bytes) = self.reader.get_ref().get_ref().slice(self.lines_at, offset)
+        {
+            let (line, line_start) = count_lines(bytes, self.lines_at, self.line, self.line_start);
+            location.line = Some(line);
+            location.column = Some(offset - line_start + 1);
+        }
+        location
+    }
+
+    #[cfg(feature = "tokio")]
+    pub fn into_stream<'a>(self) -> RssStream<'a, T, R> {
+        RssStream::new(self)
     }
 
-    pub async fn next(&mut self) -> Option<T> {
-        let mut node_stacks: Vec<XmlNode> = Vec::new();
-        let mut processing: Option<T> = None;
-        let mut buf = Vec::new();
+    /// Parses on a task of the current runtime and returns the receiving
+    /// end. At most `buffer` items wait in the channel; past that the task
+    /// stops reading the input until the consumer catches up. Like
+    /// `into_stream`, parsing ends after an error that `RssError::is_fatal`
+    /// says stops the feed, or once the receiver is dropped.
+    #[cfg(feature = "tokio")]
+    pub fn spawn(mut self, buffer: usize) -> mpsc::Receiver<Result<T, RssError>>
+    where
+        T: Send + 'static,
+        R: Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel(buffer.max(1));
+        tokio::spawn(async move {
+            loop {
+                let result = match self.next().await {
+                    Ok(Some(item)) => Ok(item),
+                    Ok(None) => break,
+                    Err(err) => Err(err),
+                };
+                let last = matches!(&result, Err(err) if err.is_fatal());
+                if sender.send(result).await.is_err() || last {
+                    break;
+                }
+            }
+        });
+        receiver
+    }
+
+    /// Gives back the input and the bytes already read from it but not
+    /// parsed, in that order, so a connection can be reused once the feed is
+    /// done. A peeked item is dropped. The bytes are exact for UTF-8 feeds;
+    /// for others they come back transcoded to UTF-8.
+    pub fn into_inner(self) -> (R, Vec<u8>) {
+        let buffered = self.reader.into_inner();
+        let mut rest = buffered.buffer().to_vec();
+        let (input, decoded) = buffered.into_inner().into_inner().into_inner().into_inner();
+        rest.extend(decoded);
+        (input, rest)
+    }
+
+    // Consumes everything up to and including the root start tag and returns its name.
+    #[cfg(feature = "tokio")]
+    pub(crate) async fn read_root(&mut self) -> Result<Option<String>, RssError> {
+        loop {
+            let before = self.reader.buffer_position();
+            self.buf.clear();
+            let event = self
+                .reader
+                .read_event_into_async(&mut self.buf)
+                .await
+                .map_err(|err| RssError::from_xml(err, self.reader.error_position()))?;
 
-        while let Ok(event) = self.reader.read_event_into_async(&mut buf).await {
             match event {
-                Event::Start(name) => {
-                    let tag = String::from_utf8_lossy(name.as_ref()).to_lowercase();
-                    if tag == XML_KEY_ITEM {
-                        processing = Some(T::init());
+                Event::Start(start) => {
+                    let node = Self::resolve(&self.reader, &start, &self.options, &mut self.tags, None, None);
+                    let tag = node.tag.clone();
+                    self.node_stack.push(node);
+                    return Ok(Some(tag));
+                }
+                Event::Empty(start) => return Ok(Some(XmlNode::from_start(&start, &self.options).tag)),
+                Event::DocType(_) => self.check_doctype(before)?,
+                Event::Eof => return Ok(None),
+                _ => {}
+            }
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    pub(crate) fn retarget<U>(self, item_tag: &str) -> RssParser<U, R> {
+        let mut options = self.options;
+        options.item_tag = options.normalize_tag(item_tag);
+        RssParser {
+            reader: self.reader,
+            options,
+            buf: self.buf,
+            node_stack: self.node_stack,
+            processing: None,
+            item_depth: 0,
+            peeked: None,
+            channel_nodes: self.channel_nodes,
+            item_seen: self.item_seen,
+            item_start: 0,
+            warnings: self.warnings,
+            resyncing: false,
+            raw_item_start: None,
+            raw_item: None,
+            channel_info: None,
+            skipped_depth: 0,
+            encoding_checked: false,
+            line: 1,
+            line_start: 0,
+            lines_at: 0,
+            item_location: None,
+            stop_when: None,
+            stopped: self.stopped,
+            deferred_error: self.deferred_error,
+            failed: self.failed,
+            started: self.started,
+            items_returned: self.items_returned,
+            items_skipped: self.items_skipped,
+            item_started: self.item_started,
+            spare_nodes: self.spare_nodes,
+            spare_text: self.spare_text,
+            path: self.path,
+            tags: self.tags,
+            input_base: self.input_base,
+            inherited_namespaces: self.inherited_namespaces,
+        }
+    }
+
+    pub async fn next(&mut self) -> Result<Option<T>, RssError> {
+        self.peek().await?;
+        let item = self.peeked.take();
+        if item.is_some() {
+            self.items_returned += 1;
+        }
+        if let Some(progress) = &self.options.progress {
+            progress.0(&self.stats());
+        }
+        Ok(item)
+    }
+
+    /// Up to `max` items at once, e.g. for batched database inserts; an empty
+    /// chunk means the feed has ended. An error after some items were parsed
+    /// is returned by the following call, so the items aren't lost.
+    pub async fn next_chunk(&mut self, max: usize) -> Result<Vec<T>, RssError> {
+        if let Some(err) = self.deferred_error.take() {
+            return Err(err);
+        }
+        let mut chunk = Vec::with_capacity(max.min(64));
+        while chunk.len() < max {
+            match self.next().await {
+                Ok(Some(item)) => chunk.push(item),
+                Ok(None) => break,
+                Err(err) if chunk.is_empty() => return Err(err),
+                Err(err) => {
+                    self.deferred_error = Some(err);
+                    break;
+                }
+            }
+        }
+        Ok(chunk)
+    }
+
+    /// The item `next` will return, parsed ahead but not consumed.
+    pub async fn peek(&mut self) -> Result<Option<&T>, RssError> {
+        if self.peeked.is_none()
+            && !self.stopped
+            && let Some(item) = self.read_item().await?
+        {
+            self.hold(item);
+        }
+        Ok(self.peeked.as_ref())
+    }
+
+    // Keeps a parsed item for `next`, unless it meets the stop condition.
+    fn hold(&mut self, item: T) {
+        if self.stop_when.as_mut().is_some_and(|should_stop| should_stop(&item)) {
+            self.stopped = true;
+        } else {
+            self.peeked = Some(item);
+        }
+    }
+
+    /// Like `next` with a one-off stop condition: once `should_stop` accepts
+    /// an item, that item and everything after it are skipped unread.
+    pub async fn next_until(&mut self, mut should_stop: impl FnMut(&T) -> bool) -> Result<Option<T>, RssError> {
+        match self.next().await? {
+            Some(item) if should_stop(&item) => {
+                self.stopped = true;
+                Ok(None)
+            }
+            item => Ok(item),
+        }
+    }
+
+    async fn read_item(&mut self) -> Result<Option<T>, RssError> {
+        loop {
+            match self.advance().await? {
+                Step::Item(item) => return Ok(Some(item)),
+                Step::Eof => return Ok(None),
+                Step::ItemStarted | Step::Continue => {}
+            }
+        }
+    }
+
+    /// Like `next`, but also returns the item's markup from its start tag to its
+    /// end tag exactly as it was read (after transcoding to UTF-8). The markup is
+    /// `None` unless `RssParserBuilder::capture_raw_items` is on.
+    pub async fn next_with_raw(&mut self) -> Result<Option<(T, Option<String>)>, RssError> {
+        let item = self.next().await?;
+        Ok(item.map(|item| (item, self.raw_item.take())))
+    }
+
+    /// Builds the feed metadata, reading ahead up to the first item if needed.
+    ///
+    /// Channel elements that appear after the items are only included once
+    /// the parser has read past them.
+    pub async fn channel<C: GradualRssChannel>(&mut self) -> Result<C, RssError> {
+        while !self.item_seen && self.peeked.is_none() {
+            match self.advance().await? {
+                Step::Item(item) => self.hold(item),
+                Step::ItemStarted | Step::Eof => break,
+                Step::Continue => {}
+            }
+        }
+
+        let mut channel = C::init();
+        for node in &self.channel_nodes {
+            channel.populate(node.clone());
+        }
+        Ok(channel)
+    }
+
+    /// Reads only the feed metadata, stopping at the first item's start tag,
+    /// and drops the parser without reading the rest. For previews and
+    /// title refreshes; channel elements after the items are missed.
+    pub async fn parse_channel_only<C: GradualRssChannel>(mut self) -> Result<C, RssError> {
+        self.channel().await
+    }
+
+    // `step`, except that malformed input discards the item it occurred in,
+    // which is skipped up to the next item start tag. Lenient mode records the
+    // error as a warning and carries on; strict mode returns it, and calling
+    // `next` again resumes with the following item. Guard and I/O errors end
+    // the feed: they are returned once, and the feed reads as finished after.
+    async fn advance(&mut self) -> Result<Step<T>, RssError> {
+        loop {
+            if self.failed {
+                return Ok(Step::Eof);
+            }
+            if let Some(err) = self.guard_error().or_else(|| self.encoding_error()) {
+                self.failed = true;
+                return Err(err);
+            }
+            match self.step().await {
+                Err(RssError::Io(err)) => {
+                    self.failed = true;
+                    let err = self.guard_error().unwrap_or(RssError::Io(err));
+                    if let Some(metrics) = &self.options.metrics {
+                        metrics.0.parse_error(&err);
+                    }
+                    return Err(err);
+                }
+                Err(err) => {
+                    if let Some(metrics) = &self.options.metrics {
+                        metrics.0.parse_error(&err);
+                    }
+                    let discarded = self.processing.take().is_some();
+                    if discarded || matches!(err, RssError::InvalidItem(_)) {
+                        self.items_skipped += 1;
+                    }
+                    self.resyncing |= discarded;
+                    self.raw_item_start = None;
+                    match self.options.mode {
+                        ParseMode::Lenient => self.warnings.push(err),
+                        ParseMode::Strict => return Err(err),
+                    }
+                }
+                result => return result,
+            }
+        }
+    }
+
+    // Drops whatever the discarded item left open, so the new item sits where
+    // the broken one did even if the broken one was never closed.
+    fn resync(&mut self) {
+        if self.resyncing {
+            self.node_stack.truncate(self.item_depth);
+            self.resyncing = false;
+        }
+    }
+
+    async fn step(&mut self) -> Result<Step<T>, RssError> {
+        if let Some(max) = self.options.max_item_size
+            && self.processing.is_some()
+            && self.reader.buffer_position() - self.item_start > max
+        {
+            return Err(RssError::LimitExceeded {
+                limit: "item size",
+                max,
+                position: self.reader.buffer_position(),
+            });
+        }
+
+        let before = self.reader.buffer_position();
+        if self.options.track_lines
+            && let Some(bytes) = self.reader.get_ref().get_ref().slice(self.lines_at, before)
+        {
+            (self.line, self.line_start) = count_lines(bytes, self.lines_at, self.line, self.line_start);
+            self.lines_at = before;
+        }
+        let keep = self
+            .raw_item_start
+            .into_iter()
+            .chain(self.node_stack.iter().find_map(|node| node.inner_start))
+            .min()
+            .unwrap_or(before);
+        let recorder = self.reader.get_mut().get_mut();
+        recorder.discard_before(keep);
+        recorder.get_mut().checkpoint(before);
+
+        self.buf.clear();
+        #[cfg(feature = "tokio")]
+        let event = self.reader.read_event_into_async(&mut self.buf).await;
+        #[cfg(not(feature = "tokio"))]
+        let event = self.reader.read_event_into(&mut self.buf);
+        let event = match event {
+            Ok(event) => event,
+            Err(err) => {
+                // quick-xml closes the element anyway, so keep the stack in step.
+                if let quick_xml::Error::IllFormed(IllFormedError::MismatchedEndTag { .. }) = err {
+                    self.close();
+                }
+                return Err(RssError::from_xml(err, self.reader.error_position()));
+            }
+        };
+
+        match event {
+            Event::Start(_) if self.skipped_depth > 0 => self.skipped_depth += 1,
+            Event::Start(_) if self.options.max_depth.is_some_and(|max| self.node_stack.len() >= max) => {
+                self.skipped_depth = 1;
+                return Err(RssError::LimitExceeded {
+                    limit: "depth",
+                    max: self.options.max_depth.unwrap_or_default() as u64,
+                    position: before,
+                });
+            }
+            Event::Empty(_) | Event::CData(_) | Event::Text(_) | Event::GeneralRef(_) if self.skipped_depth > 0 => {}
+            Event::End(_) if self.skipped_depth > 0 => self.skipped_depth -= 1,
+            Event::Start(start) => {
+                let mut node = Self::resolve(&self.reader, &start, &self.options, &mut self.tags, self.node_stack.last(), self.spare_nodes.pop());
+                self.inherit_namespace(&mut node);
+                // An element of the item tag inside an item is part of it.
+                let started = node.tag == self.options.item_tag && self.processing.is_none();
+                if self.options.capture_inner_xml && (started || self.processing.is_some() || self.channel_depth().is_some()) {
+                    node.inner_start = Some(self.reader.buffer_position());
+                }
+                if started {
+                    self.resync();
+                    self.processing = Some(T::init());
+                    self.item_depth = self.node_stack.len();
+                    self.item_seen = true;
+                    self.item_start = self.reader.buffer_position();
+                    self.item_started = Instant::now();
+                    self.item_location = Some(self.locate(before));
+                    if self.options.capture_raw_items {
+                        self.raw_item_start = Some(before);
                     }
+                }
 
-                    node_stacks.push(XmlNode::new(tag));
+                self.node_stack.push(node);
+                if started {
+                    return Ok(Step::ItemStarted);
                 }
-                Event::End(name) => {
-                    let tag = String::from_utf8_lossy(name.as_ref()).to_lowercase();
-                    if tag == XML_KEY_ITEM {
-                        break;
+            }
+            Event::Empty(start) => {
+                let mut node = Self::resolve(&self.reader, &start, &self.options, &mut self.tags, self.node_stack.last(), self.spare_nodes.pop());
+                self.inherit_namespace(&mut node);
+                if node.tag == self.options.item_tag && self.processing.is_none() {
+                    self.resync();
+                    self.item_seen = true;
+                    self.item_started = Instant::now();
+                    self.item_location = Some(self.locate(before));
+                    if self.options.capture_raw_items {
+                        self.raw_item = self.recorded(before, self.reader.buffer_position());
                     }
-                    if let (Some(node), Some(raw_item)) = (node_stacks.pop(), processing.as_mut()) {
-                        raw_item.populate(node);
+                    let mut item = T::init();
+                    let mut node = node;
+                    node.path.clear();
+                    if T::BORROWED {
+                        item.populate_ref(XmlNodeRef::new(&node, "", &self.options));
+                        self.recycle(node);
+                    } else {
+                        item.populate(node);
                     }
+                    return self.complete(item);
                 }
-                Event::CData(content) => {
-                    if let Some(item) = node_stacks.last_mut() {
-                        item.cdata = content.decode().ok().map(|s| s.into_owned());
+                self.deliver(node);
+            }
+            Event::End(name) => {
+                let is_item = self.options.name_is(name.as_ref(), &self.options.item_tag);
+                // The item element itself is delivered last so its attributes
+                // (e.g. RSS 1.0's `rdf:about`) are available too.
+                if let Some(mut node) = self.node_stack.pop() {
+                    if let Some(start) = node.inner_start {
+                        node.inner_xml = self.recorded(start, before);
                     }
+                    self.deliver(node);
                 }
-                Event::Text(cmt) => {
-                    if let Some(item) = node_stacks.last_mut() {
-                        item.value = cmt.decode().ok().map(|s| s.into_owned())
+                if is_item
+                    && self.node_stack.len() == self.item_depth
+                    && let Some(item) = self.processing.take()
+                {
+                    if let Some(start) = self.raw_item_start.take() {
+                        self.raw_item = self.recorded(start, self.reader.buffer_position());
                     }
+                    return self.complete(item);
                 }
-                Event::Eof => break,
-                _ => {}
             }
+            // Adjacent sections are joined, as are CDATA chunks split by text.
+            Event::CData(content) => {
+                if let Some(item) = self.node_stack.last_mut()
+                    && !item.truncated
+                {
+                    item.cdata.get_or_insert_with(|| self.spare_text.pop().unwrap_or_default()).push_str(&content.decode()?);
+                }
+                self.check_element_size(before)?;
+            }
+            Event::Text(cmt) => {
+                if let Some(item) = self.node_stack.last_mut()
+                    && !item.truncated
+                {
+                    item.value.get_or_insert_with(|| self.spare_text.pop().unwrap_or_default()).push_str(&cmt.decode()?);
+                }
+                self.check_element_size(before)?;
+            }
+            // Text is split around references, so they're appended to whatever
+            // came before. Unknown entities are kept as written.
+            Event::GeneralRef(entity) => {
+                let name = entity.decode()?;
+                let resolved = match self.options.decode_entities {
+                    true if entity.is_char_ref() => entity.resolve_char_ref().ok().flatten().map(String::from),
+                    true => resolve_html5_entity(&name).map(String::from),
+                    false => None,
+                };
+                if let Some(item) = self.node_stack.last_mut()
+                    && !item.truncated
+                {
+                    let value = item.value.get_or_insert_with(String::new);
+                    match resolved {
+                        Some(text) => value.push_str(&text),
+                        None => {
+                            value.push('&');
+                            value.push_str(&name);
+                            value.push(';');
+                        }
+                    }
+                }
+                self.check_element_size(before)?;
+            }
+            Event::DocType(_) => self.check_doctype(before)?,
+            Event::Eof => {
+                if self.processing.take().is_some() {
+                    return Err(RssError::UnexpectedEof);
+                }
+                return Ok(Step::Eof);
+            }
+            _ => {}
+        }
+        Ok(Step::Continue)
+    }
+
+    // Builds a node for a start tag, resolving its prefix against the namespaces
+    // in scope and its `xml:base` against the parent's, and inheriting `xml:lang`.
+    fn resolve(
+        reader: &NsReader<Source<R>>,
+        start: &BytesStart,
+        options: &ParserOptions,
+        tags: &mut TagTable,
+        parent: Option<&XmlNode>,
+        blank: Option<XmlNode>,
+    ) -> XmlNode {
+        let mut node = XmlNode::fill(blank.unwrap_or_else(XmlNode::blank), start, options, tags);
+        node.base = child_base(parent.and_then(|parent| parent.base.as_deref()), node.attr("xml:base"));
+        node.lang = match node.attr("xml:lang") {
+            Some(lang) => Some(lang.to_string()),
+            None => parent.and_then(|parent| parent.lang.clone()),
+        };
+        node.preserve_space = match node.attr("xml:space") {
+            Some(space) => space == "preserve",
+            None => parent.is_some_and(|parent| parent.preserve_space),
+        };
+        // `local_name` is already set from the normalized tag.
+        let (namespace, _) = reader.resolve_element(start.name());
+        if let ResolveResult::Bound(namespace) = namespace {
+            node.namespace = Some(String::from_utf8_lossy(namespace.as_ref()).into_owned());
         }
-        processing
+        node
+    }
+
+    // After `resume`, prefixes declared on the restored ancestors are unknown
+    // to the reader and are looked up here instead.
+    fn inherit_namespace(&self, node: &mut XmlNode) {
+        if node.namespace.is_some() || self.inherited_namespaces.is_empty() {
+            return;
+        }
+        let prefix = node.tag.split_once(':').map_or("", |(prefix, _)| prefix);
+        node.namespace = self
+            .inherited_namespaces
+            .iter()
+            .find(|(declared, _)| declared.eq_ignore_ascii_case(prefix))
+            .map(|(_, namespace)| namespace.clone());
+    }
+
+    // Hands a completed element to the item being built, or records it as channel
+    // metadata when it sits directly under `<channel>` / `<feed>`.
+    fn deliver(&mut self, mut node: XmlNode) {
+        if T::BORROWED
+            && let Some(item) = self.processing.as_mut()
+        {
+            self.path.clear();
+            if let Some(ancestors) = self.node_stack.get(self.item_depth + 1..) {
+                push_relative_path(&mut self.path, ancestors, &node.tag);
+            }
+            item.populate_ref(XmlNodeRef::new(&node, &self.path, &self.options));
+            self.recycle(node);
+            return;
+        }
+
+        if !node.preserve_space {
+            if self.options.collapse_whitespace {
+                node.value = collapsed(node.value);
+            }
+            if self.options.trim_whitespace || self.options.collapse_whitespace {
+                node.value = trimmed(node.value);
+                node.cdata = trimmed(node.cdata);
+            }
+        }
+
+        if let Some(raw_item) = self.processing.as_mut() {
+            node.path = match self.node_stack.get(self.item_depth + 1..) {
+                Some(ancestors) => relative_path(ancestors, &node.tag),
+                None => String::new(),
+            };
+            raw_item.populate(node);
+        } else if let Some(channel) = self.channel_depth() {
+            // Leftovers of a discarded item are not channel metadata.
+            let ancestors = &self.node_stack[channel + 1..];
+            if node.tag != self.options.item_tag && ancestors.iter().all(|ancestor| ancestor.tag != self.options.item_tag) {
+                node.path = relative_path(ancestors, &node.tag);
+                self.channel_nodes.push(node);
+                self.channel_info = None;
+            }
+        }
+    }
+
+    fn recycle(&mut self, mut node: XmlNode) {
+        for mut text in node.reset().into_iter().flatten() {
+            if self.spare_text.len() < SPARE_LIMIT {
+                text.clear();
+                self.spare_text.push(text);
+            }
+        }
+        if self.spare_nodes.len() < SPARE_LIMIT {
+            self.spare_nodes.push(node);
+        }
+    }
+
+    fn close(&mut self) {
+        match self.skipped_depth {
+            0 => drop(self.node_stack.pop()),
+            _ => self.skipped_depth -= 1,
+        }
+    }
+
+    // Text that arrives in many small events (around entity references, say)
+    // slips past the reader's check, so the accumulated value is checked too.
+    // Lenient mode keeps what fits and records a warning.
+    fn check_element_size(&mut self, position: u64) -> Result<(), RssError> {
+        let (Some(max), Some(node)) = (self.options.max_element_size, self.node_stack.last_mut()) else {
+            return Ok(());
+        };
+        let size = node.value.as_ref().map_or(0, String::len) + node.cdata.as_ref().map_or(0, String::len);
+        if size as u64 <= max {
+            return Ok(());
+        }
+
+        let error = RssError::LimitExceeded {
+            limit: "element size",
+            max,
+            position,
+        };
+        if self.options.mode == ParseMode::Strict {
+            node.value = None;
+            node.cdata = None;
+            return Err(error);
+        }
+        let mut budget = usize::try_from(max).unwrap_or(usize::MAX);
+        truncate(&mut node.value, &mut budget);
+        truncate(&mut node.cdata, &mut budget);
+        node.truncated = true;
+        self.warnings.push(error);
+        Ok(())
+    }
+
+    // A declared encoding that isn't supported is read as ASCII and noted once
+    // as a warning. Strict mode stops once a byte that isn't ASCII has been
+    // read, rather than hand out text with it replaced.
+    fn encoding_error(&mut self) -> Option<RssError> {
+        let decoder = self.reader.get_ref().get_ref().get_ref().get_ref();
+        decoder.encoding()?;
+        let label = decoder.unknown_label()?.to_string();
+        let replaced = decoder.replaced();
+        if !self.encoding_checked {
+            self.encoding_checked = true;
+            self.warnings.push(RssError::UnknownEncoding(label.clone()));
+        }
+        (replaced && self.options.mode == ParseMode::Strict).then_some(RssError::UnknownEncoding(label))
+    }
+
+    // A read stopped by the guard cannot be resumed, so it ends the feed.
+    fn guard_error(&self) -> Option<RssError> {
+        let position = self.reader.buffer_position();
+        let error = match self.reader.get_ref().get_ref().get_ref().tripped()? {
+            Trip::ElementSize => RssError::LimitExceeded {
+                limit: "element size",
+                max: self.options.max_element_size.unwrap_or_default(),
+                position,
+            },
+            Trip::FeedSize => RssError::FeedTooLarge {
+                max: self.options.max_feed_size.unwrap_or_default(),
+            },
+            Trip::Stalled => RssError::Stalled {
+                timeout: self.options.stall_timeout.unwrap_or_default(),
+                position,
+            },
+        };
+        Some(error)
+    }
+
+    fn check_doctype(&self, position: u64) -> Result<(), RssError> {
+        match self.options.allow_doctype {
+            true => Ok(()),
+            false => Err(RssError::DoctypeNotAllowed { position }),
+        }
+    }
+
+    fn complete(&mut self, mut item: T) -> Result<Step<T>, RssError> {
+        let channel_nodes = &self.channel_nodes;
+        let channel = self.channel_info.get_or_insert_with(|| {
+            let mut channel = ChannelInfo::init();
+            for node in channel_nodes {
+                channel.populate(node.clone());
+            }
+            channel
+        });
+        item.with_channel(channel);
+        let item = item.finish().map_err(RssError::InvalidItem)?;
+        if let Some(metrics) = &self.options.metrics {
+            metrics.0.item_parsed(self.item_started.elapsed());
+        }
+        Ok(Step::Item(item))
+    }
+
+    fn recorded(&self, start: u64, end: u64) -> Option<String> {
+        let bytes = self.reader.get_ref().get_ref().slice(start, end)?;
+        Some(String::from_utf8_lossy(bytes).into_owned())
+    }
+
+    fn channel_depth(&self) -> Option<usize> {
+        self.node_stack
+            .iter()
+            .position(|parent| parent.tag_is(XML_KEY_CHANNEL) || parent.tag_is(XML_KEY_FEED))
     }
 }
 
-impl <T: GradualRssItem + Unpin, R: AsyncRead + Unpin> Stream for RssParser<T, R> {
-    type Item = T;
+fn relative_path(ancestors: &[XmlNode], tag: &str) -> String {
+    let mut path = String::new();
+    push_relative_path(&mut path, ancestors, tag);
+    path
+}
 
-    fn poll_next(self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> std::task::Poll<Option<Self::Item>> {
-        let fut = self.get_mut().next();
-        let mut pinned_future = Box::pin(fut);
-        pinned_future.as_mut().poll(cx)
+fn push_relative_path(path: &mut String, ancestors: &[XmlNode], tag: &str) {
+    for ancestor in ancestors {
+        path.push_str(&ancestor.tag);
+        path.push('/');
+    }
+    path.push_str(tag);
+}
+
+// Advances a line count from `offset` over `bytes`, returning the line and the
+// offset it starts at.
+fn count_lines(bytes: &[u8], offset: u64, mut line: u64, mut line_start: u64) -> (u64, u64) {
+    for (index, _) in bytes.iter().enumerate().filter(|(_, byte)| **byte == b'\n') {
+        line += 1;
+        line_start = offset + index as u64 + 1;
+    }
+    (line, line_start)
+}
+
+// Cuts `text` down to `budget` bytes (on a character boundary) and takes what
+// it kept out of the budget.
+fn truncate(text: &mut Option<String>, budget: &mut usize) {
+    if let Some(text) = text {
+        let mut end = text.len().min(*budget);
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+        *budget -= end;
+    }
+}
+
+fn trimmed(text: Option<String>) -> Option<String> {
+    text.map(|text| text.trim().to_string()).filter(|text| !text.is_empty())
+}
+
+fn collapsed(text: Option<String>) -> Option<String> {
+    text.map(|text| text.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+impl RssParser<(), ()> {
+    /// Starts configuring a parser; finish with `RssParserBuilder::build`.
+    pub fn builder() -> RssParserBuilder {
+        RssParserBuilder::new()
     }
 }
 
 // Convenience constructors for common use cases
+#[cfg(feature = "tokio")]
 impl<T: GradualRssItem> RssParser<T, File> {
     pub async fn from_file(path: &str) -> std::io::Result<Self> {
         let file = File::open(path).await?;
@@ -105,14 +1215,115 @@ impl<T: GradualRssItem> RssParser<T, File> {
     }
 }
 
+#[cfg(feature = "tokio")]
 impl<T: GradualRssItem> RssParser<T, TcpStream> {
     pub async fn from_tcp(stream: TcpStream) -> std::io::Result<Self> {
         Self::new(stream).await
     }
 }
 
+#[cfg(feature = "tokio")]
+impl<T: GradualRssItem> RssParser<T, BoxedConnection> {
+    /// Connects to `host:port`, runs the TLS handshake with `tls` and reads a
+    /// feed sent over the connection, as `from_tcp` does without TLS.
+    pub async fn connect_tls(host: &str, port: u16, tls: &dyn TlsConnector) -> std::io::Result<Self> {
+        let stream = TcpStream::connect((host, port)).await?;
+        Self::new(tls.connect(host, stream).await?).await
+    }
+}
 
-#[cfg(test)]
+#[cfg(all(unix, feature = "tokio"))]
+impl<T: GradualRssItem> RssParser<T, UnixStream> {
+    /// Parses a feed arriving over a Unix domain socket, e.g. from a local
+    /// proxy or sidecar.
+    pub async fn from_unix(stream: UnixStream) -> std::io::Result<Self> {
+        Self::new(stream).await
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<T: GradualRssItem> RssParser<T, Stdin> {
+    /// Parses standard input, for shell pipelines: `curl -s $FEED | mytool`.
+    pub async fn from_stdin() -> std::io::Result<Self> {
+        Self::new(tokio::io::stdin()).await
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<'a, T: GradualRssItem> RssParser<T, &'a [u8]> {
+    /// Parses a feed already in memory.
+    pub async fn from_slice(input: &'a [u8]) -> std::io::Result<Self> {
+        Self::new(input).await
+    }
+
+    pub async fn from_str(input: &'a str) -> std::io::Result<Self> {
+        Self::new(input.as_bytes()).await
+    }
+}
+
+/// Parses every item of a feed already in memory, for tests and small feeds.
+#[cfg(feature = "tokio")]
+pub async fn parse_all<T: GradualRssItem>(input: impl AsRef<[u8]>) -> Result<Vec<T>, RssError> {
+    let mut parser = RssParser::<T, _>::from_slice(input.as_ref()).await?;
+    let mut items = Vec::new();
+    while let Some(item) = parser.next().await? {
+        items.push(item);
+    }
+    Ok(items)
+}
+
+#[cfg(feature = "tokio")]
+impl<T, S, B, E> RssParser<T, ByteStream<S, B>>
+where
+    T: GradualRssItem,
+    S: Stream<Item = Result<B, E>> + Unpin,
+    B: AsRef<[u8]>,
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    /// Parses a stream of byte chunks, such as a reqwest, hyper or axum body
+    /// (`Bytes`, `Vec<u8>` or anything else that is `AsRef<[u8]>`). A stream
+    /// error ends the feed with `RssError::Io`.
+    pub async fn from_stream(stream: S) -> std::io::Result<Self> {
+        Self::new(ByteStream::new(stream)).await
+    }
+}
+
+#[cfg(feature = "http")]
+impl<T: GradualRssItem> RssParser<T, HttpBody> {
+    /// Fetches `url` with a GET request (following redirects) and parses the
+    /// response body as it arrives. `https://` feeds need a `TlsConnector`,
+    /// passed in the `FetchOptions` of `from_url_with`.
+    pub async fn from_url(url: &str) -> Result<Self, RssError> {
+        match Self::from_url_with(url, &FetchOptions::default()).await? {
+            FetchResult::Modified { parser, .. } => Ok(parser),
+            FetchResult::NotModified => Err(RssError::Http(HttpError::Status(304))),
+        }
+    }
+
+    /// Like `from_url`, but sends the validators in `options` so an unchanged
+    /// feed comes back as `FetchResult::NotModified` without being downloaded.
+    pub async fn from_url_with(url: &str, options: &FetchOptions) -> Result<FetchResult<Self>, RssError> {
+        Ok(Self::fetch(url, options).await?.0)
+    }
+
+    // `from_url_with`, also returning how long the response is fresh for.
+    pub(crate) async fn fetch(url: &str, options: &FetchOptions) -> Result<(FetchResult<Self>, Option<std::time::Duration>), RssError> {
+        let (head, body) = crate::http::fetch(url, options).await?;
+        let freshness = head.freshness();
+        if head.status == 304 {
+            return Ok((FetchResult::NotModified, freshness));
+        }
+        let result = FetchResult::Modified {
+            etag: head.header("etag").map(String::from),
+            last_modified: head.header("last-modified").map(String::from),
+            parser: Self::new(body).await?,
+        };
+        Ok((result, freshness))
+    }
+}
+
+
+#[cfg(all(test, feature = "tokio"))]
 mod tests {
     use super::*;
     use std::io::Cursor;
@@ -180,7 +1391,7 @@ mod tests {
         let cursor = Cursor::new(SAMPLE_RSS.as_bytes());
         let mut parser = RssParser::<TestRssItem, _>::new(cursor).await.unwrap();
 
-        let item = parser.next().await;
+        let item = parser.next().await.unwrap();
         assert!(item.is_some());
 
         let item = item.unwrap();
@@ -196,20 +1407,20 @@ mod tests {
         let mut parser = RssParser::<TestRssItem, _>::new(cursor).await.unwrap();
 
         // First item
-        let item1 = parser.next().await;
+        let item1 = parser.next().await.unwrap();
         assert!(item1.is_some());
         let item1 = item1.unwrap();
         assert_eq!(item1.title, Some("First Item".to_string()));
 
         // Second item with CDATA
-        let item2 = parser.next().await;
+        let item2 = parser.next().await.unwrap();
         assert!(item2.is_some());
         let item2 = item2.unwrap();
         assert_eq!(item2.title, Some("Second Item".to_string()));
         assert_eq!(item2.description, Some("Description with <b>HTML</b> content".to_string()));
 
         // No more items
-        let item3 = parser.next().await;
+        let item3 = parser.next().await.unwrap();
         assert!(item3.is_none());
     }
 
@@ -218,7 +1429,7 @@ mod tests {
         let cursor = Cursor::new(EMPTY_RSS.as_bytes());
         let mut parser = RssParser::<TestRssItem, _>::new(cursor).await.unwrap();
 
-        let item = parser.next().await;
+        let item = parser.next().await.unwrap();
         assert!(item.is_none());
     }
 
@@ -228,10 +1439,305 @@ mod tests {
         let cursor = Cursor::new(invalid_xml.as_bytes());
         let mut parser = RssParser::<TestRssItem, _>::new(cursor).await.unwrap();
 
-        let item = parser.next().await;
+        let item = parser.next().await.unwrap();
         assert!(item.is_none());
     }
 
+    #[tokio::test]
+    async fn test_malformed_xml_reports_error() {
+        let broken = r#"<rss><channel>
+            <item><title>First</title></item>
+            <item><title>Second</wrong></item>
+        </channel></rss>"#;
+        let cursor = Cursor::new(broken.as_bytes());
+        let mut parser = RssParser::<TestRssItem, _>::new(cursor).await.unwrap();
+
+        let first = parser.next().await.unwrap().unwrap();
+        assert_eq!(first.title, Some("First".to_string()));
+
+        match parser.next().await {
+            Err(RssError::Xml { position, .. }) => assert!(position > 0),
+            other => panic!("expected XML error, got {:?}", other.map(|item| item.is_some())),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resumes_after_malformed_item() {
+        let broken = r#"<rss><channel>
+            <item><title>First</title></item>
+            <item><title>Second</wrong><link>https://example.com/2</link></item>
+            <item><title>Bad & unescaped</title></item>
+            <item><title>Third</title></item>
+        </channel></rss>"#;
+        let cursor = Cursor::new(broken.as_bytes());
+        let mut parser = RssParser::<TestRssItem, _>::new(cursor).await.unwrap();
+
+        assert_eq!(parser.next().await.unwrap().unwrap().title.as_deref(), Some("First"));
+        assert!(matches!(parser.next().await, Err(RssError::Xml { .. })));
+        assert!(matches!(parser.next().await, Err(RssError::Xml { .. })));
+
+        // Nothing from the broken items leaks into the next one.
+        let third = parser.next().await.unwrap().unwrap();
+        assert_eq!(third.title.as_deref(), Some("Third"));
+        assert!(third.link.is_none());
+        assert!(parser.next().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_resyncs_after_unclosed_item() {
+        use tokio_stream::StreamExt;
+
+        let broken = r#"<rss><channel>
+            <item><title>Open<description>never closed</title></item>
+            <item><title>Next</title><description>Fine</description></item>
+        </channel></rss>"#;
+        let cursor = Cursor::new(broken.as_bytes());
+        let parser: RssParser<TestRssItem, _> = RssParser::builder()
+            .mode(ParseMode::Lenient)
+            .build(cursor)
+            .await
+            .unwrap();
+
+        let items: Vec<TestRssItem> = parser.into_stream().collect::<Result<_, _>>().await.unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].title.as_deref(), Some("Next"));
+        assert_eq!(items[0].description.as_deref(), Some("Fine"));
+    }
+
+    #[tokio::test]
+    async fn test_truncated_item_is_unexpected_eof() {
+        let truncated = r#"<rss><channel><item><title>Cut off</title>"#;
+        let cursor = Cursor::new(truncated.as_bytes());
+        let mut parser = RssParser::<TestRssItem, _>::new(cursor).await.unwrap();
+
+        assert!(matches!(parser.next().await, Err(RssError::UnexpectedEof)));
+    }
+
+    #[tokio::test]
+    async fn test_stop_when() {
+        // Everything after the known item is left unread, malformed or not.
+        let feed = r#"<rss><channel><item><title>New</title></item><item><title>Seen</title></item><item><title>Old</title></item><broken"#;
+        let parser = RssParser::<TestRssItem, _>::new(Cursor::new(feed.as_bytes())).await.unwrap();
+        let mut parser = parser.stop_when(|item| item.title.as_deref() == Some("Seen"));
+        assert_eq!(parser.next().await.unwrap().unwrap().title.as_deref(), Some("New"));
+        assert!(parser.next().await.unwrap().is_none());
+        assert!(parser.next().await.unwrap().is_none());
+
+        let mut parser = RssParser::<TestRssItem, _>::new(Cursor::new(feed.as_bytes())).await.unwrap();
+        let seen = |item: &TestRssItem| item.title.as_deref() == Some("New");
+        assert!(parser.next_until(seen).await.unwrap().is_none());
+        assert!(parser.next().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_peek() {
+        let feed = "<rss><channel><item><title>One</title></item><item><title>Two</title></item></channel></rss>";
+        let mut parser = RssParser::<TestRssItem, _>::new(Cursor::new(feed.as_bytes())).await.unwrap();
+        assert_eq!(parser.peek().await.unwrap().unwrap().title.as_deref(), Some("One"));
+        assert_eq!(parser.peek().await.unwrap().unwrap().title.as_deref(), Some("One"));
+        assert_eq!(parser.next().await.unwrap().unwrap().title.as_deref(), Some("One"));
+        assert_eq!(parser.peek().await.unwrap().unwrap().title.as_deref(), Some("Two"));
+        assert_eq!(parser.next().await.unwrap().unwrap().title.as_deref(), Some("Two"));
+        assert!(parser.peek().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_next_chunk() {
+        let feed = "<rss><channel><item><title>1</title></item><item><title>2</title></item><item><title>3</title></item>\
+            <item><title>4</wrong></item><item><title>5</title></item></channel></rss>";
+        let mut parser = RssParser::<TestRssItem, _>::new(Cursor::new(feed.as_bytes())).await.unwrap();
+        let titles = |chunk: Vec<TestRssItem>| chunk.into_iter().map(|item| item.title.unwrap()).collect::<Vec<_>>();
+        assert_eq!(titles(parser.next_chunk(2).await.unwrap()), vec!["1", "2"]);
+        assert_eq!(titles(parser.next_chunk(2).await.unwrap()), vec!["3"]);
+        assert!(matches!(parser.next_chunk(2).await, Err(RssError::Xml { .. })));
+        assert_eq!(titles(parser.next_chunk(2).await.unwrap()), vec!["5"]);
+        assert!(parser.next_chunk(2).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_stats() {
+        let feed = "<rss><channel><item><title>1</title></item><item><title>2</wrong></item><item><title>3</title></item></channel></rss>";
+        let mut parser: RssParser<TestRssItem, _> = RssParser::builder()
+            .mode(ParseMode::Lenient)
+            .build(Cursor::new(feed.as_bytes()))
+            .await
+            .unwrap();
+        while parser.next().await.unwrap().is_some() {}
+        let stats = parser.stats();
+        assert_eq!(stats.bytes_read, feed.len() as u64);
+        assert_eq!((stats.items, stats.items_skipped, stats.warnings), (2, 1, 1));
+    }
+
+    #[tokio::test]
+    async fn test_on_progress() {
+        let reports = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = reports.clone();
+        let feed = "<rss><channel><item><title>A</title></item><item><title>B</title></item></channel></rss>";
+        let mut parser: RssParser<TestRssItem, _> = RssParser::builder()
+            .on_progress(move |stats| sink.lock().unwrap().push((stats.items, stats.bytes_read)))
+            .build(feed.as_bytes())
+            .await
+            .unwrap();
+        while parser.next().await.unwrap().is_some() {}
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.iter().map(|(items, _)| *items).collect::<Vec<_>>(), vec![1, 2, 2]);
+        assert_eq!(reports.last().unwrap().1, feed.len() as u64);
+    }
+
+    #[tokio::test]
+    async fn test_spawn() {
+        let feed = "<rss><channel><item><title>A</title></item><item><title>B</title></item></wrong></rss>";
+        let parser = RssParser::<TestRssItem, _>::new(Cursor::new(feed.as_bytes())).await.unwrap();
+        let mut receiver = parser.spawn(1);
+        assert_eq!(receiver.recv().await.unwrap().unwrap().title.as_deref(), Some("A"));
+        assert_eq!(receiver.recv().await.unwrap().unwrap().title.as_deref(), Some("B"));
+        assert!(matches!(receiver.recv().await, Some(Err(RssError::Xml { .. }))));
+        assert!(receiver.recv().await.is_none());
+
+        // The same results as `into_stream`, which reads on past a malformed item.
+        use tokio_stream::StreamExt;
+        let feed = "<rss><channel><item><title>A</wrong></item><item><title>B</title></item></channel></rss>";
+        let parser = RssParser::<TestRssItem, _>::new(Cursor::new(feed.as_bytes())).await.unwrap();
+        let spawned: Vec<_> = tokio_stream::wrappers::ReceiverStream::new(parser.spawn(1)).collect().await;
+        let parser = RssParser::<TestRssItem, _>::new(Cursor::new(feed.as_bytes())).await.unwrap();
+        let streamed: Vec<_> = parser.into_stream().collect().await;
+        let summary = |results: &[Result<TestRssItem, RssError>]| {
+            results.iter().map(|result| result.as_ref().map(|item| item.title.clone()).map_err(|err| err.is_fatal())).collect::<Vec<_>>()
+        };
+        assert_eq!(summary(&spawned), summary(&streamed));
+        assert_eq!(summary(&spawned).last(), Some(&Ok(Some("B".to_string()))));
+    }
+
+    #[tokio::test]
+    async fn test_populate_ref() {
+        use std::borrow::Cow;
+
+        #[derive(Default)]
+        struct Lengths(Vec<(String, Option<usize>, bool)>);
+
+        impl GradualRssItem for Lengths {
+            const BORROWED: bool = true;
+
+            fn init() -> Self {
+                Lengths::default()
+            }
+
+            fn populate(&mut self, _node: XmlNode) {
+                unreachable!("elements are borrowed");
+            }
+
+            fn populate_ref(&mut self, node: XmlNodeRef<'_>) {
+                let borrowed = matches!(node.value, Some(Cow::Borrowed(_)));
+                self.0.push((node.path.to_string(), node.text().map(str::len), borrowed));
+            }
+        }
+
+        let feed = "<rss><channel><item><title> Padded </title><author><name>A  B</name></author><empty/></item>\
+            <item><title>Short</title><author/></item><item/></channel></rss>";
+        let mut parser: RssParser<Lengths, _> =
+            RssParser::builder().collapse_whitespace(true).build(Cursor::new(feed.as_bytes())).await.unwrap();
+        let first = parser.next().await.unwrap().unwrap().0;
+        let path = |(path, _, _): &(String, Option<usize>, bool)| path.clone();
+        assert_eq!(first.iter().map(path).collect::<Vec<_>>(), vec!["title", "author/name", "author", "empty", ""]);
+        assert_eq!(first[0], ("title".to_string(), Some(6), true));
+        assert_eq!(first[1], ("author/name".to_string(), Some(3), false));
+        // Reused buffers don't carry text over to elements without any.
+        let second = parser.next().await.unwrap().unwrap().0;
+        assert_eq!(second, vec![("title".to_string(), Some(5), true), ("author".to_string(), None, false), (String::new(), None, false)]);
+        assert_eq!(parser.next().await.unwrap().unwrap().0, vec![(String::new(), None, false)]);
+    }
+
+    #[tokio::test]
+    async fn test_from_str_and_parse_all() {
+        let feed = "<rss><channel><item><title>A</title></item><item><title>B</title></item></channel></rss>";
+        let mut parser = RssParser::<TestRssItem, _>::from_str(feed).await.unwrap();
+        assert_eq!(parser.next().await.unwrap().unwrap().title.as_deref(), Some("A"));
+
+        let items: Vec<TestRssItem> = parse_all(feed).await.unwrap();
+        assert_eq!(items.iter().map(|item| item.title.as_deref().unwrap()).collect::<Vec<_>>(), vec!["A", "B"]);
+        assert!(parse_all::<TestRssItem>(b"<rss><channel><item>".to_vec()).await.is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_from_unix() {
+        use tokio::io::AsyncWriteExt;
+
+        let (mut sender, receiver) = UnixStream::pair().unwrap();
+        tokio::spawn(async move {
+            sender.write_all(b"<rss><channel><item><title>Local</title></item></channel></rss>").await.unwrap();
+        });
+        let mut parser = RssParser::<TestRssItem, _>::from_unix(receiver).await.unwrap();
+        assert_eq!(parser.next().await.unwrap().unwrap().title.as_deref(), Some("Local"));
+        assert!(parser.next().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_from_stream() {
+        let chunks = ["<rss><channel><item><ti", "tle>Chunked</title></it", "", "em></channel></rss>"];
+        let stream = tokio_stream::iter(chunks.map(|chunk| Ok::<_, std::io::Error>(chunk.as_bytes().to_vec())));
+        let mut parser = RssParser::<TestRssItem, _>::from_stream(stream).await.unwrap();
+        assert_eq!(parser.next().await.unwrap().unwrap().title.as_deref(), Some("Chunked"));
+        assert!(parser.next().await.unwrap().is_none());
+
+        let failing = tokio_stream::iter(vec![Ok("<rss><channel><item>"), Err("connection reset")]);
+        let mut parser = RssParser::<TestRssItem, _>::from_stream(failing).await.unwrap();
+        assert!(matches!(parser.next().await, Err(RssError::Io(err)) if err.to_string() == "connection reset"));
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_and_resume() {
+        let feed = concat!(
+            "\u{FEFF}<rss xmlns:d=\"http://purl.org/dc/elements/1.1/\"><channel><title>Log</title>",
+            "<item><title>One</title></item>",
+            "<item><title>Two</title><d:creator>Ann</d:creator></item>",
+            "<item><title>Three</title></item>",
+            "</channel></rss>"
+        );
+        let mut parser = RssParser::<crate::RssItem, _>::from_str(feed).await.unwrap();
+        parser.next().await.unwrap().unwrap();
+        let checkpoint = parser.checkpoint().unwrap();
+        assert_eq!(checkpoint.items(), 1);
+        assert!(feed.as_bytes()[checkpoint.offset() as usize..].starts_with(b"<item><title>Two"));
+
+        let rest = &feed.as_bytes()[checkpoint.offset() as usize..];
+        let mut resumed = RssParser::<crate::RssItem, _>::resume(checkpoint, rest);
+        let two = resumed.next().await.unwrap().unwrap();
+        assert_eq!(two.title.as_deref(), Some("Two"));
+        assert_eq!(two.author.and_then(|author| author.name).as_deref(), Some("Ann"));
+        assert_eq!(resumed.channel::<ChannelInfo>().await.unwrap().title.as_deref(), Some("Log"));
+
+        // A checkpoint of the resumed parser still counts from the document start.
+        resumed.peek().await.unwrap();
+        let checkpoint = resumed.checkpoint().unwrap();
+        assert!(feed.as_bytes()[checkpoint.offset() as usize..].starts_with(b"<item><title>Three"));
+        let rest = &feed.as_bytes()[checkpoint.offset() as usize..];
+        let mut resumed = RssParser::<crate::RssItem, _>::resume(checkpoint, rest);
+        assert_eq!(resumed.next().await.unwrap().unwrap().title.as_deref(), Some("Three"));
+        assert!(resumed.next().await.unwrap().is_none());
+        assert_eq!(resumed.stats().items, 3);
+    }
+
+    #[tokio::test]
+    async fn test_into_inner() {
+        use tokio::io::AsyncReadExt;
+
+        let feed = "<rss><channel><item><title>1</title></item><item><title>2</title></item></channel></rss>NEXT";
+        let mut parser = RssParser::<TestRssItem, _>::new(Cursor::new(feed.as_bytes())).await.unwrap();
+        parser.next().await.unwrap().unwrap();
+        let (mut input, mut rest) = parser.into_inner();
+        input.read_to_end(&mut rest).await.unwrap();
+        assert_eq!(rest, b"<item><title>2</title></item></channel></rss>NEXT");
+    }
+
+    #[tokio::test]
+    async fn test_parse_channel_only() {
+        let feed = r#"<rss><channel><title>Preview</title><item><title>Never read"#;
+        let parser = RssParser::<TestRssItem, _>::new(Cursor::new(feed.as_bytes())).await.unwrap();
+        let channel: ChannelInfo = parser.parse_channel_only().await.unwrap();
+        assert_eq!(channel.title.as_deref(), Some("Preview"));
+    }
+
     #[tokio::test]
     async fn test_stream_implementation() {
         use tokio_stream::StreamExt;
@@ -239,12 +1745,28 @@ mod tests {
         let cursor = Cursor::new(SAMPLE_RSS.as_bytes());
         let parser = RssParser::<TestRssItem, _>::new(cursor).await.unwrap();
 
-        let items: Vec<TestRssItem> = parser.collect().await;
+        let items: Vec<TestRssItem> = parser.into_stream().collect::<Result<_, _>>().await.unwrap();
         assert_eq!(items.len(), 2);
         assert_eq!(items[0].title, Some("First Item".to_string()));
         assert_eq!(items[1].title, Some("Second Item".to_string()));
     }
 
+    #[tokio::test]
+    async fn test_stream_yields_errors() {
+        use tokio_stream::StreamExt;
+
+        let broken = r#"<rss><channel>
+            <item><title>First</title></item>
+            <item><title>Second</wrong></item>
+        </channel></rss>"#;
+        let cursor = Cursor::new(broken.as_bytes());
+        let mut stream = RssParser::<TestRssItem, _>::new(cursor).await.unwrap().into_stream();
+
+        let first = stream.next().await.unwrap().unwrap();
+        assert_eq!(first.title, Some("First".to_string()));
+        assert!(matches!(stream.next().await, Some(Err(RssError::Xml { .. }))));
+    }
+
     #[tokio::test]
     async fn test_from_file_convenience() {
         use std::io::Write;
@@ -262,7 +1784,7 @@ mod tests {
         assert!(parser.is_ok());
         let mut parser = parser.unwrap();
 
-        let item = parser.next().await;
+        let item = parser.next().await.unwrap();
         assert!(item.is_some());
         assert_eq!(item.unwrap().title, Some("First Item".to_string()));
     }
@@ -279,6 +1801,345 @@ mod tests {
         assert_eq!(node.tag, "test");
         assert!(node.value.is_none());
         assert!(node.cdata.is_none());
+        assert!(node.attributes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_self_closing_elements() {
+        #[derive(Default)]
+        struct EnclosureItem {
+            title: Option<String>,
+            enclosure_url: Option<String>,
+            enclosure_type: Option<String>,
+        }
+
+        impl GradualRssItem for EnclosureItem {
+            fn init() -> Self {
+                EnclosureItem::default()
+            }
+
+            fn populate(&mut self, node: XmlNode) {
+                match node.tag.as_str() {
+                    "title" => self.title = node.value,
+                    "enclosure" => {
+                        self.enclosure_url = node.attr("url").map(String::from);
+                        self.enclosure_type = node.attr("type").map(String::from);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let rss = r#"<rss><channel>
+            <atom:link href="https://example.com/feed" rel="self"/>
+            <item>
+                <enclosure url="https://example.com/ep1.mp3" length="1024" type="audio/mpeg"/>
+                <title>Episode 1</title>
+            </item>
+            <item/>
+            <item><title>Episode 2</title></item>
+        </channel></rss>"#;
+
+        let cursor = Cursor::new(rss.as_bytes());
+        let mut parser = RssParser::<EnclosureItem, _>::new(cursor).await.unwrap();
+
+        let first = parser.next().await.unwrap().unwrap();
+        assert_eq!(first.title, Some("Episode 1".to_string()));
+        assert_eq!(first.enclosure_url, Some("https://example.com/ep1.mp3".to_string()));
+        assert_eq!(first.enclosure_type, Some("audio/mpeg".to_string()));
+
+        let empty = parser.next().await.unwrap().unwrap();
+        assert!(empty.title.is_none());
+        assert!(empty.enclosure_url.is_none());
+
+        let second = parser.next().await.unwrap().unwrap();
+        assert_eq!(second.title, Some("Episode 2".to_string()));
+        assert!(parser.next().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_namespace_resolution() {
+        #[derive(Default)]
+        struct NsItem {
+            creator: Option<String>,
+            encoded: Option<String>,
+            plain_title_ns: Option<Option<String>>,
+        }
+
+        impl GradualRssItem for NsItem {
+            fn init() -> Self {
+                NsItem::default()
+            }
+
+            fn populate(&mut self, node: XmlNode) {
+                if node.is(crate::ns::DUBLIN_CORE, "creator") {
+                    self.creator = node.into_text();
+                } else if node.is(crate::ns::CONTENT, "encoded") {
+                    self.encoded = node.into_text();
+                } else if node.tag == "title" {
+                    self.plain_title_ns = Some(node.namespace);
+                }
+            }
+        }
+
+        let rss = r#"<rss xmlns:creators="http://purl.org/dc/elements/1.1/"
+                         xmlns:content="http://purl.org/rss/1.0/modules/content/">
+            <channel><item>
+                <title>No namespace</title>
+                <creators:creator>Jane</creators:creator>
+                <content:encoded><![CDATA[<p>Body</p>]]></content:encoded>
+                <dc:creator>Unbound prefix is not Dublin Core</dc:creator>
+            </item></channel></rss>"#;
+
+        let cursor = Cursor::new(rss.as_bytes());
+        let mut parser = RssParser::<NsItem, _>::new(cursor).await.unwrap();
+
+        let item = parser.next().await.unwrap().unwrap();
+        assert_eq!(item.creator, Some("Jane".to_string()));
+        assert_eq!(item.encoded, Some("<p>Body</p>".to_string()));
+        assert_eq!(item.plain_title_ns, Some(None));
+    }
+
+    #[tokio::test]
+    async fn test_local_name_and_default_namespace() {
+        let feed = r#"<feed xmlns="http://www.w3.org/2005/Atom"><entry><Title>Hi</Title></entry></feed>"#;
+
+        #[derive(Default)]
+        struct Names(Vec<(String, String, Option<String>)>);
+
+        impl GradualRssItem for Names {
+            fn init() -> Self {
+                Names::default()
+            }
+
+            fn populate(&mut self, node: XmlNode) {
+                self.0.push((node.tag, node.local_name, node.namespace));
+            }
+        }
+
+        let mut parser = RssParser::<Names, _>::new_atom(Cursor::new(feed.as_bytes())).await.unwrap();
+        let item = parser.next().await.unwrap().unwrap();
+        let atom = Some(crate::ns::ATOM.to_string());
+        assert_eq!(
+            item.0,
+            vec![
+                ("title".to_string(), "title".to_string(), atom.clone()),
+                ("entry".to_string(), "entry".to_string(), atom),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_nested_item_tags() {
+        let rss = r#"<rss><channel>
+            <item>
+                <title>Outer</title>
+                <item><title>Inner</title><link>inner</link></item>
+                <item/>
+                <link>outer</link>
+            </item>
+            <item><title>Next</title></item>
+        </channel></rss>"#;
+        let items = crate::parse_all::<crate::RssItem>(rss.as_bytes()).await.unwrap();
+        let fields: Vec<_> = items.iter().map(|item| (item.title.as_deref(), item.link.as_deref())).collect();
+        assert_eq!(fields, vec![(Some("Outer"), Some("outer")), (Some("Next"), None)]);
+    }
+
+    #[tokio::test]
+    async fn test_nested_paths() {
+        #[derive(Default)]
+        struct Paths(Vec<String>);
+
+        impl GradualRssItem for Paths {
+            fn init() -> Self {
+                Paths::default()
+            }
+
+            fn populate(&mut self, node: XmlNode) {
+                self.0.push(node.path);
+            }
+        }
+
+        let rss = r#"<rss><channel><item>
+            <title>T</title>
+            <media:group>
+                <media:content url="a.mp4"/>
+                <media:content url="b.mp4"><media:title>B</media:title></media:content>
+            </media:group>
+        </item></channel></rss>"#;
+
+        let mut parser = RssParser::<Paths, _>::new(Cursor::new(rss.as_bytes())).await.unwrap();
+        let item = parser.next().await.unwrap().unwrap();
+        let node = |path: &str| XmlNode { path: path.to_string(), ..XmlNode::new("x".to_string()) };
+        assert_eq!(node("media:group/media:content/media:title").parent(), Some("media:content"));
+        assert_eq!(node("media:group/media:content").parent(), Some("media:group"));
+        assert_eq!(node("title").parent(), None);
+        assert_eq!(node("").parent(), None);
+        assert_eq!(
+            item.0,
+            vec![
+                "title",
+                "media:group/media:content",
+                "media:group/media:content/media:title",
+                "media:group/media:content",
+                "media:group",
+                "",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_locations() {
+        let rss = "<rss>\n<channel>\n  <item><title>one</title></item>\n  <item>\n    <title>two</titel>\n  </item>\n</channel></rss>";
+        let mut parser: RssParser<TestRssItem, _> = RssParser::builder()
+            .track_lines(true)
+            .buffer_capacity(16)
+            .build(Cursor::new(rss.as_bytes()))
+            .await
+            .unwrap();
+
+        parser.next().await.unwrap().unwrap();
+        let location = parser.item_location().unwrap();
+        assert_eq!((location.offset, location.line, location.column), (18, Some(3), Some(3)));
+
+        let err = parser.next().await.unwrap_err();
+        assert_eq!(parser.locate(err.position().unwrap()).to_string(), "line 5, column 15");
+        assert_eq!(parser.item_location().unwrap().line, Some(4));
+
+        let mut untracked = RssParser::<TestRssItem, _>::new(Cursor::new(rss.as_bytes())).await.unwrap();
+        untracked.next().await.unwrap();
+        assert_eq!(untracked.item_location().unwrap().to_string(), "byte 18");
+    }
+
+    #[tokio::test]
+    async fn test_xml_lang_inherited() {
+        #[derive(Default)]
+        struct Langs(Vec<(String, Option<String>)>);
+
+        impl GradualRssItem for Langs {
+            fn init() -> Self {
+                Langs::default()
+            }
+
+            fn populate(&mut self, node: XmlNode) {
+                self.0.push((node.path, node.lang));
+            }
+        }
+
+        let feed = r#"<feed xml:lang="en"><entry><title>T</title><summary xml:lang="fr"><p>S</p></summary></entry></feed>"#;
+        let mut parser = RssParser::<Langs, _>::new_atom(Cursor::new(feed.as_bytes())).await.unwrap();
+        let lang = |path: &str, item: &Langs| item.0.iter().find(|(p, _)| p == path).and_then(|(_, lang)| lang.clone());
+        let item = parser.next().await.unwrap().unwrap();
+        assert_eq!(lang("", &item).as_deref(), Some("en"));
+        assert_eq!(lang("title", &item).as_deref(), Some("en"));
+        assert_eq!(lang("summary/p", &item).as_deref(), Some("fr"));
+    }
+
+    #[tokio::test]
+    async fn test_finish_rejects_items() {
+        #[derive(Debug)]
+        struct Linked(Option<String>);
+
+        impl GradualRssItem for Linked {
+            fn init() -> Self {
+                Linked(None)
+            }
+
+            fn populate(&mut self, node: XmlNode) {
+                if node.tag == "link" {
+                    self.0 = node.into_text();
+                }
+            }
+
+            fn finish(self) -> Result<Self, String> {
+                match self.0 {
+                    Some(_) => Ok(self),
+                    None => Err("missing link".to_string()),
+                }
+            }
+        }
+
+        let rss = "<rss><channel><item><title>no link</title></item><item/><item><link>a</link></item></channel></rss>";
+        let mut parser = RssParser::<Linked, _>::new(Cursor::new(rss.as_bytes())).await.unwrap();
+        assert!(matches!(parser.next().await, Err(RssError::InvalidItem(reason)) if reason == "missing link"));
+        assert!(matches!(parser.next().await, Err(RssError::InvalidItem(_))));
+        assert_eq!(parser.next().await.unwrap().unwrap().0.as_deref(), Some("a"));
+
+        let mut lenient: RssParser<Linked, _> = RssParser::builder()
+            .mode(ParseMode::Lenient)
+            .build(Cursor::new(rss.as_bytes()))
+            .await
+            .unwrap();
+        assert_eq!(lenient.next().await.unwrap().unwrap().0.as_deref(), Some("a"));
+        assert!(lenient.next().await.unwrap().is_none());
+        assert_eq!(lenient.warnings().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_inner_xml() {
+        #[derive(Default)]
+        struct Inner(Vec<(String, Option<String>)>);
+
+        impl GradualRssItem for Inner {
+            fn init() -> Self {
+                Inner::default()
+            }
+
+            fn populate(&mut self, node: XmlNode) {
+                self.0.push((node.path, node.inner_xml));
+            }
+        }
+
+        let rss = r#"<rss><channel><title>Feed</title><item><ext:data a="1"><x>&amp;<![CDATA[<raw>]]></x><!-- c --><y/></ext:data></item></channel></rss>"#;
+        let mut parser: RssParser<Inner, _> = RssParser::builder()
+            .capture_inner_xml(true)
+            .buffer_capacity(8)
+            .build(Cursor::new(rss.as_bytes()))
+            .await
+            .unwrap();
+
+        let item = parser.next().await.unwrap().unwrap();
+        let inner = |path: &str| item.0.iter().find(|(p, _)| p == path).and_then(|(_, inner)| inner.as_deref());
+        assert_eq!(inner("ext:data/x"), Some("&amp;<![CDATA[<raw>]]>"));
+        assert_eq!(inner("ext:data"), Some(r#"<x>&amp;<![CDATA[<raw>]]></x><!-- c --><y/>"#));
+        assert_eq!(inner(""), Some(r#"<ext:data a="1"><x>&amp;<![CDATA[<raw>]]></x><!-- c --><y/></ext:data>"#));
+        assert!(item.0.iter().any(|(path, inner)| path == "ext:data/y" && inner.is_none()));
+
+        let plain = RssParser::<Inner, _>::new(Cursor::new(rss.as_bytes())).await.unwrap().next().await.unwrap().unwrap();
+        assert!(plain.0.iter().all(|(_, inner)| inner.is_none()));
+    }
+
+    #[tokio::test]
+    async fn test_attributes_captured() {
+        #[derive(Default)]
+        struct LinkItem {
+            href: Option<String>,
+            rel: Option<String>,
+        }
+
+        impl GradualRssItem for LinkItem {
+            fn init() -> Self {
+                LinkItem::default()
+            }
+
+            fn populate(&mut self, node: XmlNode) {
+                if node.tag == "atom:link" {
+                    self.href = node.attr("href").map(String::from);
+                    self.rel = node.attr("REL").map(String::from);
+                }
+            }
+        }
+
+        let rss = r#"<rss><channel><item>
+            <atom:link href="https://example.com/?a=1&amp;b=2" Rel="self"></atom:link>
+        </item></channel></rss>"#;
+
+        let cursor = Cursor::new(rss.as_bytes());
+        let mut parser = RssParser::<LinkItem, _>::new(cursor).await.unwrap();
+
+        let item = parser.next().await.unwrap().unwrap();
+        assert_eq!(item.href, Some("https://example.com/?a=1&b=2".to_string()));
+        assert_eq!(item.rel, Some("self".to_string()));
     }
 
     #[tokio::test]
@@ -297,11 +2158,59 @@ mod tests {
         let cursor = Cursor::new(mixed_rss.as_bytes());
         let mut parser = RssParser::<TestRssItem, _>::new(cursor).await.unwrap();
 
-        let item = parser.next().await.unwrap();
+        let item = parser.next().await.unwrap().unwrap();
         assert_eq!(item.title, Some("Mixed Content".to_string()));
         assert_eq!(item.description, Some("Regular text".to_string()));
     }
 
+    const ENTITY_RSS: &str = r#"<rss><channel><item>
+        <title>Tom &amp; Jerry&#8217;s&nbsp;&#x2014; &lt;live&gt; &bogus;</title>
+    </item></channel></rss>"#;
+
+    #[tokio::test]
+    async fn test_entities_decoded() {
+        let mut parser = RssParser::<TestRssItem, _>::new(Cursor::new(ENTITY_RSS.as_bytes())).await.unwrap();
+        let item = parser.next().await.unwrap().unwrap();
+        assert_eq!(
+            item.title.as_deref(),
+            Some("Tom & Jerry\u{2019}s\u{A0}\u{2014} <live> &bogus;")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_split_text_and_cdata() {
+        let long = "word ".repeat(40);
+        let rss = format!(
+            "<rss><channel><item><title>{long}&amp;{long}</title><description>\n  <![CDATA[<p>one</p>]]><![CDATA[<p>two</p>]]>\n</description></item></channel></rss>"
+        );
+        let mut parser: RssParser<TestRssItem, _> = RssParser::builder()
+            .buffer_capacity(16)
+            .build(Cursor::new(rss.into_bytes()))
+            .await
+            .unwrap();
+        let item = parser.next().await.unwrap().unwrap();
+        assert_eq!(item.title, Some(format!("{long}&{long}")));
+
+        let mut node = XmlNode::new("description".to_string());
+        node.value = Some("\n  \n".to_string());
+        node.cdata = Some("<p>one</p><p>two</p>".to_string());
+        assert_eq!(node.into_text().as_deref(), Some("<p>one</p><p>two</p>"));
+    }
+
+    #[tokio::test]
+    async fn test_entities_kept() {
+        let mut parser: RssParser<TestRssItem, _> = RssParser::builder()
+            .decode_entities(false)
+            .build(Cursor::new(ENTITY_RSS.as_bytes()))
+            .await
+            .unwrap();
+        let item = parser.next().await.unwrap().unwrap();
+        assert_eq!(
+            item.title.as_deref(),
+            Some("Tom &amp; Jerry&#8217;s&nbsp;&#x2014; &lt;live&gt; &bogus;")
+        );
+    }
+
     #[tokio::test]
     async fn test_case_insensitive_tags() {
         let case_rss = r#"<?xml version="1.0" encoding="UTF-8"?>
@@ -317,7 +2226,7 @@ mod tests {
         let cursor = Cursor::new(case_rss.as_bytes());
         let mut parser = RssParser::<TestRssItem, _>::new(cursor).await.unwrap();
 
-        let item = parser.next().await.unwrap();
+        let item = parser.next().await.unwrap().unwrap();
         assert_eq!(item.title, Some("Case Test".to_string()));
         assert_eq!(item.description, Some("Case insensitive tags".to_string()));
     }
@@ -328,12 +2237,12 @@ mod tests {
         // Test with Cursor
         let cursor = Cursor::new(SAMPLE_RSS.as_bytes());
         let mut parser = RssParser::<TestRssItem, _>::new(cursor).await.unwrap();
-        assert!(parser.next().await.is_some());
+        assert!(parser.next().await.unwrap().is_some());
 
         // Test with empty cursor
         let empty_cursor = Cursor::new(Vec::<u8>::new());
         let mut empty_parser = RssParser::<TestRssItem, _>::new(empty_cursor).await.unwrap();
-        assert!(empty_parser.next().await.is_none());
+        assert!(empty_parser.next().await.unwrap().is_none());
     }
 
     // Benchmark-style test for performance
@@ -362,7 +2271,7 @@ mod tests {
         let parser = RssParser::<TestRssItem, _>::new(cursor).await.unwrap();
 
         use tokio_stream::StreamExt;
-        let items: Vec<TestRssItem> = parser.collect().await;
+        let items: Vec<TestRssItem> = parser.into_stream().collect::<Result<_, _>>().await.unwrap();
         assert_eq!(items.len(), 100);
         assert_eq!(items[0].title, Some("Item 0".to_string()));
         assert_eq!(items[99].title, Some("Item 99".to_string()));
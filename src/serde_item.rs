@@ -0,0 +1,280 @@
+use std::ops::Deref;
+
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::de::{DeserializeOwned, Deserializer, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+use serde_json::{Map, Value};
+
+use crate::dynamic::{add_node, TEXT_KEY};
+use crate::rss_parser::{GradualRssItem, XmlNode};
+
+/// Fills any `T: Deserialize` from the item's elements, so serde's renaming,
+/// defaults and nesting can stand in for a hand-written `populate`.
+///
+/// The item is first built into the tree that `RssParser::<serde_json::Value, _>`
+/// returns (see that `GradualRssItem` implementation). All leaf values are text: numbers and booleans are parsed
+/// from it, a single element fills a `Vec`, and a `String` field accepts an
+/// element with attributes by taking its `$text`.
+///
+/// Items that don't deserialize are rejected through `GradualRssItem::finish`.
+#[derive(Debug, Clone)]
+pub struct Deserialized<T> {
+    item: Option<T>,
+    // Children of the elements not yet closed, keyed by their path.
+    pending: Map<String, Value>,
+}
+
+impl<T> Deserialized<T> {
+    pub fn into_inner(self) -> T {
+        self.item.expect("items are deserialized before they are returned")
+    }
+}
+
+impl<T> Deref for Deserialized<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.item.as_ref().expect("items are deserialized before they are returned")
+    }
+}
+
+impl<T: DeserializeOwned> GradualRssItem for Deserialized<T> {
+    fn init() -> Self {
+        Deserialized {
+            item: None,
+            pending: Map::new(),
+        }
+    }
+
+    fn populate(&mut self, node: XmlNode) {
+        add_node(&mut self.pending, node);
+    }
+
+    fn finish(mut self) -> Result<Self, String> {
+        let root = self.pending.remove("").unwrap_or_else(|| Value::Object(Map::new()));
+        self.pending.clear();
+        let item = T::deserialize(Node(root)).map_err(|err| err.to_string())?;
+        self.item = Some(item);
+        Ok(self)
+    }
+}
+
+// A `Value` built from XML text, which hands out its strings as whatever type
+// is asked for.
+struct Node(Value);
+
+impl<'de> IntoDeserializer<'de, serde_json::Error> for Node {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+macro_rules! parse_text {
+    ($($method:ident => $visit:ident,)*) => {
+        $(fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+            match self.text() {
+                Some(text) => match text.trim().parse() {
+                    Ok(value) => visitor.$visit(value),
+                    Err(_) => Err(serde::de::Error::invalid_value(serde::de::Unexpected::Str(&text), &visitor)),
+                },
+                None => self.deserialize_any(visitor),
+            }
+        })*
+    };
+}
+
+impl Node {
+    // The text of a leaf, or of an element with attributes.
+    fn text(&self) -> Option<String> {
+        match &self.0 {
+            Value::String(text) => Some(text.clone()),
+            Value::Object(object) => object.get(TEXT_KEY).and_then(Value::as_str).map(String::from),
+            _ => None,
+        }
+    }
+}
+
+impl<'de> Deserializer<'de> for Node {
+    type Error = serde_json::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::Array(values) => visitor.visit_seq(SeqDeserializer::new(values.into_iter().map(Node))),
+            Value::Object(object) => visitor.visit_map(MapDeserializer::new(object.into_iter().map(|(key, value)| (key, Node(value))))),
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    parse_text! {
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.text().as_deref().map(str::trim) {
+            Some("true" | "1" | "yes") => visitor.visit_bool(true),
+            Some("false" | "0" | "no") => visitor.visit_bool(false),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.text() {
+            Some(text) => visitor.visit_string(text),
+            None => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::Null => visitor.visit_none(),
+            value => visitor.visit_some(Node(value)),
+        }
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::Array(values) => visitor.visit_seq(SeqDeserializer::new(values.into_iter().map(Node))),
+            value => visitor.visit_seq(SeqDeserializer::new(std::iter::once(Node(value)))),
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::String(text) => {
+                let object = std::iter::once((TEXT_KEY.to_string(), Node(Value::String(text))));
+                visitor.visit_map(MapDeserializer::new(object))
+            }
+            value => Node(value).deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::String(text) => visitor.visit_enum(text.trim().to_string().into_deserializer()),
+            value => value.deserialize_enum(name, variants, visitor),
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        i128 u128 bytes byte_buf unit unit_struct tuple tuple_struct map identifier
+    }
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    use super::*;
+    use crate::RssParser;
+    use serde::Deserialize;
+    use std::io::Cursor;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Enclosure {
+        #[serde(rename = "@url")]
+        url: String,
+        #[serde(rename = "@length")]
+        length: Option<u64>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename_all = "lowercase")]
+    enum Kind {
+        Full,
+        Trailer,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Episode {
+        title: String,
+        #[serde(rename = "pubdate")]
+        published: Option<String>,
+        #[serde(rename = "category", default)]
+        categories: Vec<String>,
+        guid: String,
+        enclosure: Option<Enclosure>,
+        #[serde(rename = "itunes:episode")]
+        number: Option<u32>,
+        #[serde(rename = "itunes:episodetype")]
+        kind: Option<Kind>,
+        #[serde(rename = "itunes:explicit", default)]
+        explicit: bool,
+    }
+
+    #[tokio::test]
+    async fn test_deserialize_items() {
+        let feed = r#"<rss><channel>
+<item>
+    <title>One</title>
+    <pubDate>Mon, 01 Jan 2024 00:00:00 GMT</pubDate>
+    <category>a</category><category>b</category>
+    <guid isPermaLink="false">ep-1</guid>
+    <enclosure url="https://example.com/1.mp3" length="123"/>
+    <itunes:episode>7</itunes:episode>
+    <itunes:episodeType>trailer</itunes:episodeType>
+    <itunes:explicit>yes</itunes:explicit>
+</item>
+<item><title>Two</title><guid>ep-2</guid><category>only</category></item>
+<item><guid>no title</guid></item>
+<item><title>Three</title><guid>ep-3</guid></item>
+</channel></rss>"#;
+        let mut parser = RssParser::<Deserialized<Episode>, _>::new(Cursor::new(feed.as_bytes())).await.unwrap();
+
+        let first = parser.next().await.unwrap().unwrap().into_inner();
+        assert_eq!(
+            first,
+            Episode {
+                title: "One".to_string(),
+                published: Some("Mon, 01 Jan 2024 00:00:00 GMT".to_string()),
+                categories: vec!["a".to_string(), "b".to_string()],
+                guid: "ep-1".to_string(),
+                enclosure: Some(Enclosure {
+                    url: "https://example.com/1.mp3".to_string(),
+                    length: Some(123),
+                }),
+                number: Some(7),
+                kind: Some(Kind::Trailer),
+                explicit: true,
+            }
+        );
+
+        let second = parser.next().await.unwrap().unwrap();
+        assert_eq!(second.categories, vec!["only".to_string()]);
+        assert!(!second.explicit);
+
+        assert!(matches!(parser.next().await, Err(crate::RssError::InvalidItem(reason)) if reason.contains("title")));
+        assert_eq!(parser.next().await.unwrap().unwrap().title, "Three");
+    }
+}
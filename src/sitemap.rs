@@ -0,0 +1,133 @@
+use serde::Serialize;
+use tokio::fs::File;
+use tokio::io::AsyncRead;
+
+use crate::error::RssError;
+use crate::rss_parser::{GradualRssItem, RssParser, XmlNode};
+use crate::stream::RssStream;
+use crate::url::url_text;
+
+/// A `<url>` of a sitemap, or a `<sitemap>` of a sitemap index.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SitemapEntry {
+    pub loc: Option<String>,
+    pub lastmod: Option<String>,
+    /// `always`, `hourly`, `daily`, `weekly`, `monthly`, `yearly` or `never`.
+    pub changefreq: Option<String>,
+    /// From 0.0 to 1.0; sitemaps that leave it out mean 0.5.
+    pub priority: Option<f32>,
+}
+
+impl GradualRssItem for SitemapEntry {
+    fn init() -> Self {
+        SitemapEntry::default()
+    }
+
+    fn populate(&mut self, node: XmlNode) {
+        match node.path.as_str() {
+            "loc" => self.loc = url_text(node).map(|loc| loc.trim().to_string()),
+            "lastmod" => self.lastmod = node.into_text().map(|lastmod| lastmod.trim().to_string()),
+            "changefreq" => self.changefreq = node.into_text().map(|changefreq| changefreq.trim().to_lowercase()),
+            "priority" => self.priority = node.into_text().and_then(|priority| priority.trim().parse().ok()),
+            _ => {}
+        }
+    }
+
+    fn finish(self) -> Result<Self, String> {
+        if self.loc.is_none() {
+            return Err("sitemap entry without <loc>".to_string());
+        }
+        Ok(self)
+    }
+}
+
+/// Streams the entries of a sitemap (`<urlset>`) or sitemap index
+/// (`<sitemapindex>`), as https://www.sitemaps.org describes them.
+pub struct SitemapParser<R> {
+    parser: RssParser<SitemapEntry, R>,
+    index: bool,
+}
+
+impl<R: AsyncRead + Unpin> SitemapParser<R> {
+    pub async fn new(input: R) -> Result<Self, RssError> {
+        let mut parser = RssParser::<SitemapEntry, R>::new(input).await?;
+        let root = parser.read_root().await?.ok_or(RssError::UnexpectedEof)?;
+        let (item_tag, index) = match root.rsplit(':').next().unwrap_or(&root) {
+            "urlset" => ("url", false),
+            "sitemapindex" => ("sitemap", true),
+            _ => return Err(RssError::UnsupportedFormat(root)),
+        };
+        Ok(SitemapParser {
+            parser: parser.retarget(item_tag),
+            index,
+        })
+    }
+
+    /// Whether the entries point at further sitemaps rather than pages.
+    pub fn is_index(&self) -> bool {
+        self.index
+    }
+
+    pub async fn next(&mut self) -> Result<Option<SitemapEntry>, RssError> {
+        self.parser.next().await
+    }
+
+    pub fn into_stream<'a>(self) -> RssStream<'a, SitemapEntry, R> {
+        self.parser.into_stream()
+    }
+}
+
+impl SitemapParser<File> {
+    pub async fn from_file(path: &str) -> Result<Self, RssError> {
+        Self::new(File::open(path).await?).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[tokio::test]
+    async fn test_sitemap() {
+        let sitemap = r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <url>
+    <loc>https://example.com/</loc>
+    <lastmod>2024-01-02</lastmod>
+    <changefreq>Daily</changefreq>
+    <priority>0.8</priority>
+  </url>
+  <url><loc> https://example.com/about?a=1&amp;b=2 </loc></url>
+</urlset>"#;
+        let mut parser = SitemapParser::new(Cursor::new(sitemap)).await.unwrap();
+        assert!(!parser.is_index());
+        let home = parser.next().await.unwrap().unwrap();
+        assert_eq!(
+            home,
+            SitemapEntry {
+                loc: Some("https://example.com/".to_string()),
+                lastmod: Some("2024-01-02".to_string()),
+                changefreq: Some("daily".to_string()),
+                priority: Some(0.8),
+            }
+        );
+        let about = parser.next().await.unwrap().unwrap();
+        assert_eq!(about.loc.as_deref(), Some("https://example.com/about?a=1&b=2"));
+        assert_eq!(about.priority, None);
+        assert!(parser.next().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_sitemap_index() {
+        let index = r#"<sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <sitemap><loc>https://example.com/sitemap-1.xml</loc><lastmod>2024-03-01T10:00:00Z</lastmod></sitemap>
+</sitemapindex>"#;
+        let mut parser = SitemapParser::new(Cursor::new(index)).await.unwrap();
+        assert!(parser.is_index());
+        assert_eq!(parser.next().await.unwrap().unwrap().loc.as_deref(), Some("https://example.com/sitemap-1.xml"));
+
+        let err = SitemapParser::new(Cursor::new("<rss/>")).await.err().unwrap();
+        assert!(matches!(err, RssError::UnsupportedFormat(root) if root == "rss"));
+    }
+}
@@ -0,0 +1,30 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Progress counters of a parser, from `RssParser::stats`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParserStats {
+    /// Bytes of the document parsed so far, after transcoding to UTF-8.
+    pub bytes_read: u64,
+    /// Items returned by `next`.
+    pub items: u64,
+    /// Items dropped because they were malformed or failed `finish`.
+    pub items_skipped: u64,
+    /// The length of `RssParser::warnings`.
+    pub warnings: usize,
+    /// Time since the parser was created.
+    pub elapsed: Duration,
+}
+
+pub(crate) type ProgressCallback = dyn Fn(&ParserStats) + Send + Sync;
+
+// Lets the options holding it stay `Debug`.
+#[derive(Clone)]
+pub(crate) struct ProgressHandle(pub(crate) Arc<ProgressCallback>);
+
+impl fmt::Debug for ProgressHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Progress")
+    }
+}
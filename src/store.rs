@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use tokio::fs::{self, File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+
+/// Where `dedup_store` and the poller keep what they have seen, so it
+/// survives a restart.
+///
+/// A string key-value store; implement it over a database or a cache to share
+/// state between processes.
+pub trait SeenStore {
+    fn get(&self, key: &str) -> impl Future<Output = io::Result<Option<String>>> + Send;
+
+    fn put(&mut self, key: String, value: String) -> impl Future<Output = io::Result<()>> + Send;
+}
+
+/// Keeps everything in memory, so nothing survives the process.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+    entries: HashMap<String, String>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        MemoryStore::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl SeenStore for MemoryStore {
+    async fn get(&self, key: &str) -> io::Result<Option<String>> {
+        Ok(self.entries.get(key).cloned())
+    }
+
+    async fn put(&mut self, key: String, value: String) -> io::Result<()> {
+        self.entries.insert(key, value);
+        Ok(())
+    }
+}
+
+/// Keeps the entries in memory and appends every `put` to a file, one JSON
+/// `[key, value]` array per line, which `open` reads back. The file is
+/// compacted once overwritten entries make up most of it.
+#[derive(Debug)]
+pub struct FileStore {
+    path: PathBuf,
+    file: File,
+    entries: HashMap<String, String>,
+    // Lines in the file, including overwritten entries.
+    lines: usize,
+}
+
+impl FileStore {
+    /// Opens the file at `path`, creating it if it doesn't exist. A line that
+    /// doesn't parse, like one cut short by a crash, is skipped.
+    pub async fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let contents = match fs::read_to_string(&path).await {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err),
+        };
+        let mut entries = HashMap::new();
+        let mut lines = 0;
+        for line in contents.lines() {
+            if let Ok((key, value)) = serde_json::from_str::<(String, String)>(line) {
+                entries.insert(key, value);
+                lines += 1;
+            }
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(&path).await?;
+        if !contents.is_empty() && !contents.ends_with('\n') {
+            file.write_all(b"\n").await?;
+        }
+        Ok(FileStore { path, file, entries, lines })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Rewrites the file with only the current entries, dropping overwritten
+    /// ones. The new file replaces the old one by a rename, so a crash leaves
+    /// one or the other.
+    pub async fn compact(&mut self) -> io::Result<()> {
+        let mut compacted = self.path.clone().into_os_string();
+        compacted.push(".tmp");
+        let mut out = String::new();
+        for entry in &self.entries {
+            out.push_str(&serde_json::to_string(&entry)?);
+            out.push('\n');
+        }
+        fs::write(&compacted, out).await?;
+        fs::rename(&compacted, &self.path).await?;
+        self.file = OpenOptions::new().append(true).open(&self.path).await?;
+        self.lines = self.entries.len();
+        Ok(())
+    }
+
+    /// Whether the file has grown to more than twice the live entries.
+    pub fn needs_compaction(&self) -> bool {
+        self.lines > 2 * self.entries.len().max(64)
+    }
+}
+
+impl SeenStore for FileStore {
+    async fn get(&self, key: &str) -> io::Result<Option<String>> {
+        Ok(self.entries.get(key).cloned())
+    }
+
+    async fn put(&mut self, key: String, value: String) -> io::Result<()> {
+        let mut line = serde_json::to_string(&(&key, &value))?;
+        line.push('\n');
+        self.file.write_all(line.as_bytes()).await?;
+        self.file.flush().await?;
+        self.entries.insert(key, value);
+        self.lines += 1;
+        if self.needs_compaction() {
+            self.compact().await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_file_store_reopens() {
+        let path = std::env::temp_dir().join(format!("rss_parser_store_{}", std::process::id()));
+        let _ = fs::remove_file(&path).await;
+
+        let mut store = FileStore::open(&path).await.unwrap();
+        store.put("a".to_string(), "1".to_string()).await.unwrap();
+        store.put("b\n\"".to_string(), "2".to_string()).await.unwrap();
+        store.put("a".to_string(), "3".to_string()).await.unwrap();
+        drop(store);
+        // A line cut short by a crash.
+        fs::OpenOptions::new().append(true).open(&path).await.unwrap().write_all(b"[\"c\",").await.unwrap();
+
+        let mut store = FileStore::open(&path).await.unwrap();
+        assert_eq!(store.get("a").await.unwrap().as_deref(), Some("3"));
+        assert_eq!(store.get("b\n\"").await.unwrap().as_deref(), Some("2"));
+        assert_eq!(store.get("c").await.unwrap(), None);
+
+        store.compact().await.unwrap();
+        store.put("d".to_string(), "4".to_string()).await.unwrap();
+        let store = FileStore::open(&path).await.unwrap();
+        assert_eq!(store.len(), 3);
+        assert_eq!(fs::read_to_string(&path).await.unwrap().lines().count(), 3);
+        fs::remove_file(&path).await.unwrap();
+    }
+}
@@ -0,0 +1,192 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use serde::Serialize;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_stream::Stream;
+
+use crate::error::RssError;
+use crate::rss_parser::{GradualRssItem, RssParser};
+use crate::writer::write_ndjson;
+
+type NextFuture<'a, T, R> = Pin<Box<dyn Future<Output = (RssParser<T, R>, Result<Option<T>, RssError>)> + Send + 'a>>;
+
+/// A `Stream` over a parser that keeps the in-flight read alive between polls.
+///
+/// `'a` is how long the parser's input lives, `'static` for owned input.
+/// The stream ends after an error that stops the feed, see
+/// `RssError::is_fatal`.
+pub struct RssStream<'a, T, R> {
+    parser: Option<RssParser<T, R>>,
+    pending: Option<NextFuture<'a, T, R>>,
+}
+
+impl<T, R> RssStream<'_, T, R> {
+    pub(crate) fn new(parser: RssParser<T, R>) -> Self {
+        RssStream {
+            parser: Some(parser),
+            pending: None,
+        }
+    }
+}
+
+impl<'a, T, R> RssStream<'a, T, R>
+where
+    T: GradualRssItem + Serialize + Send + 'a,
+    R: AsyncRead + Unpin + Send + 'a,
+{
+    /// Writes every item as a line of JSON, see `write_ndjson`.
+    pub async fn to_ndjson<W: AsyncWrite + Unpin>(self, out: W) -> Result<usize, RssError> {
+        write_ndjson(self, out).await
+    }
+}
+
+// The parser is only ever moved into and out of the boxed future, never pinned in place.
+impl<T, R> Unpin for RssStream<'_, T, R> {}
+
+impl<'a, T, R> Stream for RssStream<'a, T, R>
+where
+    T: GradualRssItem + Send + 'a,
+    R: AsyncRead + Unpin + Send + 'a,
+{
+    type Item = Result<T, RssError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        if this.pending.is_none() {
+            let Some(mut parser) = this.parser.take() else {
+                return Poll::Ready(None);
+            };
+            this.pending = Some(Box::pin(async move {
+                let result = parser.next().await;
+                (parser, result)
+            }));
+        }
+
+        let pending = this.pending.as_mut().expect("pending future was just set");
+        match pending.as_mut().poll(cx) {
+            Poll::Ready((parser, result)) => {
+                this.pending = None;
+                if !matches!(&result, Err(err) if err.is_fatal()) {
+                    this.parser = Some(parser);
+                }
+                Poll::Ready(result.transpose())
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::XmlNode;
+    use std::io::Cursor;
+    use tokio::io::ReadBuf;
+    use tokio_stream::StreamExt;
+
+    #[derive(Debug)]
+    struct TitleItem {
+        title: Option<String>,
+    }
+
+    impl GradualRssItem for TitleItem {
+        fn init() -> Self {
+            TitleItem { title: None }
+        }
+
+        fn populate(&mut self, node: XmlNode) {
+            if node.tag == "title" {
+                self.title = node.value;
+            }
+        }
+    }
+
+    // Hands out one byte per read and returns Pending on every other poll,
+    // which is what a slow network peer looks like to the parser.
+    struct TrickleReader {
+        data: Vec<u8>,
+        pos: usize,
+        ready: bool,
+    }
+
+    impl AsyncRead for TrickleReader {
+        fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<std::io::Result<()>> {
+            if !self.ready {
+                self.ready = true;
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            self.ready = false;
+            if self.pos < self.data.len() {
+                let byte = self.data[self.pos];
+                buf.put_slice(&[byte]);
+                self.pos += 1;
+            }
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    const FEED: &str = r#"<rss><channel>
+        <item><title>First</title></item>
+        <item><title>Second</title></item>
+        <item><title>Third</title></item>
+    </channel></rss>"#;
+
+    #[tokio::test]
+    async fn test_stream_survives_pending_reads() {
+        let reader = TrickleReader {
+            data: FEED.as_bytes().to_vec(),
+            pos: 0,
+            ready: false,
+        };
+        let parser = RssParser::<TitleItem, _>::new(reader).await.unwrap();
+
+        let titles: Vec<Option<String>> = parser
+            .into_stream()
+            .map(|item| item.unwrap().title)
+            .collect()
+            .await;
+        assert_eq!(
+            titles,
+            vec![Some("First".to_string()), Some("Second".to_string()), Some("Third".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_ends_after_last_item() {
+        let parser = RssParser::<TitleItem, _>::new(Cursor::new(FEED.as_bytes())).await.unwrap();
+        let mut stream = parser.into_stream();
+
+        for _ in 0..3 {
+            assert!(stream.next().await.unwrap().is_ok());
+        }
+        assert!(stream.next().await.is_none());
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_stream_borrows_input_and_ends_after_fatal_error() {
+        let feed = String::from("<rss><channel><item><title>First</title></item><item><title>");
+        let parser: RssParser<TitleItem, _> = RssParser::builder().max_feed_size(50).build(feed.as_bytes()).await.unwrap();
+        let results: Vec<_> = parser.into_stream().collect().await;
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].as_ref().unwrap().title.as_deref(), Some("First"));
+        assert!(matches!(results[1], Err(RssError::FeedTooLarge { max: 50 })));
+    }
+
+    #[tokio::test]
+    async fn test_stream_goes_on_after_item_limits() {
+        let feed = format!(
+            "<rss><channel><item><title>{}</title></item><item><title>Small</title></item></channel></rss>",
+            "x".repeat(200)
+        );
+        let parser: RssParser<TitleItem, _> = RssParser::builder().max_item_size(100).build(feed.as_bytes()).await.unwrap();
+        let results: Vec<_> = parser.into_stream().collect().await;
+        assert_eq!(results.len(), 2, "{:?}", results.iter().map(|result| result.as_ref().err()).collect::<Vec<_>>());
+        assert!(matches!(results[0], Err(RssError::LimitExceeded { limit: "item size", .. })));
+        assert_eq!(results[1].as_ref().unwrap().title.as_deref(), Some("Small"));
+    }
+}
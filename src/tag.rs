@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock};
+
+// Past this many distinct names a parser stops sharing the names it hands
+// out, so a feed making up names can't grow its table without bound.
+const MAX_INTERNED: usize = 4096;
+
+static EMPTY: LazyLock<Arc<str>> = LazyLock::new(|| Arc::from(""));
+
+/// A tag name as a cheap enum, in `XmlNode::tag_id`, so `populate` can match
+/// on it instead of comparing strings.
+///
+/// The well-known RSS and Atom elements have their own variants, matched
+/// ignoring ASCII case; prefixed extensions only with their conventional
+/// prefix (use `XmlNode::is` for any prefix). Every other name is kept in
+/// `Other`: compare against `TagId::of("podcast:chapters")`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TagId {
+    Item,
+    Channel,
+    Title,
+    Link,
+    Description,
+    PubDate,
+    Guid,
+    Author,
+    Category,
+    Comments,
+    Enclosure,
+    Source,
+    Entry,
+    Feed,
+    Id,
+    Updated,
+    Published,
+    Summary,
+    Content,
+    Name,
+    Email,
+    Uri,
+    Contributor,
+    Rights,
+    ContentEncoded,
+    DcCreator,
+    DcDate,
+    MediaContent,
+    MediaThumbnail,
+    MediaGroup,
+    ItunesImage,
+    ItunesDuration,
+    /// Any other name, as written.
+    Other(Arc<str>),
+}
+
+impl TagId {
+    pub fn of(tag: &str) -> TagId {
+        Self::known_any_case(tag).unwrap_or_else(|| TagId::Other(tag.into()))
+    }
+
+    pub(crate) fn empty() -> TagId {
+        TagId::Other(EMPTY.clone())
+    }
+
+    fn known_any_case(tag: &str) -> Option<TagId> {
+        if let Some(known) = Self::known(tag) {
+            return Some(known);
+        }
+        if tag.bytes().any(|byte| byte.is_ascii_uppercase()) {
+            return Self::known(&tag.to_ascii_lowercase());
+        }
+        None
+    }
+
+    fn known(tag: &str) -> Option<TagId> {
+        Some(match tag {
+            "item" => TagId::Item,
+            "channel" => TagId::Channel,
+            "title" => TagId::Title,
+            "link" => TagId::Link,
+            "description" => TagId::Description,
+            "pubdate" => TagId::PubDate,
+            "guid" => TagId::Guid,
+            "author" => TagId::Author,
+            "category" => TagId::Category,
+            "comments" => TagId::Comments,
+            "enclosure" => TagId::Enclosure,
+            "source" => TagId::Source,
+            "entry" => TagId::Entry,
+            "feed" => TagId::Feed,
+            "id" => TagId::Id,
+            "updated" => TagId::Updated,
+            "published" => TagId::Published,
+            "summary" => TagId::Summary,
+            "content" => TagId::Content,
+            "name" => TagId::Name,
+            "email" => TagId::Email,
+            "uri" => TagId::Uri,
+            "contributor" => TagId::Contributor,
+            "rights" => TagId::Rights,
+            "content:encoded" => TagId::ContentEncoded,
+            "dc:creator" => TagId::DcCreator,
+            "dc:date" => TagId::DcDate,
+            "media:content" => TagId::MediaContent,
+            "media:thumbnail" => TagId::MediaThumbnail,
+            "media:group" => TagId::MediaGroup,
+            "itunes:image" => TagId::ItunesImage,
+            "itunes:duration" => TagId::ItunesDuration,
+            _ => return None,
+        })
+    }
+}
+
+/// A parser's own table of the other names it has seen, so an element whose
+/// name came up before shares it rather than allocating it again.
+#[derive(Default)]
+pub(crate) struct TagTable {
+    names: HashMap<Box<str>, Arc<str>>,
+}
+
+impl TagTable {
+    pub(crate) fn id(&mut self, tag: &str) -> TagId {
+        if let Some(known) = TagId::known_any_case(tag) {
+            return known;
+        }
+        if let Some(name) = self.names.get(tag) {
+            return TagId::Other(name.clone());
+        }
+        let name: Arc<str> = tag.into();
+        if self.names.len() < MAX_INTERNED {
+            self.names.insert(tag.into(), name.clone());
+        }
+        TagId::Other(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tag_ids() {
+        assert_eq!(TagId::of("pubdate"), TagId::PubDate);
+        assert_eq!(TagId::of("pubDate"), TagId::PubDate);
+        assert_eq!(TagId::of("content:encoded"), TagId::ContentEncoded);
+        let chapters = TagId::of("podcast:chapters");
+        assert!(matches!(&chapters, TagId::Other(name) if &**name == "podcast:chapters"));
+        assert_eq!(TagId::of("podcast:chapters"), chapters);
+        assert_ne!(TagId::of("podcast:transcript"), chapters);
+
+        // A full table still tells names apart.
+        let mut table = TagTable::default();
+        for n in 0..MAX_INTERNED {
+            table.id(&format!("x:{n}"));
+        }
+        assert_eq!(table.id("title"), TagId::Title);
+        assert_eq!(table.id("podcast:chapters"), chapters);
+        assert_ne!(table.id("podcast:transcript"), table.id("podcast:person"));
+    }
+}
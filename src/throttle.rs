@@ -0,0 +1,93 @@
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll, ready};
+use std::time::Duration;
+
+use tokio::io::{AsyncRead, ReadBuf};
+use tokio::time::{Instant, Sleep};
+
+/// Reads its input no faster than `bytes_per_second`, so a large download
+/// leaves bandwidth for everything else:
+/// `RssParser::builder().build(Throttle::new(body, 256 * 1024))`.
+///
+/// Reads are cut to a tenth of a second's worth, and time spent waiting on
+/// the input earns no more than a second of burst. Needs a Tokio runtime.
+pub struct Throttle<R> {
+    inner: R,
+    bytes_per_second: u64,
+    scratch: Vec<u8>,
+    // When the current run of reads started and how much it has passed on.
+    since: Option<Instant>,
+    passed: u64,
+    delay: Option<Pin<Box<Sleep>>>,
+}
+
+impl<R> Throttle<R> {
+    pub fn new(inner: R, bytes_per_second: u64) -> Self {
+        let bytes_per_second = bytes_per_second.max(1);
+        Throttle {
+            inner,
+            bytes_per_second,
+            scratch: vec![0; (bytes_per_second / 10).clamp(1, 64 * 1024) as usize],
+            since: None,
+            passed: 0,
+            delay: None,
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for Throttle<R> {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if let Some(delay) = &mut this.delay {
+            ready!(delay.as_mut().poll(cx));
+            this.delay = None;
+        }
+
+        let limit = this.scratch.len().min(buf.remaining());
+        let mut limited = ReadBuf::new(&mut this.scratch[..limit]);
+        ready!(Pin::new(&mut this.inner).poll_read(cx, &mut limited))?;
+        let read = limited.filled().len();
+        buf.put_slice(limited.filled());
+
+        let now = Instant::now();
+        let since = *this.since.get_or_insert(now);
+        this.passed += read as u64;
+        let due = since + Duration::from_secs_f64(this.passed as f64 / this.bytes_per_second as f64);
+        if due > now {
+            this.delay = Some(Box::pin(tokio::time::sleep_until(due)));
+        } else if now - due > Duration::from_secs(1) {
+            this.since = Some(now);
+            this.passed = 0;
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RssItem, RssParser};
+
+    #[tokio::test(start_paused = true)]
+    async fn test_throttle() {
+        let items: String = (0..40).map(|n| format!("<item><title>{:03}</title></item>", n)).collect();
+        let feed = format!("<rss><channel>{}</channel></rss>", items);
+        let started = Instant::now();
+        let mut parser = RssParser::<RssItem, _>::new(Throttle::new(feed.as_bytes(), 500)).await.unwrap();
+        let mut count = 0;
+        while parser.next().await.unwrap().is_some() {
+            count += 1;
+        }
+        assert_eq!(count, 40);
+        // About 1.3 KB at 500 bytes a second.
+        let seconds = (feed.len() / 500) as u64;
+        assert!(started.elapsed() >= Duration::from_secs(seconds));
+        assert!(started.elapsed() <= Duration::from_secs(seconds + 2));
+    }
+}
@@ -0,0 +1,79 @@
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
+
+/// A connection carrying bytes both ways: a `TcpStream`, or a TLS stream
+/// over one.
+pub trait Connection: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<S: AsyncRead + AsyncWrite + Unpin + Send> Connection for S {}
+
+pub type BoxedConnection = Box<dyn Connection>;
+
+pub type TlsFuture<'a> = Pin<Box<dyn Future<Output = io::Result<BoxedConnection>> + Send + 'a>>;
+
+/// Wraps a TCP connection in TLS, for `https://` feeds (`FetchOptions::tls`)
+/// and `RssParser::connect_tls`. The crate bundles no TLS library, so this
+/// is implemented over the one the application already uses, such as
+/// `tokio-rustls`:
+///
+/// ```ignore
+/// struct Rustls(tokio_rustls::TlsConnector);
+///
+/// impl TlsConnector for Rustls {
+///     fn connect<'a>(&'a self, domain: &'a str, stream: TcpStream) -> TlsFuture<'a> {
+///         Box::pin(async move {
+///             let name = ServerName::try_from(domain.to_string())
+///                 .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+///             Ok(Box::new(self.0.connect(name, stream).await?) as BoxedConnection)
+///         })
+///     }
+/// }
+/// ```
+pub trait TlsConnector: Send + Sync {
+    /// Runs the handshake for `domain`, which certificates are checked against.
+    fn connect<'a>(&'a self, domain: &'a str, stream: TcpStream) -> TlsFuture<'a>;
+}
+
+impl fmt::Debug for dyn TlsConnector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsConnector").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::{RssItem, RssParser};
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::TcpListener;
+
+    // Stands in for a TLS library: notes the domain and passes bytes through.
+    #[derive(Default)]
+    pub(crate) struct Passthrough(pub(crate) std::sync::Mutex<Vec<String>>);
+
+    impl TlsConnector for Passthrough {
+        fn connect<'a>(&'a self, domain: &'a str, stream: TcpStream) -> TlsFuture<'a> {
+            self.0.lock().unwrap().push(domain.to_string());
+            Box::pin(async move { Ok(Box::new(stream) as BoxedConnection) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_connect_tls() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            socket.write_all(b"<rss><channel><item><title>Secure</title></item></channel></rss>").await.unwrap();
+        });
+        let tls = Passthrough::default();
+        let mut parser = RssParser::<RssItem, _>::connect_tls("127.0.0.1", port, &tls).await.unwrap();
+        assert_eq!(parser.next().await.unwrap().unwrap().title.as_deref(), Some("Secure"));
+        assert_eq!(tls.0.lock().unwrap().as_slice(), ["127.0.0.1"]);
+    }
+}
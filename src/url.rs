@@ -0,0 +1,205 @@
+use crate::rss_parser::XmlNode;
+
+// The components of a URI reference (RFC 3986, section 3).
+struct Parts<'a> {
+    scheme: Option<&'a str>,
+    authority: Option<&'a str>,
+    path: &'a str,
+    query: Option<&'a str>,
+    fragment: Option<&'a str>,
+}
+
+impl<'a> Parts<'a> {
+    fn split(uri: &'a str) -> Self {
+        let (rest, fragment) = match uri.split_once('#') {
+            Some((rest, fragment)) => (rest, Some(fragment)),
+            None => (uri, None),
+        };
+        let (rest, query) = match rest.split_once('?') {
+            Some((rest, query)) => (rest, Some(query)),
+            None => (rest, None),
+        };
+        let (scheme, rest) = match rest.split_once(':') {
+            Some((scheme, rest)) if is_scheme(scheme) => (Some(scheme), rest),
+            _ => (None, rest),
+        };
+        let (authority, path) = match rest.strip_prefix("//") {
+            Some(rest) => {
+                let end = rest.find('/').unwrap_or(rest.len());
+                (Some(&rest[..end]), &rest[end..])
+            }
+            None => (None, rest),
+        };
+        Parts {
+            scheme,
+            authority,
+            path,
+            query,
+            fragment,
+        }
+    }
+}
+
+fn is_scheme(scheme: &str) -> bool {
+    let mut chars = scheme.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+/// Resolves a possibly relative URL against an absolute base URL, as a browser
+/// would (RFC 3986, section 5). Returns `None` when `base` has no scheme.
+pub fn resolve_url(base: &str, reference: &str) -> Option<String> {
+    let base = Parts::split(base.trim());
+    let scheme = base.scheme?;
+    let reference = reference.trim();
+    let target = Parts::split(reference);
+
+    if target.scheme.is_some() {
+        return Some(compose(target.scheme, target.authority, &remove_dot_segments(target.path), target.query, target.fragment));
+    }
+    let (authority, path, query) = match (target.authority, target.path) {
+        (Some(authority), path) => (Some(authority), remove_dot_segments(path), target.query),
+        (None, "") => (base.authority, base.path.to_string(), target.query.or(base.query)),
+        (None, path) if path.starts_with('/') => (base.authority, remove_dot_segments(path), target.query),
+        (None, path) => (base.authority, remove_dot_segments(&merge(&base, path)), target.query),
+    };
+    Some(compose(Some(scheme), authority, &path, query, target.fragment))
+}
+
+// Whether `url` has a scheme, i.e. doesn't depend on a base.
+#[cfg(feature = "validate")]
+pub(crate) fn is_absolute(url: &str) -> bool {
+    Parts::split(url.trim()).scheme.is_some()
+}
+
+fn merge(base: &Parts, path: &str) -> String {
+    if base.authority.is_some() && base.path.is_empty() {
+        return format!("/{}", path);
+    }
+    match base.path.rfind('/') {
+        Some(slash) => format!("{}{}", &base.path[..=slash], path),
+        None => path.to_string(),
+    }
+}
+
+fn remove_dot_segments(path: &str) -> String {
+    let mut output: Vec<&str> = Vec::new();
+    let segments: Vec<&str> = path.split('/').collect();
+    for (index, segment) in segments.iter().enumerate() {
+        let last = index == segments.len() - 1;
+        match *segment {
+            "." => {
+                if last {
+                    output.push("");
+                }
+            }
+            ".." => {
+                if output.len() > 1 || output.first().is_some_and(|first| !first.is_empty()) {
+                    output.pop();
+                }
+                if last {
+                    output.push("");
+                }
+            }
+            segment => output.push(segment),
+        }
+    }
+    let mut result = output.join("/");
+    if path.starts_with('/') && !result.starts_with('/') {
+        result.insert(0, '/');
+    }
+    result
+}
+
+fn compose(scheme: Option<&str>, authority: Option<&str>, path: &str, query: Option<&str>, fragment: Option<&str>) -> String {
+    let mut uri = String::new();
+    if let Some(scheme) = scheme {
+        uri.push_str(scheme);
+        uri.push(':');
+    }
+    if let Some(authority) = authority {
+        uri.push_str("//");
+        uri.push_str(authority);
+    }
+    uri.push_str(path);
+    if let Some(query) = query {
+        uri.push('?');
+        uri.push_str(query);
+    }
+    if let Some(fragment) = fragment {
+        uri.push('#');
+        uri.push_str(fragment);
+    }
+    uri
+}
+
+// The `xml:base` in scope for a child of `parent` that declares `declared`.
+pub(crate) fn child_base(parent: Option<&str>, declared: Option<&str>) -> Option<String> {
+    match (parent, declared) {
+        (Some(parent), Some(declared)) => resolve_url(parent, declared).or_else(|| Some(declared.to_string())),
+        (None, Some(declared)) => Some(declared.to_string()),
+        (parent, None) => parent.map(String::from),
+    }
+}
+
+/// `url` resolved against `base` with the `urls` feature, unchanged otherwise
+/// or when `base` is not absolute.
+#[cfg(feature = "urls")]
+pub(crate) fn absolute(base: Option<&str>, url: String) -> String {
+    base.and_then(|base| resolve_url(base, &url)).unwrap_or(url)
+}
+
+#[cfg(not(feature = "urls"))]
+pub(crate) fn absolute(_base: Option<&str>, url: String) -> String {
+    url
+}
+
+// The node's text as a URL, resolved against its `xml:base`.
+pub(crate) fn url_text(node: XmlNode) -> Option<String> {
+    let base = node.base.clone();
+    node.into_text().map(|url| absolute(base.as_deref(), url))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rfc3986_examples() {
+        let base = "http://a/b/c/d;p?q";
+        for (reference, expected) in [
+            ("g:h", "g:h"),
+            ("g", "http://a/b/c/g"),
+            ("./g", "http://a/b/c/g"),
+            ("g/", "http://a/b/c/g/"),
+            ("/g", "http://a/g"),
+            ("//g", "http://g"),
+            ("?y", "http://a/b/c/d;p?y"),
+            ("g?y", "http://a/b/c/g?y"),
+            ("#s", "http://a/b/c/d;p?q#s"),
+            ("g#s", "http://a/b/c/g#s"),
+            ("", "http://a/b/c/d;p?q"),
+            (".", "http://a/b/c/"),
+            ("./", "http://a/b/c/"),
+            ("..", "http://a/b/"),
+            ("../g", "http://a/b/g"),
+            ("../..", "http://a/"),
+            ("../../g", "http://a/g"),
+            ("../../../g", "http://a/g"),
+            ("/./g", "http://a/g"),
+            ("g.", "http://a/b/c/g."),
+            ("g/../h", "http://a/b/c/h"),
+            ("g;x=1/./y", "http://a/b/c/g;x=1/y"),
+        ] {
+            assert_eq!(resolve_url(base, reference).as_deref(), Some(expected), "{reference}");
+        }
+    }
+
+    #[test]
+    fn test_relative_base() {
+        assert_eq!(resolve_url("/relative/", "a"), None);
+        assert_eq!(resolve_url("https://example.com", "post/1").as_deref(), Some("https://example.com/post/1"));
+        assert_eq!(child_base(Some("https://example.com/blog/"), Some("2024/")).as_deref(), Some("https://example.com/blog/2024/"));
+        assert_eq!(child_base(None, Some("/dir/")).as_deref(), Some("/dir/"));
+    }
+}
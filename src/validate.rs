@@ -0,0 +1,386 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use tokio::io::AsyncRead;
+
+use crate::atom::AtomEntry;
+use crate::builder::ParseMode;
+use crate::channel::ChannelInfo;
+use crate::date::parse_date;
+use crate::error::{Location, RssError};
+use crate::feed::{FeedFormat, FeedParser};
+use crate::item::RssItem;
+use crate::rss_parser::{GradualRssChannel, GradualRssItem, RssParser, XmlNode};
+use crate::url::is_absolute;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The feed breaks its specification.
+    Error,
+    /// Allowed, but likely to trip up readers.
+    Warning,
+}
+
+/// One finding of `validate`. Item problems are located at the item's start
+/// tag and malformed XML by byte offset; channel problems have no location.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    pub severity: Severity,
+    pub message: String,
+    pub location: Option<Location>,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        match &self.location {
+            Some(location) => write!(f, "{} at {}: {}", severity, location, self.message),
+            None => write!(f, "{}: {}", severity, self.message),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationReport {
+    pub format: FeedFormat,
+    /// Items (or entries) read, including invalid ones.
+    pub items: usize,
+    /// In document order, except that channel problems come first.
+    pub issues: Vec<Issue>,
+}
+
+impl ValidationReport {
+    /// No errors; warnings are allowed.
+    pub fn is_valid(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &Issue> {
+        self.issues.iter().filter(|issue| issue.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &Issue> {
+        self.issues.iter().filter(|issue| issue.severity == Severity::Warning)
+    }
+}
+
+/// Checks a feed against the RSS 2.0, RSS 1.0 or Atom 1.0 requirements:
+/// required channel and item elements, parseable dates, absolute URLs (once
+/// `xml:base` and the channel link are applied) and unique item ids.
+///
+/// The feed is streamed; only the ids seen so far are kept. Malformed items
+/// are reported and skipped. Errors are returned for I/O failures and for
+/// documents that aren't feeds at all.
+pub async fn validate<R: AsyncRead + Unpin>(input: R) -> Result<ValidationReport, RssError> {
+    let parser = RssParser::builder().mode(ParseMode::Lenient).track_lines(true).build(input).await?;
+    let mut validator = Validator::default();
+    let format = match FeedParser::from_parser(parser).await? {
+        FeedParser::Rss(mut parser) => {
+            while let Some(item) = parser.next().await? {
+                validator.at(parser.item_location());
+                validator.rss_item(&item, FeedFormat::Rss);
+            }
+            validator.rss_channel(&parser.channel().await?, FeedFormat::Rss);
+            validator.parse_warnings(&parser);
+            FeedFormat::Rss
+        }
+        FeedParser::Rdf(mut parser) => {
+            while let Some(item) = parser.next().await? {
+                validator.at(parser.item_location());
+                validator.rss_item(&item, FeedFormat::Rdf);
+            }
+            validator.rss_channel(&parser.channel().await?, FeedFormat::Rdf);
+            validator.parse_warnings(&parser);
+            FeedFormat::Rdf
+        }
+        FeedParser::Atom(mut parser) => {
+            while let Some(entry) = parser.next().await? {
+                validator.at(parser.item_location());
+                validator.atom_entry(&entry);
+            }
+            validator.atom_feed(&parser.channel().await?);
+            validator.parse_warnings(&parser);
+            FeedFormat::Atom
+        }
+    };
+
+    let Validator { items, channel_issues, mut issues, .. } = validator;
+    let mut all = channel_issues;
+    all.append(&mut issues);
+    Ok(ValidationReport { format, items, issues: all })
+}
+
+// The channel elements `ChannelInfo` doesn't keep.
+#[derive(Default)]
+struct Channel {
+    info: ChannelInfo,
+    id: Option<String>,
+    pub_date: Option<String>,
+}
+
+impl GradualRssChannel for Channel {
+    fn init() -> Self {
+        Channel::default()
+    }
+
+    fn populate(&mut self, node: XmlNode) {
+        match node.path.as_str() {
+            "id" => self.id = node.clone().into_text(),
+            "pubdate" => self.pub_date = node.clone().into_text(),
+            _ => {}
+        }
+        self.info.populate(node);
+    }
+}
+
+#[derive(Default)]
+struct Validator {
+    items: usize,
+    ids: HashSet<String>,
+    // Where the item being checked starts.
+    location: Option<Location>,
+    channel_issues: Vec<Issue>,
+    issues: Vec<Issue>,
+}
+
+impl Validator {
+    fn at(&mut self, location: Option<Location>) {
+        self.items += 1;
+        self.location = location;
+    }
+
+    fn report(&mut self, severity: Severity, message: String) {
+        self.issues.push(Issue {
+            severity,
+            message,
+            location: self.location,
+        });
+    }
+
+    fn error(&mut self, message: String) {
+        self.report(Severity::Error, message);
+    }
+
+    fn required(&mut self, value: Option<&str>, what: &str) {
+        if value.is_none_or(|value| value.trim().is_empty()) {
+            self.error(format!("{} is missing", what));
+        }
+    }
+
+    fn date(&mut self, value: Option<&str>, what: &str) {
+        if let Some(value) = value
+            && parse_date(value).is_none()
+        {
+            self.error(format!("{} {:?} is not a valid date", what, value));
+        }
+    }
+
+    fn url(&mut self, value: Option<&str>, what: &str) {
+        if let Some(value) = value
+            && !is_absolute(value)
+        {
+            self.error(format!("{} {:?} is not an absolute URL", what, value));
+        }
+    }
+
+    fn unique(&mut self, id: &str, what: &str) {
+        if !self.ids.insert(id.to_string()) {
+            self.error(format!("duplicate {} {:?}", what, id));
+        }
+    }
+
+    // The channel is checked once everything is read, as its elements may
+    // follow the items, but its issues are listed first.
+    fn channel(&mut self, check: impl FnOnce(&mut Self)) {
+        let issues = std::mem::take(&mut self.issues);
+        self.location = None;
+        check(self);
+        self.channel_issues = std::mem::replace(&mut self.issues, issues);
+    }
+
+    fn rss_channel(&mut self, channel: &Channel, format: FeedFormat) {
+        self.channel(|v| {
+            let info = &channel.info;
+            v.required(info.title.as_deref(), "channel <title>");
+            v.required(info.link.as_deref(), "channel <link>");
+            v.required(info.description.as_deref(), "channel <description>");
+            v.url(info.link.as_deref(), "channel <link>");
+            if let Some(image) = &info.image {
+                v.url(image.url.as_deref(), "channel <image> <url>");
+            }
+            if format == FeedFormat::Rss {
+                v.date(channel.pub_date.as_deref(), "channel <pubDate>");
+                v.date(info.last_build_date.as_deref(), "channel <lastBuildDate>");
+            }
+        });
+    }
+
+    fn rss_item(&mut self, item: &RssItem, format: FeedFormat) {
+        if format == FeedFormat::Rdf {
+            self.required(item.title.as_deref(), "item <title>");
+            self.required(item.link.as_deref(), "item <link>");
+        } else if item.title.is_none() && item.description.is_none() {
+            self.error("item has neither <title> nor <description>".to_string());
+        }
+        self.url(item.link.as_deref(), "item <link>");
+        self.url(item.comments.as_deref(), "item <comments>");
+        if let Some(enclosure) = &item.enclosure {
+            self.url(Some(&enclosure.url), "<enclosure> url");
+        }
+        self.date(item.pub_date.as_deref(), "item <pubDate>");
+        match &item.guid {
+            Some(guid) => {
+                if guid.is_permalink {
+                    self.url(Some(&guid.value), "permalink <guid>");
+                }
+                self.unique(&guid.value, "<guid>");
+            }
+            None if format == FeedFormat::Rss => {
+                self.report(Severity::Warning, "item has no <guid>".to_string());
+            }
+            None => {}
+        }
+    }
+
+    fn atom_feed(&mut self, feed: &Channel) {
+        self.channel(|v| {
+            let info = &feed.info;
+            v.required(feed.id.as_deref(), "feed <id>");
+            v.required(info.title.as_deref(), "feed <title>");
+            v.required(info.last_build_date.as_deref(), "feed <updated>");
+            v.date(info.last_build_date.as_deref(), "feed <updated>");
+            v.url(info.link.as_deref(), "feed <link>");
+            if info.link.is_none() {
+                v.report(Severity::Warning, "feed has no alternate <link>".to_string());
+            }
+        });
+    }
+
+    fn atom_entry(&mut self, entry: &AtomEntry) {
+        self.required(entry.id.as_deref(), "entry <id>");
+        self.required(entry.title.as_deref(), "entry <title>");
+        self.required(entry.updated.as_deref(), "entry <updated>");
+        self.date(entry.updated.as_deref(), "entry <updated>");
+        self.date(entry.published.as_deref(), "entry <published>");
+        // Entries have already inherited the feed's authors.
+        if entry.authors.is_empty() {
+            self.error("entry has no <author> and neither has the feed".to_string());
+        }
+        for link in &entry.links {
+            self.url(Some(&link.href), "entry <link> href");
+        }
+        if let Some(id) = &entry.id {
+            self.unique(id, "entry <id>");
+        }
+    }
+
+    // Malformed XML the lenient parser skipped over.
+    fn parse_warnings<T: GradualRssItem, R: AsyncRead + Unpin>(&mut self, parser: &RssParser<T, R>) {
+        for warning in parser.warnings() {
+            let severity = match warning {
+                RssError::UnknownEncoding(_) | RssError::LimitExceeded { .. } => Severity::Warning,
+                _ => Severity::Error,
+            };
+            self.issues.push(Issue {
+                severity,
+                message: warning.to_string(),
+                location: warning.position().map(|position| parser.locate(position)),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    async fn report(feed: &str) -> ValidationReport {
+        validate(Cursor::new(feed.as_bytes().to_vec())).await.unwrap()
+    }
+
+    fn messages(report: &ValidationReport) -> Vec<String> {
+        report.issues.iter().map(ToString::to_string).collect()
+    }
+
+    #[tokio::test]
+    async fn test_valid_rss() {
+        let report = report(
+            r#"<rss version="2.0"><channel>
+<title>T</title><link>https://example.com/</link><description>D</description>
+<item><title>One</title><link>/1</link><guid>https://example.com/1</guid><pubDate>Mon, 01 Jan 2024 00:00:00 GMT</pubDate></item>
+</channel></rss>"#,
+        )
+        .await;
+        assert_eq!(report.format, FeedFormat::Rss);
+        assert_eq!(report.items, 1);
+        assert!(report.is_valid(), "{:?}", messages(&report));
+        assert!(report.issues.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_rss() {
+        let report = report(
+            r#"<rss version="2.0"><channel>
+<title>T</title><link>example.com</link>
+<item><title>One</title><guid>a</guid><pubDate>yesterday</pubDate></item>
+<item><description>Two</description><guid isPermaLink="false">a</guid></item>
+<item><title>Three</title></item>
+</channel></rss>"#,
+        )
+        .await;
+        assert!(!report.is_valid());
+        assert_eq!(
+            messages(&report),
+            vec![
+                r#"error: channel <description> is missing"#,
+                r#"error: channel <link> "example.com" is not an absolute URL"#,
+                r#"error at line 3, column 1: item <pubDate> "yesterday" is not a valid date"#,
+                r#"error at line 3, column 1: permalink <guid> "a" is not an absolute URL"#,
+                r#"error at line 4, column 1: duplicate <guid> "a""#,
+                r#"warning at line 5, column 1: item has no <guid>"#,
+            ]
+        );
+        assert_eq!(report.warnings().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_atom() {
+        let report = report(
+            r#"<feed xmlns="http://www.w3.org/2005/Atom">
+<id>urn:feed</id><title>T</title><updated>2024-01-01T00:00:00Z</updated><link href="https://example.org/"/>
+<entry><id>urn:1</id><title>One</title><updated>2024-01-01T00:00:00Z</updated><author><name>A</name></author></entry>
+<entry><id>urn:1</id><title>Two</title></entry>
+</feed>"#,
+        )
+        .await;
+        assert_eq!(report.format, FeedFormat::Atom);
+        assert_eq!(
+            messages(&report),
+            vec![
+                "error at line 4, column 1: entry <updated> is missing",
+                "error at line 4, column 1: entry has no <author> and neither has the feed",
+                r#"error at line 4, column 1: duplicate entry <id> "urn:1""#,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_malformed_item() {
+        let report = report(
+            r#"<rss><channel><title>T</title><link>https://example.com/</link><description>D</description>
+<item><title>One</b></item>
+<item><title>Two</title><guid>https://example.com/2</guid></item>
+</channel></rss>"#,
+        )
+        .await;
+        let errors: Vec<_> = report.errors().collect();
+        assert_eq!(report.items, 1);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.starts_with("XML error"), "{}", errors[0]);
+        assert!(errors[0].location.is_some());
+    }
+}
@@ -0,0 +1,456 @@
+use std::collections::HashMap;
+use std::io::{self, Cursor};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tokio::sync::mpsc;
+
+use crate::channel::ChannelInfo;
+use crate::error::RssError;
+use crate::feed::FeedParser;
+use crate::feed_item::FeedItem;
+use crate::hmac::{constant_time_eq, hmac, to_hex, Algorithm};
+use crate::http::{fetch, post_form, FetchOptions, HttpError};
+
+// The query parameter added to the callback URL to tell subscriptions apart.
+// Its values are random, so a subscription can't be reached by guessing.
+const SUBSCRIPTION_PARAM: &str = "websub";
+
+/// Receives items pushed by WebSub hubs instead of polling for them.
+///
+/// The subscriber doesn't run a server: mount `handle` at the `callback` URL
+/// in whatever HTTP server the application already has, passing it every
+/// request made to that URL. Hubs verify each subscription with a `GET`, which
+/// `handle` answers, and deliver new content with signed `POST`s, which come
+/// out of the receiver returned by `new` as `Notification`s. Content whose
+/// signature doesn't match the subscription's secret is dropped.
+pub struct WebSubSubscriber {
+    callback: String,
+    state: Mutex<State>,
+    sender: mpsc::Sender<Notification>,
+    options: FetchOptions,
+}
+
+#[derive(Default)]
+struct State {
+    subscriptions: HashMap<String, Subscription>,
+}
+
+struct Subscription {
+    hub: String,
+    topic: String,
+    secret: String,
+    status: Status,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    // Requested, waiting for the hub to verify it.
+    Pending,
+    Active { expires: Option<Instant> },
+    Unsubscribing,
+}
+
+/// Content a hub pushed for a subscribed topic.
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub topic: String,
+    /// The feed document (or the part of it with the new items) as the hub sent it.
+    pub body: Vec<u8>,
+}
+
+impl Notification {
+    pub async fn items(&self) -> Result<Vec<FeedItem>, RssError> {
+        let mut parser = FeedParser::detect(Cursor::new(self.body.as_slice())).await?;
+        let mut items = Vec::new();
+        while let Some(item) = parser.next().await? {
+            items.push(item);
+        }
+        Ok(items)
+    }
+}
+
+/// A request to the callback URL, as the application's HTTP server received it.
+#[derive(Debug, Clone, Copy)]
+pub struct CallbackRequest<'a> {
+    pub method: &'a str,
+    /// The query string, without the `?`.
+    pub query: &'a str,
+    /// The `X-Hub-Signature` header.
+    pub signature: Option<&'a str>,
+    pub body: &'a [u8],
+}
+
+/// What to answer a `CallbackRequest` with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallbackResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+impl CallbackResponse {
+    fn status(status: u16) -> Self {
+        CallbackResponse {
+            status,
+            body: String::new(),
+        }
+    }
+}
+
+impl WebSubSubscriber {
+    /// `callback` is the public URL `handle` is mounted at.
+    pub fn new(callback: impl Into<String>, buffer: usize) -> (Self, mpsc::Receiver<Notification>) {
+        let (sender, receiver) = mpsc::channel(buffer.max(1));
+        let subscriber = WebSubSubscriber {
+            callback: callback.into(),
+            state: Mutex::new(State::default()),
+            sender,
+            options: FetchOptions::default(),
+        };
+        (subscriber, receiver)
+    }
+
+    /// Makes the requests to feeds and hubs with `options`, e.g. with a
+    /// `TlsConnector`, since hubs are almost always `https://`.
+    pub fn options(mut self, options: FetchOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Fetches the feed at `url` and subscribes to it at its first hub, with
+    /// its `rel="self"` link as the topic. Returns false if the feed names no
+    /// hub, so it has to be polled.
+    pub async fn subscribe_feed(&self, url: &str, lease: Option<Duration>) -> Result<bool, RssError> {
+        let (_, body) = fetch(url, &self.options).await?;
+        let channel: ChannelInfo = FeedParser::detect(body).await?.channel().await?;
+        let Some(hub) = channel.hubs.first() else {
+            return Ok(false);
+        };
+        let topic = channel.self_link.as_deref().unwrap_or(url);
+        self.subscribe(hub, topic, lease).await?;
+        Ok(true)
+    }
+
+    /// Asks `hub` to push `topic` to the callback, for `lease` or the hub's
+    /// default. Subscribing to a topic again renews the subscription.
+    ///
+    /// The subscription is only active once the hub has verified it through
+    /// `handle`.
+    pub async fn subscribe(&self, hub: &str, topic: &str, lease: Option<Duration>) -> Result<(), RssError> {
+        let (id, secret) = {
+            let mut state = self.state.lock().unwrap();
+            match state.subscriptions.iter_mut().find(|(_, subscription)| subscription.topic == topic) {
+                Some((id, subscription)) => {
+                    subscription.hub = hub.to_string();
+                    if subscription.status == Status::Unsubscribing {
+                        subscription.status = Status::Pending;
+                    }
+                    (id.clone(), subscription.secret.clone())
+                }
+                None => {
+                    let id = random_hex(16)?;
+                    let secret = random_hex(32)?;
+                    state.subscriptions.insert(
+                        id.clone(),
+                        Subscription {
+                            hub: hub.to_string(),
+                            topic: topic.to_string(),
+                            secret: secret.clone(),
+                            status: Status::Pending,
+                        },
+                    );
+                    (id, secret)
+                }
+            }
+        };
+        let lease = lease.map(|lease| lease.as_secs().to_string());
+        let mut form = vec![
+            ("hub.mode", "subscribe"),
+            ("hub.topic", topic),
+            ("hub.secret", secret.as_str()),
+        ];
+        if let Some(lease) = &lease {
+            form.push(("hub.lease_seconds", lease));
+        }
+        let result = self.request(hub, &id, form).await;
+        if result.is_err() {
+            let mut state = self.state.lock().unwrap();
+            if state.subscriptions.get(&id).is_some_and(|subscription| subscription.status == Status::Pending) {
+                state.subscriptions.remove(&id);
+            }
+        }
+        result
+    }
+
+    /// Asks the hub to stop pushing `topic`. The subscription is dropped once
+    /// the hub has verified that.
+    pub async fn unsubscribe(&self, topic: &str) -> Result<(), RssError> {
+        let (id, hub) = {
+            let mut state = self.state.lock().unwrap();
+            let Some((id, subscription)) = state.subscriptions.iter_mut().find(|(_, subscription)| subscription.topic == topic) else {
+                return Ok(());
+            };
+            subscription.status = Status::Unsubscribing;
+            (id.clone(), subscription.hub.clone())
+        };
+        self.request(&hub, &id, vec![("hub.mode", "unsubscribe"), ("hub.topic", topic)]).await
+    }
+
+    /// Renews the active subscriptions whose lease ends within `within`,
+    /// returning how many. Call it periodically, e.g. once an hour.
+    pub async fn renew(&self, within: Duration) -> Result<usize, RssError> {
+        let deadline = Instant::now() + within;
+        let expiring: Vec<(String, String)> = {
+            let state = self.state.lock().unwrap();
+            state
+                .subscriptions
+                .values()
+                .filter(|subscription| matches!(subscription.status, Status::Active { expires: Some(expires) } if expires <= deadline))
+                .map(|subscription| (subscription.hub.clone(), subscription.topic.clone()))
+                .collect()
+        };
+        for (hub, topic) in &expiring {
+            self.subscribe(hub, topic, None).await?;
+        }
+        Ok(expiring.len())
+    }
+
+    /// The topics with an active (verified) subscription.
+    pub fn topics(&self) -> Vec<String> {
+        let state = self.state.lock().unwrap();
+        state
+            .subscriptions
+            .values()
+            .filter(|subscription| matches!(subscription.status, Status::Active { .. }))
+            .map(|subscription| subscription.topic.clone())
+            .collect()
+    }
+
+    async fn request(&self, hub: &str, id: &str, mut form: Vec<(&str, &str)>) -> Result<(), RssError> {
+        let separator = if self.callback.contains('?') { '&' } else { '?' };
+        let callback = format!("{}{}{}={}", self.callback, separator, SUBSCRIPTION_PARAM, id);
+        form.push(("hub.callback", &callback));
+        let form: Vec<String> = form.iter().map(|(name, value)| format!("{}={}", encode(name), encode(value))).collect();
+        match post_form(hub, &self.options, &form.join("&")).await? {
+            200..=299 => Ok(()),
+            status => Err(RssError::Http(HttpError::Status(status))),
+        }
+    }
+
+    /// Answers a request to the callback URL: hubs' verification `GET`s
+    /// and content `POST`s.
+    pub async fn handle(&self, request: CallbackRequest<'_>) -> CallbackResponse {
+        let query = decode_form(request.query);
+        let param = |name: &str| query.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str());
+        let Some(id) = param(SUBSCRIPTION_PARAM) else {
+            return CallbackResponse::status(404);
+        };
+
+        if request.method.eq_ignore_ascii_case("GET") {
+            return self.verify(id, param("hub.mode"), param("hub.topic"), param("hub.challenge"), param("hub.lease_seconds"));
+        }
+        if !request.method.eq_ignore_ascii_case("POST") {
+            return CallbackResponse::status(405);
+        }
+
+        let (topic, secret) = {
+            let state = self.state.lock().unwrap();
+            match state.subscriptions.get(id) {
+                Some(subscription) if subscription.status != Status::Pending => (subscription.topic.clone(), subscription.secret.clone()),
+                // Tells the hub to stop delivering.
+                _ => return CallbackResponse::status(410),
+            }
+        };
+        // Hubs must get a success response even for a bad signature.
+        if request.signature.is_some_and(|signature| verify_signature(signature, &secret, request.body)) {
+            let notification = Notification {
+                topic,
+                body: request.body.to_vec(),
+            };
+            let _ = self.sender.send(notification).await;
+        }
+        CallbackResponse::status(202)
+    }
+
+    fn verify(&self, id: &str, mode: Option<&str>, topic: Option<&str>, challenge: Option<&str>, lease: Option<&str>) -> CallbackResponse {
+        let mut state = self.state.lock().unwrap();
+        let Some(subscription) = state.subscriptions.get_mut(id) else {
+            return CallbackResponse::status(404);
+        };
+        // Every verification names the topic, denials included.
+        if topic != Some(subscription.topic.as_str()) {
+            return CallbackResponse::status(404);
+        }
+        let challenge = challenge.unwrap_or_default().to_string();
+        match (mode, subscription.status) {
+            (Some("subscribe"), Status::Pending | Status::Active { .. }) => {
+                let lease = lease.and_then(|lease| lease.parse().ok()).map(Duration::from_secs);
+                subscription.status = Status::Active {
+                    expires: lease.map(|lease| Instant::now() + lease),
+                };
+            }
+            (Some("unsubscribe"), Status::Unsubscribing) => {
+                state.subscriptions.remove(id);
+            }
+            // The hub refused the subscription.
+            (Some("denied"), _) => {
+                state.subscriptions.remove(id);
+                return CallbackResponse::status(200);
+            }
+            _ => return CallbackResponse::status(404),
+        }
+        CallbackResponse {
+            status: 200,
+            body: challenge,
+        }
+    }
+}
+
+// `X-Hub-Signature: sha256=<hex>`, or `sha1`.
+fn verify_signature(header: &str, secret: &str, body: &[u8]) -> bool {
+    let Some((method, signature)) = header.trim().split_once('=') else {
+        return false;
+    };
+    let Some(algorithm) = Algorithm::from_name(method) else {
+        return false;
+    };
+    let expected = to_hex(&hmac(algorithm, secret.as_bytes(), body));
+    constant_time_eq(expected.as_bytes(), signature.to_ascii_lowercase().as_bytes())
+}
+
+// `bytes` from the operating system's secure random source, in hex.
+fn random_hex(bytes: usize) -> Result<String, RssError> {
+    let mut random = vec![0u8; bytes];
+    getrandom::fill(&mut random).map_err(|err| RssError::Io(io::Error::other(err.to_string())))?;
+    Ok(to_hex(&random))
+}
+
+fn encode(text: &str) -> String {
+    let mut out = String::new();
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => out.push(byte as char),
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+fn decode_form(query: &str) -> Vec<(String, String)> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(name), decode(value))
+        })
+        .collect()
+}
+
+fn decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    let hex = |byte: u8| (byte as char).to_digit(16).map(|digit| digit as u8);
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() && hex(bytes[i + 1]).is_some() && hex(bytes[i + 2]).is_some() => {
+                out.push(hex(bytes[i + 1]).unwrap_or_default() * 16 + hex(bytes[i + 2]).unwrap_or_default());
+                i += 2;
+            }
+            byte => out.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    // A hub that accepts one subscription request and hands back its form.
+    async fn hub() -> (String, tokio::task::JoinHandle<Vec<(String, String)>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hub", listener.local_addr().unwrap());
+        let handle = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            while !String::from_utf8_lossy(&request).contains("hub.callback") {
+                let read = socket.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..read]);
+            }
+            socket.write_all(b"HTTP/1.1 202 Accepted\r\nContent-Length: 0\r\n\r\n").await.unwrap();
+            let request = String::from_utf8(request).unwrap();
+            decode_form(request.split("\r\n\r\n").nth(1).unwrap())
+        });
+        (url, handle)
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_verify_and_receive() {
+        let (subscriber, mut notifications) = WebSubSubscriber::new("https://reader.example.com/websub", 4);
+        let (hub, form) = hub().await;
+        subscriber.subscribe(&hub, "https://example.com/feed.xml", Some(Duration::from_secs(3600))).await.unwrap();
+
+        let form: HashMap<String, String> = form.await.unwrap().into_iter().collect();
+        assert_eq!(form["hub.mode"], "subscribe");
+        assert_eq!(form["hub.topic"], "https://example.com/feed.xml");
+        assert_eq!(form["hub.lease_seconds"], "3600");
+        let query = form["hub.callback"].split_once('?').unwrap().1.to_string();
+        let secret = &form["hub.secret"];
+        assert!(subscriber.topics().is_empty());
+
+        // The hub checks that the subscription was wanted.
+        let verify = format!("{}&hub.mode=subscribe&hub.topic=https%3A%2F%2Fexample.com%2Ffeed.xml&hub.challenge=abc+123&hub.lease_seconds=60", query);
+        let get = |query| CallbackRequest {
+            method: "GET",
+            query,
+            signature: None,
+            body: b"",
+        };
+        let post = |query, signature, body| CallbackRequest {
+            method: "POST",
+            query,
+            signature,
+            body,
+        };
+        let response = subscriber.handle(get(&verify)).await;
+        assert_eq!(response, CallbackResponse { status: 200, body: "abc 123".to_string() });
+        assert_eq!(subscriber.topics(), vec!["https://example.com/feed.xml"]);
+
+        let feed = b"<rss><channel><item><title>Pushed</title></item></channel></rss>";
+        let signature = format!("sha256={}", to_hex(&hmac(Algorithm::Sha256, secret.as_bytes(), feed)));
+        assert_eq!(subscriber.handle(post(&query, Some("sha256=00"), feed)).await.status, 202);
+        assert_eq!(subscriber.handle(post(&query, None, feed)).await.status, 202);
+        assert_eq!(subscriber.handle(post(&query, Some(&signature), feed)).await.status, 202);
+
+        // Only the content with the right signature gets through.
+        let notification = notifications.recv().await.unwrap();
+        assert_eq!(notification.topic, "https://example.com/feed.xml");
+        assert_eq!(notification.items().await.unwrap()[0].title.as_deref(), Some("Pushed"));
+        assert!(notifications.try_recv().is_err());
+
+        assert_eq!(subscriber.handle(post("websub=0", Some(&signature), feed)).await.status, 410);
+        // Nobody asked to unsubscribe.
+        let topic = "hub.topic=https%3A%2F%2Fexample.com%2Ffeed.xml";
+        let unsubscribe = format!("{}&hub.mode=unsubscribe&{}", query, topic);
+        assert_eq!(subscriber.handle(get(&unsubscribe)).await.status, 404);
+        // A denial has to name the topic, and the callback id can't be guessed.
+        assert_eq!(query.strip_prefix("websub=").unwrap().len(), 32);
+        let (untopical, guessed, denied) = (
+            format!("{}&hub.mode=denied", query),
+            format!("websub=0&hub.mode=denied&{}", topic),
+            format!("{}&hub.mode=denied&{}", query, topic),
+        );
+        assert_eq!(subscriber.handle(get(&untopical)).await.status, 404);
+        assert_eq!(subscriber.handle(get(&guessed)).await.status, 404);
+        assert_eq!(subscriber.topics(), vec!["https://example.com/feed.xml"]);
+        assert_eq!(subscriber.handle(get(&denied)).await.status, 200);
+        assert!(subscriber.topics().is_empty());
+    }
+}
@@ -0,0 +1,525 @@
+use quick_xml::escape::escape;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio_stream::{Stream, StreamExt};
+
+use crate::atom::AtomEntry;
+use crate::channel::ChannelInfo;
+use crate::error::RssError;
+use crate::feed_item::FeedItem;
+use crate::item::RssItem;
+use crate::ns;
+use crate::person::Person;
+
+// An indented XML fragment that elements are appended to.
+#[derive(Default)]
+pub(crate) struct Markup {
+    pub(crate) out: String,
+    depth: usize,
+}
+
+impl Markup {
+    fn tag(&mut self, tag: &str, attrs: &[(&str, Option<&str>)]) {
+        self.out.push_str(&"  ".repeat(self.depth));
+        self.out.push('<');
+        self.out.push_str(tag);
+        for (name, value) in attrs {
+            if let Some(value) = value {
+                self.out.push_str(&format!(" {}=\"{}\"", name, escape(*value)));
+            }
+        }
+    }
+
+    pub(crate) fn open(&mut self, tag: &str, attrs: &[(&str, Option<&str>)]) {
+        self.tag(tag, attrs);
+        self.out.push_str(">\n");
+        self.depth += 1;
+    }
+
+    pub(crate) fn close(&mut self, tag: &str) {
+        self.depth -= 1;
+        self.out.push_str(&format!("{}</{}>\n", "  ".repeat(self.depth), tag));
+    }
+
+    pub(crate) fn empty(&mut self, tag: &str, attrs: &[(&str, Option<&str>)]) {
+        self.tag(tag, attrs);
+        self.out.push_str("/>\n");
+    }
+
+    fn element(&mut self, tag: &str, attrs: &[(&str, Option<&str>)], text: &str) {
+        self.tag(tag, attrs);
+        self.out.push_str(&format!(">{}</{}>\n", escape(text), tag));
+    }
+
+    // An element without attributes, written only when there is a value.
+    pub(crate) fn text(&mut self, tag: &str, text: Option<&str>) {
+        if let Some(text) = text {
+            self.element(tag, &[], text);
+        }
+    }
+
+    fn person(&mut self, tag: &str, person: &Person) {
+        self.open(tag, &[]);
+        self.text("name", person.name.as_deref());
+        self.text("email", person.email.as_deref());
+        self.text("uri", person.uri.as_deref());
+        self.close(tag);
+    }
+}
+
+// Writes `items` with `write_item` as they arrive and counts them.
+macro_rules! write_stream {
+    ($writer:expr, $items:expr) => {{
+        let mut items = std::pin::pin!($items);
+        let mut written = 0;
+        while let Some(item) = items.next().await {
+            $writer.write_item(&item?).await?;
+            written += 1;
+        }
+        Ok(written)
+    }};
+}
+
+/// Generates an RSS 2.0 document on any `AsyncWrite`, an item at a time.
+///
+/// Fields without an RSS 2.0 element go to the Dublin Core and content
+/// modules: `content` as `<content:encoded>`, `rights` as `<dc:rights>` and an
+/// author without an email address as `<dc:creator>`. Media RSS is not written.
+pub struct RssWriter<W> {
+    out: W,
+    language: Option<String>,
+}
+
+impl<W: AsyncWrite + Unpin> RssWriter<W> {
+    /// Writes the XML declaration and the channel elements.
+    pub async fn new(mut out: W, channel: &ChannelInfo) -> std::io::Result<Self> {
+        let mut markup = Markup::default();
+        markup.out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        markup.open(
+            "rss",
+            &[("version", Some("2.0")), ("xmlns:content", Some(ns::CONTENT)), ("xmlns:dc", Some(ns::DUBLIN_CORE))],
+        );
+        markup.open("channel", &[]);
+        markup.text("title", channel.title.as_deref());
+        markup.text("link", channel.link.as_deref());
+        markup.text("description", channel.description.as_deref());
+        markup.text("language", channel.language.as_deref());
+        markup.text("lastBuildDate", channel.last_build_date.as_deref());
+        if let Some(author) = &channel.author {
+            markup.text("managingEditor", rss_author(author).as_deref());
+        }
+        if let Some(image) = &channel.image {
+            markup.open("image", &[]);
+            markup.text("url", image.url.as_deref());
+            markup.text("title", image.title.as_deref());
+            markup.text("link", image.link.as_deref());
+            markup.text("width", image.width.map(|width| width.to_string()).as_deref());
+            markup.text("height", image.height.map(|height| height.to_string()).as_deref());
+            markup.text("description", image.description.as_deref());
+            markup.close("image");
+        }
+        markup.text("ttl", channel.ttl.map(|ttl| ttl.to_string()).as_deref());
+        if !channel.skip_hours.is_empty() {
+            markup.open("skipHours", &[]);
+            for hour in &channel.skip_hours {
+                markup.text("hour", Some(&hour.to_string()));
+            }
+            markup.close("skipHours");
+        }
+        if !channel.skip_days.is_empty() {
+            markup.open("skipDays", &[]);
+            for day in &channel.skip_days {
+                markup.text("day", Some(day));
+            }
+            markup.close("skipDays");
+        }
+        out.write_all(markup.out.as_bytes()).await?;
+        Ok(RssWriter {
+            out,
+            language: channel.language.clone(),
+        })
+    }
+
+    pub async fn write_item(&mut self, item: &RssItem) -> std::io::Result<()> {
+        let mut markup = Markup {
+            depth: 2,
+            ..Markup::default()
+        };
+        markup.open("item", &[]);
+        markup.text("title", item.title.as_deref());
+        markup.text("link", item.link.as_deref());
+        markup.text("description", item.description.as_deref());
+        markup.text("content:encoded", item.content.as_deref());
+        match item.author.as_ref().map(|author| (author, rss_author(author))) {
+            Some((_, Some(author))) => markup.text("author", Some(&author)),
+            Some((author, None)) => markup.text("dc:creator", author.name.as_deref()),
+            None => {}
+        }
+        for category in &item.categories {
+            markup.element("category", &[("domain", category.domain.as_deref())], &category.name);
+        }
+        markup.text("comments", item.comments.as_deref());
+        if let Some(enclosure) = &item.enclosure {
+            let length = enclosure.length.map(|length| length.to_string());
+            let mime_type = enclosure.mime_type.as_ref().map(ToString::to_string);
+            markup.empty(
+                "enclosure",
+                &[("url", Some(&enclosure.url)), ("length", length.as_deref()), ("type", mime_type.as_deref())],
+            );
+        }
+        if let Some(guid) = &item.guid {
+            let permalink = (!guid.is_permalink).then_some("false");
+            markup.element("guid", &[("isPermaLink", permalink)], &guid.value);
+        }
+        markup.text("pubDate", item.pub_date.as_deref());
+        markup.text("source", item.source.as_deref());
+        markup.text("dc:rights", item.rights.as_deref());
+        if item.language != self.language {
+            markup.text("dc:language", item.language.as_deref());
+        }
+        markup.close("item");
+        self.out.write_all(markup.out.as_bytes()).await
+    }
+
+    /// Writes every item of the stream, e.g. a filtered `RssStream`, and
+    /// returns how many there were. Stops at the first error.
+    pub async fn write_stream<S>(&mut self, items: S) -> Result<usize, RssError>
+    where
+        S: Stream<Item = Result<RssItem, RssError>>,
+    {
+        write_stream!(self, items)
+    }
+
+    /// Closes the document and flushes the output.
+    pub async fn finish(mut self) -> std::io::Result<W> {
+        self.out.write_all(b"  </channel>\n</rss>\n").await?;
+        self.out.flush().await?;
+        Ok(self.out)
+    }
+}
+
+// RSS `<author>` and `<managingEditor>` hold an email address, optionally
+// followed by the name in parentheses.
+fn rss_author(person: &Person) -> Option<String> {
+    let email = person.email.as_deref()?;
+    Some(match &person.name {
+        Some(name) => format!("{} ({})", email, name),
+        None => email.to_string(),
+    })
+}
+
+/// Generates an Atom 1.0 document on any `AsyncWrite`, an entry at a time.
+///
+/// `ChannelInfo` has no feed id, so the channel link doubles as `<id>`.
+/// Content is written as `type="html"`.
+pub struct AtomWriter<W> {
+    out: W,
+    language: Option<String>,
+}
+
+impl<W: AsyncWrite + Unpin> AtomWriter<W> {
+    /// Writes the XML declaration and the feed elements.
+    pub async fn new(mut out: W, channel: &ChannelInfo) -> std::io::Result<Self> {
+        let mut markup = Markup::default();
+        markup.out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        markup.open("feed", &[("xmlns", Some(ns::ATOM)), ("xml:lang", channel.language.as_deref())]);
+        markup.text("id", channel.link.as_deref());
+        markup.text("title", channel.title.as_deref());
+        markup.text("subtitle", channel.description.as_deref());
+        markup.text("updated", channel.last_build_date.as_deref());
+        if let Some(link) = &channel.link {
+            markup.empty("link", &[("rel", Some("alternate")), ("href", Some(link))]);
+        }
+        markup.text("logo", channel.image.as_ref().and_then(|image| image.url.as_deref()));
+        if let Some(author) = &channel.author {
+            markup.person("author", author);
+        }
+        out.write_all(markup.out.as_bytes()).await?;
+        Ok(AtomWriter {
+            out,
+            language: channel.language.clone(),
+        })
+    }
+
+    pub async fn write_item(&mut self, entry: &AtomEntry) -> std::io::Result<()> {
+        let mut markup = Markup {
+            depth: 1,
+            ..Markup::default()
+        };
+        let language = entry.language.as_deref().filter(|_| entry.language != self.language);
+        markup.open("entry", &[("xml:lang", language)]);
+        markup.text("id", entry.id.as_deref());
+        markup.text("title", entry.title.as_deref());
+        for link in &entry.links {
+            let mime_type = link.mime_type.as_ref().map(ToString::to_string);
+            markup.empty(
+                "link",
+                &[
+                    ("rel", Some(&link.rel)),
+                    ("href", Some(&link.href)),
+                    ("type", mime_type.as_deref()),
+                    ("title", link.title.as_deref()),
+                ],
+            );
+        }
+        markup.text("summary", entry.summary.as_deref());
+        if let Some(content) = &entry.content {
+            markup.element("content", &[("type", Some("html"))], content);
+        }
+        for author in &entry.authors {
+            markup.person("author", author);
+        }
+        for category in &entry.categories {
+            markup.empty("category", &[("term", Some(&category.name)), ("scheme", category.domain.as_deref())]);
+        }
+        markup.text("published", entry.published.as_deref());
+        markup.text("updated", entry.updated.as_deref());
+        markup.text("rights", entry.rights.as_deref());
+        markup.close("entry");
+        self.out.write_all(markup.out.as_bytes()).await
+    }
+
+    /// Writes every entry of the stream and returns how many there were.
+    /// Stops at the first error.
+    pub async fn write_stream<S>(&mut self, entries: S) -> Result<usize, RssError>
+    where
+        S: Stream<Item = Result<AtomEntry, RssError>>,
+    {
+        write_stream!(self, entries)
+    }
+
+    /// Closes the document and flushes the output.
+    pub async fn finish(mut self) -> std::io::Result<W> {
+        self.out.write_all(b"</feed>\n").await?;
+        self.out.flush().await?;
+        Ok(self.out)
+    }
+}
+
+/// Generates a JSON Feed 1.1 (https://jsonfeed.org) document on any
+/// `AsyncWrite`, an item at a time.
+///
+/// JSON Feed authors have no email address, so it is written as a `mailto:`
+/// URL when the author has no URI. Items without an id get their link, as the
+/// id is required.
+pub struct JsonFeedWriter<W> {
+    out: W,
+    first: bool,
+}
+
+impl<W: AsyncWrite + Unpin> JsonFeedWriter<W> {
+    /// Writes the top-level fields and opens `items`.
+    pub async fn new(mut out: W, channel: &ChannelInfo) -> std::io::Result<Self> {
+        let mut feed = Map::new();
+        feed.insert("version".to_string(), json!("https://jsonfeed.org/version/1.1"));
+        insert(&mut feed, "title", channel.title.as_deref());
+        insert(&mut feed, "home_page_url", channel.link.as_deref());
+        insert(&mut feed, "description", channel.description.as_deref());
+        insert(&mut feed, "language", channel.language.as_deref());
+        insert(&mut feed, "icon", channel.image.as_ref().and_then(|image| image.url.as_deref()));
+        if let Some(author) = &channel.author {
+            feed.insert("authors".to_string(), json!([json_author(author)]));
+        }
+
+        let mut head = Value::Object(feed).to_string();
+        head.pop();
+        head.push_str(",\"items\":[");
+        out.write_all(head.as_bytes()).await?;
+        Ok(JsonFeedWriter { out, first: true })
+    }
+
+    pub async fn write_item(&mut self, item: &FeedItem) -> std::io::Result<()> {
+        let mut object = Map::new();
+        insert(&mut object, "id", item.id.as_deref().or(item.link()));
+        insert(&mut object, "url", item.link());
+        insert(&mut object, "title", item.title.as_deref());
+        insert(&mut object, "summary", item.summary.as_deref());
+        insert(&mut object, "content_html", item.content.as_deref());
+        insert(&mut object, "date_published", item.published.as_deref());
+        insert(&mut object, "date_modified", item.updated.as_deref());
+        insert(&mut object, "language", item.language.as_deref());
+        if !item.authors.is_empty() {
+            object.insert("authors".to_string(), item.authors.iter().map(json_author).collect());
+        }
+        if !item.categories.is_empty() {
+            object.insert("tags".to_string(), item.categories.iter().map(|category| json!(category.name)).collect());
+        }
+        let attachments: Vec<Value> = item
+            .links
+            .iter()
+            .filter(|link| link.rel == "enclosure")
+            .map(|link| {
+                let mut attachment = Map::new();
+                insert(&mut attachment, "url", Some(&link.href));
+                insert(&mut attachment, "mime_type", link.mime_type.as_ref().map(ToString::to_string).as_deref());
+                Value::Object(attachment)
+            })
+            .collect();
+        if !attachments.is_empty() {
+            object.insert("attachments".to_string(), Value::Array(attachments));
+        }
+
+        if !std::mem::take(&mut self.first) {
+            self.out.write_all(b",").await?;
+        }
+        self.out.write_all(Value::Object(object).to_string().as_bytes()).await
+    }
+
+    /// Writes every item of the stream and returns how many there were.
+    /// Stops at the first error.
+    pub async fn write_stream<S>(&mut self, items: S) -> Result<usize, RssError>
+    where
+        S: Stream<Item = Result<FeedItem, RssError>>,
+    {
+        write_stream!(self, items)
+    }
+
+    /// Closes the document and flushes the output.
+    pub async fn finish(mut self) -> std::io::Result<W> {
+        self.out.write_all(b"]}\n").await?;
+        self.out.flush().await?;
+        Ok(self.out)
+    }
+}
+
+/// Writes each item of the stream as one line of JSON (NDJSON), for piping a
+/// feed into data pipelines. Returns the number of items written; stops at the
+/// first error.
+pub async fn write_ndjson<S, T, W>(items: S, mut out: W) -> Result<usize, RssError>
+where
+    S: Stream<Item = Result<T, RssError>>,
+    T: Serialize,
+    W: AsyncWrite + Unpin,
+{
+    let mut items = std::pin::pin!(items);
+    let mut written = 0;
+    let mut line = Vec::new();
+    while let Some(item) = items.next().await {
+        line.clear();
+        serde_json::to_writer(&mut line, &item?)?;
+        line.push(b'\n');
+        out.write_all(&line).await?;
+        written += 1;
+    }
+    out.flush().await?;
+    Ok(written)
+}
+
+fn insert(object: &mut Map<String, Value>, key: &str, value: Option<&str>) {
+    if let Some(value) = value {
+        object.insert(key.to_string(), json!(value));
+    }
+}
+
+fn json_author(person: &Person) -> Value {
+    let mut author = Map::new();
+    insert(&mut author, "name", person.name.as_deref());
+    let mailto = person.email.as_ref().map(|email| format!("mailto:{}", email));
+    insert(&mut author, "url", person.uri.as_deref().or(mailto.as_deref()));
+    Value::Object(author)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Category, Enclosure, Guid, MimeType, RssParser};
+    use std::io::Cursor;
+
+    fn channel() -> ChannelInfo {
+        let mut channel = ChannelInfo::default();
+        channel.title = Some("Tom & Jerry".to_string());
+        channel.link = Some("https://example.com/".to_string());
+        channel.description = Some("<b>News</b>".to_string());
+        channel.language = Some("en".to_string());
+        channel
+    }
+
+    #[tokio::test]
+    async fn test_rss_round_trip() {
+        let item = RssItem {
+            title: Some("One < two".to_string()),
+            link: Some("https://example.com/1?a=1&b=2".to_string()),
+            content: Some("<p>Body</p>".to_string()),
+            author: Some(Person::named("Jane")),
+            categories: vec![Category {
+                name: "Tech".to_string(),
+                domain: Some("https://example.com/topics".to_string()),
+            }],
+            enclosure: Some(Enclosure {
+                url: "https://example.com/1.mp3".to_string(),
+                length: Some(42),
+                mime_type: MimeType::parse("audio/mpeg"),
+            }),
+            guid: Some(Guid {
+                value: "item-1".to_string(),
+                is_permalink: false,
+            }),
+            pub_date: Some("Mon, 01 Jan 2024 00:00:00 GMT".to_string()),
+            language: Some("en".to_string()),
+            ..RssItem::default()
+        };
+
+        let mut info = channel();
+        info.ttl = Some(60);
+        info.skip_hours = vec![0, 23];
+        info.skip_days = vec!["Sunday".to_string()];
+        let mut writer = RssWriter::new(Vec::new(), &info).await.unwrap();
+        let items = tokio_stream::iter(vec![Ok(item.clone()), Ok(RssItem::default())]);
+        assert_eq!(writer.write_stream(items).await.unwrap(), 2);
+        let xml = writer.finish().await.unwrap();
+
+        let mut parser = RssParser::<RssItem, _>::new(Cursor::new(xml)).await.unwrap();
+        assert_eq!(parser.channel::<ChannelInfo>().await.unwrap(), info);
+        assert_eq!(parser.next().await.unwrap().unwrap(), item);
+        assert!(parser.next().await.unwrap().is_some());
+        assert!(parser.next().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_ndjson() {
+        let feed = r#"<rss><channel>
+<item><title>One</title><enclosure url="https://example.com/1.mp3" type="audio/mpeg"/></item>
+<item><title>Two</title></item>
+</channel></rss>"#;
+        let parser = RssParser::<RssItem, _>::new(Cursor::new(feed.as_bytes())).await.unwrap();
+        let mut out = Vec::new();
+        assert_eq!(parser.into_stream().to_ndjson(&mut out).await.unwrap(), 2);
+
+        let lines: Vec<Value> = out.split(|byte| *byte == b'\n').filter(|line| !line.is_empty()).map(|line| serde_json::from_slice(line).unwrap()).collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["title"], "One");
+        assert_eq!(lines[0]["enclosure"]["mime_type"], "audio/mpeg");
+        assert_eq!(lines[1]["media"]["contents"], json!([]));
+        assert!(lines[1]["media"].get("pending_content").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_atom_round_trip() {
+        let mut entry = AtomEntry::default();
+        entry.id = Some("urn:1".to_string());
+        entry.title = Some("Entry".to_string());
+        entry.link = Some("https://example.com/1".to_string());
+        entry.links = vec![crate::Link {
+            rel: "alternate".to_string(),
+            href: "https://example.com/1".to_string(),
+            mime_type: None,
+            title: None,
+        }];
+        entry.content = Some("<p>Body &amp; more</p>".to_string());
+        entry.authors = vec![Person::named("Jane")];
+        entry.updated = Some("2024-01-01T00:00:00Z".to_string());
+        entry.language = Some("de".to_string());
+
+        let mut writer = AtomWriter::new(Vec::new(), &channel()).await.unwrap();
+        writer.write_item(&entry).await.unwrap();
+        let xml = writer.finish().await.unwrap();
+        assert!(String::from_utf8_lossy(&xml).contains("<entry xml:lang=\"de\">"));
+
+        let mut parser = RssParser::<AtomEntry, _>::new_atom(Cursor::new(xml)).await.unwrap();
+        let feed: ChannelInfo = parser.channel().await.unwrap();
+        assert_eq!(feed.title.as_deref(), Some("Tom & Jerry"));
+        assert_eq!(feed.link.as_deref(), Some("https://example.com/"));
+        assert_eq!(parser.next().await.unwrap().unwrap(), entry);
+    }
+}
@@ -0,0 +1,69 @@
+use std::io::Cursor;
+
+use rss_parser::{GradualRssItem, RssParser};
+
+#[derive(Debug, GradualRssItem)]
+struct Episode {
+    title: Option<String>,
+    #[rss(tag = "pubDate")]
+    published: Option<String>,
+    #[rss(cdata)]
+    description: Option<String>,
+    #[rss(tag = "enclosure", attr = "url")]
+    audio_url: Option<String>,
+    #[rss(tag = "enclosure", attr = "length")]
+    audio_length: Option<u64>,
+    #[rss(tag = "category")]
+    categories: Vec<String>,
+    #[rss(tag = "itunes:episode")]
+    number: u32,
+    #[rss(skip)]
+    read: bool,
+}
+
+const FEED: &str = r#"<rss version="2.0"><channel>
+    <item>
+        <title>Pilot</title>
+        <pubDate>Mon, 01 Jan 2024 00:00:00 GMT</pubDate>
+        <description><![CDATA[<p>First one</p>]]></description>
+        <enclosure url="https://example.com/1.mp3" length="2048" type="audio/mpeg"/>
+        <category>Comedy</category>
+        <category>Talk</category>
+        <itunes:episode>1</itunes:episode>
+    </item>
+    <item>
+        <title>Second</title>
+        <description>plain text, not CDATA</description>
+        <enclosure url="https://example.com/2.mp3" length="unknown"/>
+        <itunes:episode>two</itunes:episode>
+    </item>
+</channel></rss>"#;
+
+#[tokio::test]
+async fn derive_maps_fields_to_elements() {
+    let mut parser = RssParser::<Episode, _>::new(Cursor::new(FEED.as_bytes())).await.unwrap();
+
+    let first = parser.next().await.unwrap().unwrap();
+    assert_eq!(first.title.as_deref(), Some("Pilot"));
+    assert_eq!(first.published.as_deref(), Some("Mon, 01 Jan 2024 00:00:00 GMT"));
+    assert_eq!(first.description.as_deref(), Some("<p>First one</p>"));
+    assert_eq!(first.audio_url.as_deref(), Some("https://example.com/1.mp3"));
+    assert_eq!(first.audio_length, Some(2048));
+    assert_eq!(first.categories, vec!["Comedy".to_string(), "Talk".to_string()]);
+    assert_eq!(first.number, 1);
+    assert!(!first.read);
+}
+
+#[tokio::test]
+async fn derive_ignores_unparsable_and_mismatched_values() {
+    let mut parser = RssParser::<Episode, _>::new(Cursor::new(FEED.as_bytes())).await.unwrap();
+    parser.next().await.unwrap().unwrap();
+
+    let second = parser.next().await.unwrap().unwrap();
+    assert_eq!(second.title.as_deref(), Some("Second"));
+    assert!(second.description.is_none());
+    assert_eq!(second.audio_url.as_deref(), Some("https://example.com/2.mp3"));
+    assert!(second.audio_length.is_none());
+    assert!(second.categories.is_empty());
+    assert_eq!(second.number, 0);
+}